@@ -0,0 +1,247 @@
+/*
+Dispatcher throughput, one run per PackFilter variant. Every run subscribes the same number of
+modules with that filter, replays a fixed mix of pre-built frames through a capture source that
+never touches the network, and reports how many frames a second the dispatcher got through along
+with how many deliveries that came to. Each filter is run once dispatching on the capture thread and
+once with FrameworkConfig::dispatch_workers, to compare the two. Run with
+
+    cargo bench --bench dispatch -- [modules] [frames] [workers]
+
+which defaults to 4 modules, 200000 frames and 4 workers. The frame mix is IPv4 UDP, IPv4 TCP, ARP,
+IPv6 ICMPv6 and an unknown ethertype from each of HOSTS hosts, so every filter does some rejecting
+as well as some matching and the workers have conversations to split between them.
+*/
+
+extern crate librustneedle;
+extern crate pnet;
+
+use std::env;
+use std::io;
+use std::net::{
+    Ipv4Addr,
+    Ipv6Addr
+};
+use std::sync::{
+    Arc,
+    mpsc::channel,
+    mpsc::Receiver,
+    mpsc::RecvTimeoutError
+};
+use std::thread;
+use std::time::{
+    Duration,
+    Instant
+};
+
+use pnet::datalink::MacAddr;
+
+use librustneedle::packet::{
+    ethernet,
+    icmpv6,
+    ipv4,
+    ipv4::IpProtocol,
+    ipv6
+};
+use librustneedle::{
+    ChannelPolicy,
+    DstClass,
+    Framework,
+    FrameworkConfig,
+    FrameReceiver,
+    Hook,
+    HookEnv,
+    HostMgr,
+    KnownPair,
+    MacField,
+    Module,
+    ModuleOutcome,
+    PackFilter,
+    PacketSource,
+    BROADCAST
+};
+
+const DEFAULT_MODULES: usize = 4;
+const DEFAULT_FRAMES: usize = 200_000;
+const DEFAULT_WORKERS: usize = 4;
+
+/// hosts the frame mix comes from
+const HOSTS: u8 = 16;
+
+const FILTERS: &[&str] = &[
+    "entire",
+    "etherframe",
+    "payload",
+    "ipprotocol",
+    "udpports",
+    "icmpv6",
+    "dstclass",
+    "ethertype",
+    "macmatch",
+    "bounded"
+];
+
+/// hands out frames round robin until it has given out total, then reports the end of the capture
+struct ReplaySource {
+    frames: Vec<Vec<u8>>,
+    given: usize,
+    total: usize
+}
+
+impl PacketSource for ReplaySource {
+    fn next_frame(&mut self) -> io::Result<&[u8]> {
+        if self.given == self.total {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "replay finished"));
+        }
+
+        self.given += 1;
+        Ok(&self.frames[self.given % self.frames.len()])
+    }
+}
+
+fn main() {
+    // cargo passes --bench along to benches without the test harness
+    let numbers: Vec<usize> = env::args().skip(1)
+        .filter(|arg| !arg.starts_with("--"))
+        .map(|arg| arg.parse().expect("usage: dispatch [modules] [frames] [workers]"))
+        .collect();
+
+    let modules = numbers.first().cloned().unwrap_or(DEFAULT_MODULES);
+    let frames = numbers.get(1).cloned().unwrap_or(DEFAULT_FRAMES);
+    let workers = numbers.get(2).cloned().unwrap_or(DEFAULT_WORKERS);
+    let mix = frame_mix();
+
+    println!("{} modules, {} frames, {} workers\n", modules, frames, workers);
+    println!("{:<12} {:>8} {:>14} {:>16} {:>12}", "filter", "workers", "frames/s", "deliveries/s", "delivered");
+
+    for filter in FILTERS {
+        for &workers in &[1, workers] {
+            let (elapsed, delivered) = run(filter, modules, frames, workers, &mix);
+            let secs = elapsed.as_secs_f64();
+
+            println!(
+                "{:<12} {:>8} {:>14.0} {:>16.0} {:>12}",
+                filter,
+                workers,
+                frames as f64 / secs,
+                delivered as f64 / secs,
+                delivered
+            );
+        }
+    }
+}
+
+/// time taken to dispatch frames with modules subscribed through filter, and how many frames they were handed
+fn run(filter: &str, modules: usize, frames: usize, workers: usize, mix: &[Vec<u8>]) -> (Duration, u64) {
+    let me = KnownPair::new(Ipv4Addr::new(10, 0, 0, 2), BROADCAST);
+    let gateway = KnownPair::new(Ipv4Addr::new(10, 0, 0, 1), BROADCAST);
+    let config = FrameworkConfig::default().dispatch_workers(workers);
+    let mut framework = Framework::with_config(HostMgr::new(gateway, me), config);
+    let _ = framework.set_logger(Box::new(|_| ())); // module start lines would break up the table
+
+    framework.hook_up("bench", Hook::Framework(counter)).unwrap();
+
+    for _ in 0..modules {
+        framework.try_run_hook("bench", &[filter]).unwrap();
+    }
+
+    let dispatcher = framework.dispatcher();
+    let started = Instant::now();
+
+    framework.start_capture(ReplaySource {
+        frames: mix.to_vec(),
+        given: 0,
+        total: frames
+    });
+
+    while dispatcher.frames_received() < frames as u64 {
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    let elapsed = started.elapsed();
+    let names: Vec<String> = framework.modules().keys().cloned().collect();
+    let delivered = names.iter().map(|name| framework.modules()[name].packets()).sum();
+
+    for name in names {
+        let _ = framework.stop_module(&name);
+    }
+
+    framework.shutdown();
+    (elapsed, delivered)
+}
+
+/// a module that drains whatever the named filter matches and does nothing else with it
+fn counter(args: &[&str], _env: &HookEnv, _framework: &mut Framework) -> Result<Option<Module>, String> {
+    let (killer, killed) = channel();
+    let (frames_tx, frames) = channel();
+
+    let filter = match args.first().cloned() {
+        Some("entire") => PackFilter::Entire(frames_tx),
+        Some("etherframe") => PackFilter::EtherFrame(frames_tx),
+        Some("payload") => PackFilter::Payload(frames_tx),
+        Some("ipprotocol") => PackFilter::IpProtocol(IpProtocol::Udp, frames_tx),
+        Some("udpports") => PackFilter::UdpPorts(vec![53], frames_tx),
+        Some("icmpv6") => PackFilter::Icmpv6(frames_tx),
+        Some("dstclass") => PackFilter::DstClass(vec![DstClass::Broadcast], frames_tx),
+        Some("ethertype") => PackFilter::EtherType { ethertype: ethernet::ETHERTYPE_ARP, sender: frames_tx },
+        Some("macmatch") => PackFilter::mac_match(MacAddr(0x02, 0, 0, 0, 0, 0), 24, MacField::Src, frames_tx)?,
+
+        Some("bounded") => {
+            let (filter, frames) = PackFilter::bounded(1024, ChannelPolicy::DropOldest, PackFilter::Entire);
+            let handle = thread::spawn(move || drain_bounded(frames, killed));
+            return Ok(Some(Module::new(handle, killer).with_filter(filter)));
+        },
+
+        _ => return Err(String::from("usage: bench <filter>"))
+    };
+
+    let handle = thread::spawn(move || drain(frames, killed));
+    Ok(Some(Module::new(handle, killer).with_filter(filter)))
+}
+
+fn drain(frames: Receiver<Arc<Vec<u8>>>, killed: Receiver<()>) -> Result<ModuleOutcome, String> {
+    while killed.try_recv().is_err() {
+        match frames.recv_timeout(Duration::from_millis(50)) {
+            Ok(_) | Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => break
+        }
+    }
+
+    Ok(ModuleOutcome::new())
+}
+
+fn drain_bounded(frames: FrameReceiver, killed: Receiver<()>) -> Result<ModuleOutcome, String> {
+    while killed.try_recv().is_err() {
+        match frames.recv_timeout(Duration::from_millis(50)) {
+            Ok(_) | Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => break
+        }
+    }
+
+    Ok(ModuleOutcome::new())
+}
+
+fn frame_mix() -> Vec<Vec<u8>> {
+    (0..HOSTS).flat_map(host_frames).collect()
+}
+
+/// one frame of each kind in the mix, from host number n
+fn host_frames(n: u8) -> Vec<Vec<u8>> {
+    let host = MacAddr(0x02, 0, 0, 0, 0, 0x10 + n);
+    let (src, dst) = (Ipv4Addr::new(10, 0, 0, 16 + n), Ipv4Addr::new(10, 0, 0, 1));
+
+    let udp = [&[0xc3, 0x50, 0, 53, 0, 20, 0, 0][..], &[0u8; 12][..]].concat();
+    let tcp = [0u8; 20];
+    let arp = [0u8; 28];
+
+    let v6_src = Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 0x10 + u16::from(n));
+    let v6_dst = Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1);
+    let solicitation = icmpv6::build_neighbor_solicitation(v6_src, host, v6_dst);
+
+    vec![
+        ethernet::build_frame(host, host, ethernet::ETHERTYPE_IPV4, &[&ipv4::build_header(src, dst, IpProtocol::Udp, udp.len(), 64)[..], &udp[..]].concat()),
+        ethernet::build_frame(host, host, ethernet::ETHERTYPE_IPV4, &[&ipv4::build_header(src, dst, IpProtocol::Tcp, tcp.len(), 64)[..], &tcp[..]].concat()),
+        ethernet::build_frame(BROADCAST, host, ethernet::ETHERTYPE_ARP, &arp),
+        ethernet::build_frame(host, host, ethernet::ETHERTYPE_IPV6, &[&ipv6::build_header(v6_src, v6_dst, ipv6::NEXT_HEADER_ICMPV6, solicitation.len(), 255)[..], &solicitation[..]].concat()),
+        ethernet::build_frame(host, host, 0x88b5, &[0u8; 46])
+    ]
+}
@@ -0,0 +1,157 @@
+/*
+Host table contention, the read-heavy case the RwLock around NetPairList is for. Every run has a
+number of reader threads looking up the macs of the hosts in a /24 while one writer keeps learning
+new ones, once with the table behind a plain Mutex, as it used to be, and once through the RwLock
+HostMgr hands out, and reports how long the readers took and how many lookups a second that came to.
+Run with
+
+    cargo bench --bench hosts -- [readers] [lookups] [writes]
+
+which defaults to 8 readers doing 400000 lookups each against 2000 writes. Readers only get to run
+side by side on more than one CPU, on a single one both runs come out about the same.
+*/
+
+extern crate librustneedle;
+extern crate pnet;
+
+use std::env;
+use std::net::Ipv4Addr;
+use std::sync::{
+    Arc,
+    Barrier,
+    Mutex,
+    RwLock
+};
+use std::thread;
+use std::time::{
+    Duration,
+    Instant
+};
+
+use pnet::datalink::MacAddr;
+
+use librustneedle::subnet::Cidr;
+use librustneedle::{
+    HostMgr,
+    KnownPair,
+    NetPairList,
+    BROADCAST
+};
+
+const DEFAULT_READERS: usize = 8;
+const DEFAULT_LOOKUPS: usize = 400_000;
+const DEFAULT_WRITES: usize = 2000;
+
+/// the two ways of sharing the table being compared
+#[derive(Clone)]
+enum Table {
+    Mutex(Arc<Mutex<NetPairList>>),
+    RwLock(Arc<RwLock<NetPairList>>)
+}
+
+impl Table {
+    fn lookup(&self, host: &Ipv4Addr) -> Option<MacAddr> {
+        match *self {
+            Table::Mutex(ref table) => table.lock().unwrap().macs().get(host).cloned().unwrap_or(None),
+            Table::RwLock(ref table) => table.read().unwrap().macs().get(host).cloned().unwrap_or(None)
+        }
+    }
+
+    fn learn(&self, host: Ipv4Addr, mac: MacAddr) {
+        match *self {
+            Table::Mutex(ref table) => table.lock().unwrap().set_host(host, mac),
+            Table::RwLock(ref table) => table.write().unwrap().set_host(host, mac)
+        }
+    }
+}
+
+fn main() {
+    // cargo passes --bench along to benches without the test harness
+    let numbers: Vec<usize> = env::args().skip(1)
+        .filter(|arg| !arg.starts_with("--"))
+        .map(|arg| arg.parse().expect("usage: hosts [readers] [lookups] [writes]"))
+        .collect();
+
+    let readers = numbers.first().cloned().unwrap_or(DEFAULT_READERS);
+    let lookups = numbers.get(1).cloned().unwrap_or(DEFAULT_LOOKUPS);
+    let writes = numbers.get(2).cloned().unwrap_or(DEFAULT_WRITES);
+
+    println!("{} readers, {} lookups each, {} writes\n", readers, lookups, writes);
+    println!("{:<8} {:>12} {:>14}", "lock", "elapsed", "lookups/s");
+
+    let cidr = Cidr::parse("10.0.0.0/24").unwrap();
+
+    let hosts = HostMgr::new(KnownPair::new(Ipv4Addr::new(10, 0, 0, 1), BROADCAST), KnownPair::new(Ipv4Addr::new(10, 0, 0, 2), BROADCAST));
+    *hosts.write_nethosts() = NetPairList::from_cidr(cidr);
+
+    let tables = [
+        ("mutex", Table::Mutex(Arc::new(Mutex::new(NetPairList::from_cidr(cidr))))),
+        ("rwlock", Table::RwLock(hosts.get_nethosts()))
+    ];
+
+    for &(name, ref table) in &tables {
+        let elapsed = run(table, readers, lookups, writes);
+
+        println!(
+            "{:<8} {:>10.1}ms {:>14.0}",
+            name,
+            elapsed.as_secs_f64() * 1000.0,
+            (readers * lookups) as f64 / elapsed.as_secs_f64()
+        );
+    }
+}
+
+/// how long readers threads took to do lookups each while one writer made writes changes
+fn run(table: &Table, readers: usize, lookups: usize, writes: usize) -> Duration {
+    // everyone starts together, so the readers really do contend with each other and the writer
+    let start = Arc::new(Barrier::new(readers + 2));
+    let mut handles = Vec::with_capacity(readers);
+
+    for reader in 0..readers {
+        let table = table.clone();
+        let start = start.clone();
+
+        handles.push(thread::spawn(move || {
+            start.wait();
+
+            let mut found = 0;
+
+            for i in 0..lookups {
+                if table.lookup(&host((reader + i) % 254)).is_some() {
+                    found += 1;
+                }
+            }
+
+            found
+        }));
+    }
+
+    let writer = {
+        let table = table.clone();
+        let start = start.clone();
+
+        thread::spawn(move || {
+            start.wait();
+
+            for i in 0..writes {
+                table.learn(host(i % 254), MacAddr(0x02, 0, 0, 0, (i >> 8) as u8, i as u8));
+            }
+        })
+    };
+
+    start.wait();
+    let started = Instant::now();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let elapsed = started.elapsed();
+    writer.join().unwrap();
+    elapsed
+}
+
+/// the nth host of the /24
+fn host(n: usize) -> Ipv4Addr {
+    Ipv4Addr::new(10, 0, 0, 1 + n as u8)
+}
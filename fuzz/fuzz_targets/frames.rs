@@ -0,0 +1,181 @@
+/*
+Feeds arbitrary bytes to everything that reads frames off the wire: the dispatcher, with a module
+subscribed through every kind of PackFilter, and each parser in packet, both on the bytes as given
+and on whatever the layer below hands up. Frames are attacker controlled, so none of it may panic on
+a short or malformed one. Run with
+
+    cargo fuzz run frames
+
+from the repository root, which needs cargo-fuzz and a nightly toolchain.
+*/
+
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate librustneedle;
+extern crate pnet;
+
+use std::net::Ipv4Addr;
+use std::sync::{
+    Arc,
+    mpsc::channel
+};
+use std::thread;
+
+use pnet::datalink::MacAddr;
+
+use librustneedle::packet::{
+    self,
+    arp,
+    dhcp,
+    ethernet,
+    http,
+    icmp,
+    icmpv6,
+    ipv4,
+    ipv4::IpProtocol,
+    ipv6,
+    radiotap,
+    tcp,
+    udp
+};
+use librustneedle::{
+    ChannelPolicy,
+    Dispatcher,
+    DstClass,
+    Framework,
+    Hook,
+    HookEnv,
+    HostMgr,
+    KnownPair,
+    MacField,
+    Module,
+    ModuleOutcome,
+    PackFilter,
+    BROADCAST
+};
+
+const FILTERS: &[&str] = &[
+    "entire",
+    "etherframe",
+    "payload",
+    "ipprotocol",
+    "udpports",
+    "icmpv6",
+    "dstclass",
+    "ethertype",
+    "macmatch",
+    "macmatch-partial"
+];
+
+thread_local! {
+    // the Framework owns the modules, and is kept for as long as the fuzzer runs
+    static DISPATCHER: Arc<Dispatcher> = {
+        let framework = Box::leak(Box::new(framework()));
+        framework.dispatcher()
+    };
+}
+
+fuzz_target!(|frame: &[u8]| {
+    DISPATCHER.with(|dispatcher| dispatcher.dispatch(frame));
+
+    parse_frame(frame);
+
+    // every layer also gets the bytes raw, in case the one below would never hand them up
+    parse_ip(frame);
+    parse_l4(frame);
+});
+
+fn framework() -> Framework {
+    let me = KnownPair::new(Ipv4Addr::new(10, 0, 0, 2), BROADCAST);
+    let gateway = KnownPair::new(Ipv4Addr::new(10, 0, 0, 1), BROADCAST);
+    let mut framework = Framework::new(HostMgr::new(gateway, me));
+    let _ = framework.set_logger(Box::new(|_| ()));
+
+    framework.hook_up("fuzz", Hook::Framework(sink)).unwrap();
+
+    for filter in FILTERS {
+        framework.try_run_hook("fuzz", &[filter]).unwrap();
+    }
+
+    framework
+}
+
+/// a module subscribed through the named filter, which never reads what it is sent
+/// bounded so that frames it is sent are dropped rather than piling up
+fn sink(args: &[&str], _env: &HookEnv, _framework: &mut Framework) -> Result<Option<Module>, String> {
+    let filter = args[0];
+
+    let (filter, frames) = PackFilter::bounded(16, ChannelPolicy::DropOldest, |frames_tx| match filter {
+        "entire" => PackFilter::Entire(frames_tx),
+        "etherframe" => PackFilter::EtherFrame(frames_tx),
+        "payload" => PackFilter::Payload(frames_tx),
+        "ipprotocol" => PackFilter::IpProtocol(IpProtocol::Tcp, frames_tx),
+        "udpports" => PackFilter::UdpPorts(vec![53, 67, 68], frames_tx),
+        "icmpv6" => PackFilter::Icmpv6(frames_tx),
+        "dstclass" => PackFilter::DstClass(vec![DstClass::Multicast], frames_tx),
+        "ethertype" => PackFilter::EtherType { ethertype: ethernet::ETHERTYPE_ARP, sender: frames_tx },
+        "macmatch" => PackFilter::mac_match(MacAddr(2, 0, 0, 0, 0, 0), 48, MacField::Dst, frames_tx).unwrap(),
+        _ => PackFilter::mac_match(MacAddr(2, 0, 0, 0, 0, 0), 20, MacField::Src, frames_tx).unwrap()
+    });
+
+    let (killer, killed) = channel::<()>();
+
+    let handle = thread::spawn(move || {
+        let _frames = frames;
+        let _ = killed.recv();
+        Ok(ModuleOutcome::new())
+    });
+
+    Ok(Some(Module::new(handle, killer).with_filter(filter)))
+}
+
+fn parse_frame(frame: &[u8]) {
+    let _ = ethernet::ethertype(frame);
+    let _ = ethernet::inner_ethertype(frame);
+    let _ = DstClass::of(frame);
+    let _ = arp::parse(frame);
+    let _ = arp::parse_reply(frame);
+    let _ = radiotap::strip(frame);
+
+    if let Some(packet) = frame.get(librustneedle::ETHER_HEADER_LEN..) {
+        parse_ip(packet);
+    }
+}
+
+fn parse_ip(packet: &[u8]) {
+    let _ = packet::checksum(packet);
+
+    if let Some((_, payload)) = ipv4::parse(packet) {
+        parse_l4(payload);
+    }
+
+    if let Some((_, payload)) = ipv6::parse(packet) {
+        let _ = icmpv6::parse_neighbor_advertisement(payload);
+    }
+}
+
+fn parse_l4(segment: &[u8]) {
+    let _ = icmp::parse(segment);
+    let _ = icmp::checksum_ok(segment);
+    let _ = icmpv6::parse_neighbor_advertisement(segment);
+
+    if let Some((_, payload)) = tcp::parse(segment) {
+        let _ = http::parse_request(payload);
+        let _ = http::form_fields(payload);
+    }
+
+    let mut streams = tcp::Reassembler::new(4096);
+    let _ = streams.push_segment(Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2), segment);
+
+    if let Some((_, payload)) = udp::parse(segment) {
+        let _ = dhcp::parse(payload);
+    }
+
+    let _ = dhcp::parse(segment);
+    let _ = http::parse_request(segment);
+
+    if let Ok(text) = ::std::str::from_utf8(segment) {
+        let _ = http::basic_credentials(text);
+    }
+}
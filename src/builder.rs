@@ -0,0 +1,186 @@
+/*
+The usual shape of a module, done once. Most modules are a thread that takes frames off their
+filter's channel one at a time until the killer fires or the dispatcher lets go of the channel, and
+each writes out the same channels, the same receive loop with a timeout so the killer is noticed on
+a quiet link, and the same Module::new(...).with_filter(...) to package it. ModuleBuilder does all of
+that, leaving a module only the filter it wants and what it does with each frame:
+
+    let logger = framework.logger();
+    let module = ModuleBuilder::new(|tx| PackFilter::IpProtocol(IpProtocol::Icmp, tx))
+        .run(move |packet| {
+            logger.log(&format!("{} bytes of ICMP", packet.len()));
+            Ok(())
+        });
+
+A handler returning Err ends the module with that error. Modules keeping state to report once killed
+use run_with, which hands the state to the handler on every frame and to finish at the end. Either
+way the module acknowledges its stop as it returns, see Module::with_ack.
+
+forward is the receive, modify, send loop at the heart of a man in the middle: every frame is handed
+to a transform that may rewrite it in place and says whether it goes back out, and the ones that do
+are queued with their macs rewritten to carry them on toward the real destination. A transform that
+changes a payload has to fix the checksums it broke, or the receiver quietly drops the frame:
+packet::checksum is the sum IPv4 headers and ICMP use as is, and TCP and UDP over a pseudo header
+of the addresses, protocol and length ahead of the segment.
+*/
+
+use std::sync::{
+    Arc,
+    mpsc::channel,
+    mpsc::Receiver,
+    mpsc::RecvTimeoutError,
+    mpsc::Sender
+};
+use std::thread;
+use std::time::Duration;
+
+use pnet::datalink::MacAddr;
+
+use channel::{
+    ChannelPolicy,
+    FrameReceiver
+};
+use dispatch::ETHER_HEADER_LEN;
+use {
+    Module,
+    ModuleOutcome,
+    PackFilter
+};
+
+/// how long the loop waits on a quiet filter before checking its killer again
+const DEFAULT_POLL: Duration = Duration::from_millis(250);
+
+/// whichever kind of channel the filter delivers into
+enum Frames {
+    Plain(Receiver<Arc<Vec<u8>>>),
+    Bounded(FrameReceiver)
+}
+
+impl Frames {
+    fn recv_timeout(&self, timeout: Duration) -> Result<Arc<Vec<u8>>, RecvTimeoutError> {
+        match *self {
+            Frames::Plain(ref frames) => frames.recv_timeout(timeout),
+            Frames::Bounded(ref frames) => frames.recv_timeout(timeout)
+        }
+    }
+}
+
+/// a module's filter and channels, waiting for the handler that makes it a Module
+pub struct ModuleBuilder {
+    filter: PackFilter,
+    frames: Frames,
+    poll: Duration
+}
+
+impl ModuleBuilder {
+    /// a module fed by the filter make builds around the channel it is given
+    pub fn new<F: FnOnce(Sender<Arc<Vec<u8>>>) -> PackFilter>(make: F) -> ModuleBuilder {
+        let (frames_tx, frames) = channel();
+
+        ModuleBuilder {
+            filter: make(frames_tx),
+            frames: Frames::Plain(frames),
+            poll: DEFAULT_POLL
+        }
+    }
+
+    /// like new, delivering into a bounded channel, see PackFilter::bounded
+    pub fn bounded<F>(capacity: usize, policy: ChannelPolicy, make: F) -> ModuleBuilder
+        where F: FnOnce(Sender<Arc<Vec<u8>>>) -> PackFilter
+    {
+        let (filter, frames) = PackFilter::bounded(capacity, policy, make);
+
+        ModuleBuilder {
+            filter,
+            frames: Frames::Bounded(frames),
+            poll: DEFAULT_POLL
+        }
+    }
+
+    /// how long to wait on a quiet filter before checking for the killer, 250ms unless set
+    pub fn poll(mut self, poll: Duration) -> ModuleBuilder {
+        self.poll = poll;
+        self
+    }
+
+    /// spawns the module, calling handler with every frame its filter matches
+    /// the module reports an empty outcome once killed
+    pub fn run<H>(self, mut handler: H) -> Module
+        where H: FnMut(Arc<Vec<u8>>) -> Result<(), String> + Send + 'static
+    {
+        self.run_with((), move |_, packet| handler(packet), |_| ModuleOutcome::new())
+    }
+
+    /// spawns the module, handing state to handler along with every frame, then to finish once the
+    /// module is killed or its filter's channel is dropped, finish making the module's outcome
+    pub fn run_with<S, H, F>(self, state: S, mut handler: H, finish: F) -> Module
+        where S: Send + 'static,
+              H: FnMut(&mut S, Arc<Vec<u8>>) -> Result<(), String> + Send + 'static,
+              F: FnOnce(S) -> ModuleOutcome + Send + 'static
+    {
+        let (killer, killed) = channel();
+        let (ack, acks) = channel();
+        let frames = self.frames;
+        let poll = self.poll;
+
+        let handle = thread::spawn(move || {
+            let mut state = state;
+
+            while killed.try_recv().is_err() {
+                let handled = match frames.recv_timeout(poll) {
+                    Ok(packet) => handler(&mut state, packet),
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break
+                };
+
+                if let Err(err) = handled {
+                    let _ = ack.send(());
+                    return Err(err);
+                }
+            }
+
+            let outcome = finish(state);
+            let _ = ack.send(());
+            Ok(outcome)
+        });
+
+        Module::new(handle, killer).with_filter(self.filter).with_ack(acks)
+    }
+
+    /// spawns a forwarder, handing every frame to transform and queueing the ones it returns true for
+    /// sent from src to dst, see the header above; the filter has to deliver whole frames, Entire,
+    /// EtherType, DstClass, MacMatch or NewFlow, anything shorter, whether it arrived that way or
+    /// transform left it so, is dropped rather than forwarded
+    /// the module reports how many frames it forwarded and dropped once killed
+    pub fn forward<T>(self, queue: Sender<Vec<u8>>, src: MacAddr, dst: MacAddr, mut transform: T) -> Module
+        where T: FnMut(&mut Vec<u8>) -> bool + Send + 'static
+    {
+        let MacAddr(a, b, c, d, e, f) = dst;
+        let MacAddr(g, h, i, j, k, l) = src;
+        let macs = [a, b, c, d, e, f, g, h, i, j, k, l];
+
+        self.run_with((0u64, 0u64), move |&mut (ref mut forwarded, ref mut dropped), packet| {
+            let mut frame = packet.to_vec();
+
+            // checked again after transform, which is free to shorten the frame
+            if frame.len() < ETHER_HEADER_LEN || !transform(&mut frame) || frame.len() < ETHER_HEADER_LEN {
+                *dropped += 1;
+                return Ok(());
+            }
+
+            frame[..12].copy_from_slice(&macs);
+
+            if queue.send(frame).is_err() {
+                return Err(String::from("packet queue closed"));
+            }
+
+            *forwarded += 1;
+            Ok(())
+        }, |(forwarded, dropped)| {
+            let mut outcome = ModuleOutcome::new();
+            outcome.insert(String::from("forwarded"), forwarded.to_string());
+            outcome.insert(String::from("dropped"), dropped.to_string());
+            outcome
+        })
+    }
+}
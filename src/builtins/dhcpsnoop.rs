@@ -0,0 +1,126 @@
+/*
+dhcpsnoop
+
+Watches DHCP traffic and adds every client it sees to the host table, usually before the client has
+sent anything else. Server ACKs give the leased address; client REQUESTs give the address asked for
+along with the client's hostname, when it sends one. Runs until killed and reports each client it
+learned about, with the hostname where one was seen.
+*/
+
+use std::collections::BTreeMap;
+use std::net::Ipv4Addr;
+use std::sync::RwLock;
+
+use pnet::datalink::MacAddr;
+
+use packet::{
+    dhcp,
+    ipv4,
+    udp
+};
+use {
+    Framework,
+    HookEnv,
+    Logger,
+    Module,
+    ModuleBuilder,
+    ModuleOutcome,
+    ModuleOutput,
+    NetPairList,
+    PackFilter
+};
+use super::OUTPUT_LINES;
+
+/// what has been learned about one client
+#[derive(PartialEq)]
+struct Client {
+    mac: MacAddr,
+    hostname: Option<String>
+}
+
+pub(crate) fn dhcpsnoop(args: &[&str], env: &HookEnv, framework: &mut Framework) -> Result<Option<Module>, String> {
+    if !args.is_empty() {
+        return Err(String::from("usage: dhcpsnoop"));
+    }
+
+    // hooks are only ever run on interfaces the Framework has a HostMgr for
+    let nethosts = framework.hosts_for(&env.interface).unwrap().get_nethosts();
+    let logger = framework.logger();
+
+    let output = ModuleOutput::new(OUTPUT_LINES);
+    let learned = output.clone();
+
+    let module = ModuleBuilder::new(|tx| PackFilter::UdpPorts(vec![dhcp::SERVER_PORT, dhcp::CLIENT_PORT], tx))
+        .run_with(BTreeMap::new(), move |clients, packet| {
+            learn(clients, &packet, &nethosts, &logger, &learned);
+            Ok(())
+        }, report);
+
+    Ok(Some(module.with_output(output)))
+}
+
+/// adds the client a DHCP packet tells of to the host table, logging it if it is news
+fn learn(
+    clients: &mut BTreeMap<Ipv4Addr, Client>,
+    packet: &[u8],
+    nethosts: &RwLock<NetPairList>,
+    logger: &Logger,
+    output: &ModuleOutput
+) {
+    let message = match ipv4::parse(packet)
+        .and_then(|(_, datagram)| udp::parse(datagram))
+        .and_then(|(_, payload)| dhcp::parse(payload))
+    {
+        Some(message) => message,
+        None => return
+    };
+
+    let ip = match message.message_type {
+        Some(dhcp::ACK) => message.your_ip,
+        Some(dhcp::REQUEST) => message.requested_ip.unwrap_or(message.client_ip),
+        _ => return
+    };
+
+    if ip.is_unspecified() {
+        return;
+    }
+
+    {
+        let mut nethosts = nethosts.write().unwrap();
+        nethosts.insert(ip);
+        nethosts.set_host(ip, message.client_mac);
+    }
+
+    // a hostname is only sent with some messages, so one seen earlier is kept
+    let client = Client {
+        mac: message.client_mac,
+        hostname: message.hostname.or_else(|| clients.get(&ip).and_then(|known| known.hostname.clone()))
+    };
+
+    if clients.get(&ip) == Some(&client) {
+        return; // nothing new
+    }
+
+    let learned = format!("{} is {}{}", ip, client.mac, match client.hostname {
+        Some(ref name) => format!(" ({})", name),
+        None => String::new()
+    });
+
+    logger.log(&format!("[dhcpsnoop] {}", learned));
+    output.push(learned);
+
+    clients.insert(ip, client);
+}
+
+fn report(clients: BTreeMap<Ipv4Addr, Client>) -> ModuleOutcome {
+    let mut outcome = ModuleOutcome::new();
+    let seen: Vec<String> = clients.iter().map(|(ip, client)| match client.hostname {
+        Some(ref name) => format!("{}={}({})", ip, client.mac, name),
+        None => format!("{}={}", ip, client.mac)
+    }).collect();
+
+    outcome.insert(String::from("clients"), clients.len().to_string());
+    outcome.insert(String::from("seen"), seen.join(","));
+
+    outcome
+}
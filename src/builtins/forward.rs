@@ -0,0 +1,76 @@
+/*
+forward
+
+Transparent forwarding for a man in the middle. Once a poison has hosts sending their traffic to our
+mac, every IPv4 frame addressed to it that isn't for us is sent on to the real gateway unchanged, with
+our mac as its source, so the hosts notice nothing. Only the one direction is forwarded: traffic
+the gateway sends back to a poisoned host comes from the gateway's mac and is left alone. Built on
+ModuleBuilder::forward, which a plugin wanting to modify frames on the way through can use with a
+transform of its own. Runs until killed and reports how many frames went through.
+*/
+
+use std::net::Ipv4Addr;
+
+use pnet::datalink::MacAddr;
+
+use packet::{
+    ethernet,
+    ipv4
+};
+use {
+    Framework,
+    HookEnv,
+    MacField,
+    Module,
+    ModuleBuilder,
+    PackFilter,
+    SendPriority,
+    ETHER_HEADER_LEN
+};
+
+pub(crate) fn forward(args: &[&str], env: &HookEnv, framework: &mut Framework) -> Result<Option<Module>, String> {
+    if !args.is_empty() {
+        return Err(String::from("usage: forward"));
+    }
+
+    // forwarded traffic is someone's connection, it shouldn't wait behind scans
+    let queue = match framework.get_packet_queue_on(&env.interface, SendPriority::High) {
+        Ok(queue) => queue,
+        Err(err) => return Err(format!("forward: {}", err))
+    };
+
+    // hooks are only ever run on interfaces the Framework has a HostMgr for
+    let hosts = framework.hosts_for(&env.interface).unwrap();
+    let me = hosts.get_myself();
+    let me = me.lock().unwrap();
+    let gateway_mac = hosts.get_gateway().lock().unwrap().hardw;
+    let (my_ip, my_mac) = (me.proto, me.hardw);
+
+    if gateway_mac == my_mac {
+        return Err(String::from("forward: the gateway's mac isn't known yet"));
+    }
+
+    let module = ModuleBuilder::new(|tx| PackFilter::mac_match(my_mac, 48, MacField::Dst, tx).unwrap())
+        .forward(queue, my_mac, gateway_mac, move |frame| passing_through(frame, my_ip, gateway_mac));
+
+    Ok(Some(module))
+}
+
+/// whether frame, addressed to our mac, is someone else's traffic rather than our own
+fn passing_through(frame: &[u8], my_ip: Ipv4Addr, gateway_mac: MacAddr) -> bool {
+    let MacAddr(a, b, c, d, e, f) = gateway_mac;
+
+    // the gateway's own frames to us would only bounce straight back to it
+    if frame.get(6..12) == Some(&[a, b, c, d, e, f][..]) {
+        return false;
+    }
+
+    if ethernet::ethertype(frame) != Some(ethernet::ETHERTYPE_IPV4) {
+        return false;
+    }
+
+    match frame.get(ETHER_HEADER_LEN..).and_then(ipv4::parse) {
+        Some((ref header, _)) => header.dst != my_ip,
+        None => false
+    }
+}
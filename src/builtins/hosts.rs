@@ -0,0 +1,72 @@
+/*
+hosts
+
+Logs the host table of the interface the hook was run on, one listed host a line in address order:
+its mac or "pending" if none has been learned yet, the vendor that mac's OUI belongs to or "-" if
+it isn't known, and how long ago set_host last heard of the host or "never". Then how many were
+resolved and how many weren't, counted the way status counts them. Vendors come from oui.txt in
+the data directory on top of the few built in, see OuiTable; a file that can't be read is logged
+and the built in ones used alone. The table is copied out and the lock let go before anything is
+logged, so a scan waiting to write isn't held up by a slow logger. Starts no module.
+*/
+
+use std::time::Instant;
+
+use {
+    Framework,
+    HookEnv,
+    Module,
+    OuiTable
+};
+
+pub(crate) fn hosts(args: &[&str], env: &HookEnv, framework: &mut Framework) -> Result<Option<Module>, String> {
+    if !args.is_empty() {
+        return Err(String::from("usage: hosts"));
+    }
+
+    let (mut table, resolved) = {
+        let nethosts = match framework.hosts_for(&env.interface) {
+            Some(hosts) => hosts.read_nethosts(),
+            None => return Err(format!("{}: No such interface", env.interface))
+        };
+
+        let table: Vec<_> = nethosts.hosts().iter()
+            .map(|&host| (host, nethosts.macs().get(&host).cloned().unwrap_or(None), nethosts.last_seen(&host)))
+            .collect();
+
+        (table, nethosts.resolved_len())
+    };
+
+    table.sort_by_key(|&(host, _, _)| host);
+
+    let vendors = match OuiTable::load(&env.data_path("oui.txt")) {
+        Ok(vendors) => vendors,
+        Err(err) => {
+            framework.log(&format!("[!] hosts: {}", err));
+            OuiTable::builtin()
+        }
+    };
+
+    let now = Instant::now();
+
+    framework.log(&format!("[*] Hosts on {}:", env.interface));
+
+    for (host, mac, seen) in &table {
+        let seen = match *seen {
+            Some(seen) => format!("{}s ago", now.duration_since(seen).as_secs()),
+            None => String::from("never")
+        };
+
+        match *mac {
+            Some(mac) => {
+                let vendor = vendors.lookup(mac).unwrap_or("-");
+                framework.log(&format!("    {:<15} {:<17} {:<16} {}", host, mac, vendor, seen));
+            }
+            None => framework.log(&format!("    {:<15} {:<17} {:<16} {}", host, "pending", "-", seen))
+        }
+    }
+
+    framework.log(&format!("[*] {} hosts ({} resolved, {} unresolved)", table.len(), resolved, table.len() - resolved));
+
+    Ok(None)
+}
@@ -0,0 +1,75 @@
+/*
+Hooks that ship with the core rather than a plugin. They are written exactly like plugin hooks and
+only become available once the program calls Framework::load_builtins, so a frontend that wants
+none of them, or wants a plugin of its own under the same name, can leave them out.
+*/
+
+use Hook;
+
+/// findings a builtin keeps for Module::recent_output
+const OUTPUT_LINES: usize = 100;
+
+mod arpscan;
+mod creds;
+mod dhcpsnoop;
+mod forward;
+mod hosts;
+mod ndpscan;
+mod pingsweep;
+mod portscan;
+mod relay;
+mod replay;
+mod scan;
+mod status;
+mod talkers;
+mod toptalkers;
+
+/// capabilities load_builtins tags hooks with, see Framework::set_allowed_caps
+/// active hooks put probes on the wire, creds collects other people's passwords
+pub(crate) const CAPS: &[(&str, &str)] = &[
+    ("arpscan", "active"),
+    ("creds", "creds"),
+    ("forward", "active"),
+    ("ndpscan", "active"),
+    ("pingsweep", "active"),
+    ("portscan", "active"),
+    ("relay", "active")
+];
+
+/// categories load_builtins lists hooks under, see Framework::hooks_by_tag
+/// discovery hooks find hosts and services, mitm ones sit in the path of other hosts' traffic,
+/// passive ones only ever watch, offline ones work from a capture file rather than the link and
+/// info ones only report on the Framework's own state
+pub(crate) const TAGS: &[(&str, &str)] = &[
+    ("arpscan", "discovery"),
+    ("creds", "passive"),
+    ("dhcpsnoop", "passive"),
+    ("forward", "mitm"),
+    ("hosts", "info"),
+    ("ndpscan", "discovery"),
+    ("pingsweep", "discovery"),
+    ("portscan", "discovery"),
+    ("relay", "mitm"),
+    ("replay", "offline"),
+    ("status", "info"),
+    ("talkers", "passive"),
+    ("toptalkers", "passive")
+];
+
+pub(crate) fn hooks() -> Vec<(&'static str, Hook)> {
+    vec![
+        ("arpscan", Hook::Framework(arpscan::arpscan)),
+        ("creds", Hook::Framework(creds::creds)),
+        ("dhcpsnoop", Hook::Framework(dhcpsnoop::dhcpsnoop)),
+        ("forward", Hook::Framework(forward::forward)),
+        ("hosts", Hook::Framework(hosts::hosts)),
+        ("ndpscan", Hook::Framework(ndpscan::ndpscan)),
+        ("pingsweep", Hook::Framework(pingsweep::pingsweep)),
+        ("portscan", Hook::Framework(portscan::portscan)),
+        ("relay", Hook::Framework(relay::relay)),
+        ("replay", Hook::Framework(replay::replay)),
+        ("status", Hook::Framework(status::status)),
+        ("talkers", Hook::HostMgr(talkers::talkers)),
+        ("toptalkers", Hook::Framework(toptalkers::toptalkers))
+    ]
+}
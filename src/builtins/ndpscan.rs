@@ -0,0 +1,237 @@
+/*
+ndpscan <ipv6 prefix/len> [wait secs] [--shuffle] [--interval dur] [--jitter dur]
+
+The IPv6 counterpart of an ARP scan. Every address in the prefix is sent a neighbor solicitation at
+its solicited-node multicast group, from our EUI-64 link-local address, and each neighbor that
+advertises itself back is recorded with its mac in the HostMgr's neighbor table. Solicitations never
+leave the link, so the prefix has to be one the interface is on, and it is capped at /112 since
+anything wider can't be walked address by address. The scan options, and the progress and steering
+every discovery module shares, are described in builtins/scan.rs; added targets are single
+addresses, on the link like the rest.
+*/
+
+use std::collections::{
+    BTreeMap,
+    HashMap
+};
+use std::net::Ipv6Addr;
+use std::sync::{
+    Arc,
+    Mutex,
+    mpsc::channel,
+    mpsc::Receiver,
+    mpsc::Sender
+};
+use std::thread;
+use std::time::{
+    Duration,
+    Instant
+};
+
+use pnet::datalink::MacAddr;
+
+use packet::{
+    ethernet,
+    icmpv6,
+    ipv6
+};
+use super::scan::{
+    ScanOptions,
+    Targets,
+    SCAN_USAGE
+};
+use {
+    Framework,
+    HookEnv,
+    Module,
+    ModuleOutcome,
+    PackFilter,
+    SendPriority
+};
+
+const DEFAULT_WAIT_SECS: u64 = 3;
+const MIN_PREFIX: u8 = 112;
+const ND_HOP_LIMIT: u8 = 255; // anything else is dropped by the receiver, it proves the message never crossed a router
+const PROBE_GAP: Duration = Duration::from_millis(2);
+const SETTLE: Duration = Duration::from_millis(50);
+
+/// what the scan thread needs to build its solicitations
+struct Scan {
+    me: (Ipv6Addr, MacAddr),
+    neighbors: Arc<Mutex<HashMap<Ipv6Addr, MacAddr>>>,
+    queue: Sender<Vec<u8>>,
+    wait: Duration,
+    pacing: ScanOptions
+}
+
+pub(crate) fn ndpscan(args: &[&str], env: &HookEnv, framework: &mut Framework) -> Result<Option<Module>, String> {
+    let (mut pacing, args) = ScanOptions::parse(args, PROBE_GAP, env.rng.clone())?;
+
+    let (prefix, len) = match args.first() {
+        Some(prefix) => parse_prefix(prefix)?,
+        None => return Err(format!("usage: ndpscan <ipv6 prefix/len> [wait secs] {}", SCAN_USAGE))
+    };
+
+    let wait = match args.get(1) {
+        Some(secs) => match secs.parse::<u64>() {
+            Ok(secs) => Duration::from_secs(secs),
+            Err(_) => return Err(format!("{}: not a number of seconds", secs))
+        },
+
+        None => Duration::from_secs(DEFAULT_WAIT_SECS)
+    };
+
+    // probes can wait on anything more urgent queued alongside them
+    let queue = match framework.get_packet_queue_on(&env.interface, SendPriority::Low) {
+        Ok(queue) => queue,
+        Err(err) => return Err(format!("ndpscan: {}", err))
+    };
+
+    // hooks are only ever run on interfaces the Framework has a HostMgr for
+    let hosts = framework.hosts_for(&env.interface).unwrap();
+    let mac = hosts.get_myself().lock().unwrap().hardw;
+    let me = ipv6::link_local_for(mac);
+
+    let (killer, killed) = channel();
+    let (controls_tx, controls) = channel();
+    let mut targets = Targets::new(|target| target.parse::<Ipv6Addr>().ok(), killed, controls, env.reporter.clone());
+    let walked: Vec<Ipv6Addr> = addresses_in(prefix, len).filter(|&target| target != me).collect();
+    targets.queue(pacing.order(&walked));
+
+    let scan = Scan {
+        me: (me, mac),
+        neighbors: hosts.get_neighbors(),
+        queue,
+        wait,
+        pacing
+    };
+
+    let (replies_tx, replies) = channel();
+    let handle = thread::spawn(move || scan.run(replies, targets));
+
+    Ok(Some(Module::new(handle, killer)
+        .with_filter(PackFilter::Icmpv6(replies_tx))
+        .with_controls(controls_tx)))
+}
+
+/// addr/len with len no shorter than MIN_PREFIX, a bare address is taken as a /128
+fn parse_prefix(prefix: &str) -> Result<(Ipv6Addr, u8), String> {
+    let mut parts = prefix.splitn(2, '/');
+
+    let addr = match parts.next().unwrap_or("").parse::<Ipv6Addr>() {
+        Ok(addr) => addr,
+        Err(_) => return Err(format!("{}: not an IPv6 address", prefix))
+    };
+
+    let len = match parts.next() {
+        Some(len) => match len.parse::<u8>() {
+            Ok(len) if len <= 128 => len,
+            _ => return Err(format!("{}: not a prefix length", prefix))
+        },
+
+        None => 128
+    };
+
+    if len < MIN_PREFIX {
+        return Err(format!("{}: ndpscan only walks prefixes of /{} or longer", prefix, MIN_PREFIX));
+    }
+
+    Ok((addr, len))
+}
+
+/// every address in addr/len, len being at least MIN_PREFIX
+fn addresses_in(addr: Ipv6Addr, len: u8) -> impl Iterator<Item = Ipv6Addr> {
+    let host_bits = u32::from(128 - len);
+    let first = u128::from(addr) >> host_bits << host_bits;
+
+    (0..1u128 << host_bits).map(move |host| Ipv6Addr::from(first | host))
+}
+
+impl Scan {
+    fn run(mut self, replies: Receiver<Arc<Vec<u8>>>, mut targets: Targets<Ipv6Addr>) -> Result<ModuleOutcome, String> {
+        let mut found = BTreeMap::new(); // neighbor -> mac it advertised
+
+        // the filter is only subscribed once the hook returns, give that a moment before any reply can arrive
+        thread::sleep(SETTLE);
+
+        // targets added while waiting on the advertisements are solicited, then waited on in turn
+        loop {
+            while let Some(target) = targets.next() {
+                if self.queue.send(self.solicitation(target)).is_err() {
+                    return Err(String::from("ndpscan: packet queue closed"));
+                }
+
+                self.collect(&replies, &targets, &mut found);
+                self.pacing.pause();
+            }
+
+            let deadline = Instant::now() + self.wait;
+
+            while Instant::now() < deadline && !targets.stopped() && !targets.has_pending() {
+                if let Ok(reply) = replies.recv_timeout(Duration::from_millis(100)) {
+                    self.record(&reply, &targets, &mut found);
+                }
+            }
+
+            if targets.stopped() || !targets.has_pending() {
+                break;
+            }
+        }
+
+        self.collect(&replies, &targets, &mut found);
+        Ok(self.outcome(&found, &targets))
+    }
+
+    fn solicitation(&self, target: Ipv6Addr) -> Vec<u8> {
+        let group = ipv6::solicited_node(target);
+        let message = icmpv6::build_neighbor_solicitation(self.me.0, self.me.1, target);
+        let mut packet = ipv6::build_header(self.me.0, group, ipv6::NEXT_HEADER_ICMPV6, message.len(), ND_HOP_LIMIT).to_vec();
+        packet.extend_from_slice(&message);
+
+        ethernet::build_frame(ipv6::multicast_mac(group), self.me.1, ethernet::ETHERTYPE_IPV6, &packet)
+    }
+
+    /// records every advertisement that has already arrived
+    fn collect(&self, replies: &Receiver<Arc<Vec<u8>>>, targets: &Targets<Ipv6Addr>, found: &mut BTreeMap<Ipv6Addr, MacAddr>) {
+        for reply in replies.try_iter() {
+            self.record(&reply, targets, found);
+        }
+    }
+
+    fn record(&self, packet: &[u8], targets: &Targets<Ipv6Addr>, found: &mut BTreeMap<Ipv6Addr, MacAddr>) {
+        let (header, message) = match ipv6::parse(packet) {
+            Some(parsed) => parsed,
+            None => return
+        };
+
+        if header.hop_limit != ND_HOP_LIMIT {
+            return;
+        }
+
+        let advertisement = match icmpv6::parse_neighbor_advertisement(message) {
+            Some(advertisement) => advertisement,
+            None => return
+        };
+
+        // only answers to our own solicitations, for addresses we asked about
+        if !advertisement.solicited || !targets.contains(&advertisement.target) {
+            return;
+        }
+
+        if let Some(mac) = advertisement.target_mac {
+            found.insert(advertisement.target, mac);
+            self.neighbors.lock().unwrap().insert(advertisement.target, mac);
+        }
+    }
+
+    fn outcome(&self, found: &BTreeMap<Ipv6Addr, MacAddr>, targets: &Targets<Ipv6Addr>) -> ModuleOutcome {
+        let neighbors: Vec<String> = found.iter().map(|(host, mac)| format!("{}={}", host, mac)).collect();
+        let mut outcome = ModuleOutcome::new();
+
+        outcome.insert(String::from("probed"), targets.probed().len().to_string());
+        outcome.insert(String::from("found"), found.len().to_string());
+        outcome.insert(String::from("neighbors"), neighbors.join(","));
+
+        outcome
+    }
+}
@@ -0,0 +1,217 @@
+/*
+pingsweep <cidr> [wait secs] [--shuffle] [--interval dur] [--jitter dur]
+
+Sends an ICMP echo request to every host in cidr and records which ones answer. Unlike an ARP scan
+this reaches hosts past the gateway: any host whose mac isn't already in the host table is sent the
+request through the gateway, and the router takes it from there. Responders are added to the host
+table and reported in the module's outcome once every reply has had wait seconds to come back, each
+with an OS family guessed from the TTL its reply arrived with. The scan options, and the progress
+and steering every discovery module shares, are described in builtins/scan.rs; added targets are
+single addresses. cidr has to be a /16 or narrower.
+*/
+
+use std::collections::BTreeMap;
+use std::net::Ipv4Addr;
+use std::process;
+use std::sync::{
+    Arc,
+    RwLock,
+    mpsc::channel,
+    mpsc::Receiver,
+    mpsc::Sender
+};
+use std::thread;
+use std::time::{
+    Duration,
+    Instant
+};
+
+use pnet::datalink::MacAddr;
+
+use packet::{
+    ethernet,
+    icmp,
+    ipv4,
+    ipv4::IpProtocol
+};
+use super::scan::{
+    self,
+    ScanOptions,
+    Targets,
+    SCAN_USAGE
+};
+use subnet::{
+    self,
+    Cidr
+};
+use {
+    Framework,
+    HookEnv,
+    Module,
+    ModuleOutcome,
+    NetPairList,
+    PackFilter,
+    SendPriority
+};
+
+const DEFAULT_WAIT_SECS: u64 = 3;
+const PROBE_TTL: u8 = 64;
+const PROBE_GAP: Duration = Duration::from_millis(2);
+const SETTLE: Duration = Duration::from_millis(50);
+const PAYLOAD: &[u8] = b"rustneedle pingsweep";
+
+/// what the sweep thread needs to build its probes
+struct Sweep {
+    id: u16,
+    me: (Ipv4Addr, MacAddr),
+    gateway_mac: MacAddr,
+    nethosts: Arc<RwLock<NetPairList>>,
+    queue: Sender<Vec<u8>>,
+    wait: Duration,
+    pacing: ScanOptions
+}
+
+pub(crate) fn pingsweep(args: &[&str], env: &HookEnv, framework: &mut Framework) -> Result<Option<Module>, String> {
+    let (mut pacing, args) = ScanOptions::parse(args, PROBE_GAP, env.rng.clone())?;
+
+    let cidr = match args.first() {
+        Some(cidr) => Cidr::parse(cidr)?,
+        None => return Err(format!("usage: pingsweep <cidr> [wait secs] {}", SCAN_USAGE))
+    };
+
+    scan::check_width("pingsweep", &cidr)?;
+
+    let wait = match args.get(1) {
+        Some(secs) => match secs.parse::<u64>() {
+            Ok(secs) => Duration::from_secs(secs),
+            Err(_) => return Err(format!("{}: not a number of seconds", secs))
+        },
+
+        None => Duration::from_secs(DEFAULT_WAIT_SECS)
+    };
+
+    // probes can wait on anything more urgent queued alongside them
+    let queue = match framework.get_packet_queue_on(&env.interface, SendPriority::Low) {
+        Ok(queue) => queue,
+        Err(err) => return Err(format!("pingsweep: {}", err))
+    };
+
+    // hooks are only ever run on interfaces the Framework has a HostMgr for
+    let hosts = framework.hosts_for(&env.interface).unwrap();
+    let me = hosts.get_myself();
+    let me = me.lock().unwrap();
+    let gateway = hosts.get_gateway();
+
+    let (killer, killed) = channel();
+    let (controls_tx, controls) = channel();
+    let mut targets = Targets::new(|target| target.parse::<Ipv4Addr>().ok(), killed, controls, env.reporter.clone());
+    let swept: Vec<Ipv4Addr> = subnet::hosts_in(cidr).filter(|&host| host != me.proto).collect();
+    targets.queue(pacing.order(&swept));
+
+    let sweep = Sweep {
+        id: process::id() as u16, // tells our replies apart from anyone else's
+        me: (me.proto, me.hardw),
+        gateway_mac: gateway.lock().unwrap().hardw,
+        nethosts: hosts.get_nethosts(),
+        queue,
+        wait,
+        pacing
+    };
+
+    let (replies_tx, replies) = channel();
+    let handle = thread::spawn(move || sweep.run(replies, targets));
+
+    Ok(Some(Module::new(handle, killer)
+        .with_filter(PackFilter::IpProtocol(IpProtocol::Icmp, replies_tx))
+        .with_controls(controls_tx)))
+}
+
+impl Sweep {
+    fn run(mut self, replies: Receiver<Arc<Vec<u8>>>, mut targets: Targets<Ipv4Addr>) -> Result<ModuleOutcome, String> {
+        let mut live = BTreeMap::new(); // responder -> ttl its reply arrived with
+        let mut seq: u16 = 0;
+
+        // the filter is only subscribed once the hook returns, give that a moment before any reply can arrive
+        thread::sleep(SETTLE);
+
+        // targets added while waiting on the replies are probed, then waited on in turn
+        loop {
+            while let Some(target) = targets.next() {
+                let probe = self.probe(target, seq);
+                seq = seq.wrapping_add(1);
+
+                if self.queue.send(probe).is_err() {
+                    return Err(String::from("pingsweep: packet queue closed"));
+                }
+
+                self.collect(&replies, &mut live);
+                self.pacing.pause();
+            }
+
+            let deadline = Instant::now() + self.wait;
+
+            while Instant::now() < deadline && !targets.stopped() && !targets.has_pending() {
+                if let Ok(reply) = replies.recv_timeout(Duration::from_millis(100)) {
+                    self.record(&reply, &mut live);
+                }
+            }
+
+            if targets.stopped() || !targets.has_pending() {
+                break;
+            }
+        }
+
+        self.collect(&replies, &mut live);
+        Ok(self.outcome(&live, &targets))
+    }
+
+    fn probe(&self, target: Ipv4Addr, seq: u16) -> Vec<u8> {
+        let dst_mac = match self.nethosts.read().unwrap().macs().get(&target) {
+            Some(&Some(mac)) => mac,
+            _ => self.gateway_mac
+        };
+
+        let message = icmp::build_echo_request(self.id, seq, PAYLOAD);
+        let mut packet = ipv4::build_header(self.me.0, target, IpProtocol::Icmp, message.len(), PROBE_TTL).to_vec();
+        packet.extend_from_slice(&message);
+
+        ethernet::build_frame(dst_mac, self.me.1, ethernet::ETHERTYPE_IPV4, &packet)
+    }
+
+    /// records every reply that has already arrived
+    fn collect(&self, replies: &Receiver<Arc<Vec<u8>>>, live: &mut BTreeMap<Ipv4Addr, u8>) {
+        for reply in replies.try_iter() {
+            self.record(&reply, live);
+        }
+    }
+
+    fn record(&self, packet: &[u8], live: &mut BTreeMap<Ipv4Addr, u8>) {
+        let (header, payload) = match ipv4::parse(packet) {
+            Some(parsed) => parsed,
+            None => return
+        };
+
+        match icmp::parse(payload) {
+            Some(ref message) if message.kind == icmp::ECHO_REPLY && message.id == self.id && live.insert(header.src, header.ttl).is_none() => {
+                self.nethosts.write().unwrap().insert(header.src);
+            },
+
+            _ => ()
+        }
+    }
+
+    fn outcome(&self, live: &BTreeMap<Ipv4Addr, u8>, targets: &Targets<Ipv4Addr>) -> ModuleOutcome {
+        let responders: Vec<String> = live.keys().map(|host| host.to_string()).collect();
+        let guesses: Vec<String> = live.iter()
+            .map(|(host, &ttl)| format!("{}={}", host, ipv4::guess_os_from_ttl(ttl)))
+            .collect();
+        let mut outcome = ModuleOutcome::new();
+
+        outcome.insert(String::from("probed"), targets.probed().len().to_string());
+        outcome.insert(String::from("live"), live.len().to_string());
+        outcome.insert(String::from("hosts"), responders.join(","));
+        outcome.insert(String::from("os"), guesses.join(","));
+
+        outcome
+    }
+}
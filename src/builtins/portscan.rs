@@ -0,0 +1,277 @@
+/*
+portscan <ip> <ports> [wait secs] [--shuffle] [--interval dur] [--jitter dur]
+
+A SYN scan of a few ports on one host. ports is a comma separated list of ports and ranges, like
+22,80,8000-8100. Every port is sent a SYN from one source port picked for the scan, and whatever the
+host answers with decides what the port is: a SYN-ACK means open, a RST closed, and no answer within
+wait seconds (2 unless given) of the last SYN filtered. The connection is never completed, the
+host's SYN-ACK is answered with a RST by our own kernel since it never opened the connection. SYNs
+are paced by the scan options, see builtins/scan.rs, and sent to the host's mac if the host table
+has it and through the gateway otherwise. Open ports are reported as findings as they are found and
+all three lists end up in the module's outcome. Progress and steering work as for the other
+discovery modules, see builtins/scan.rs; added targets are single ports.
+*/
+
+use std::collections::BTreeSet;
+use std::net::{
+    Ipv4Addr,
+    SocketAddrV4
+};
+use std::sync::{
+    Arc,
+    mpsc::channel,
+    mpsc::Receiver,
+    mpsc::Sender
+};
+use std::thread;
+use std::time::{
+    Duration,
+    Instant
+};
+
+use pnet::datalink::MacAddr;
+
+use packet::{
+    ethernet,
+    ipv4,
+    ipv4::IpProtocol,
+    tcp
+};
+use super::scan::{
+    ScanOptions,
+    Targets,
+    SCAN_USAGE
+};
+use {
+    Framework,
+    HookEnv,
+    Module,
+    ModuleOutcome,
+    PackFilter,
+    Reporter,
+    SendPriority
+};
+
+const DEFAULT_WAIT_SECS: u64 = 2;
+const PROBE_TTL: u8 = 64;
+const PROBE_GAP: Duration = Duration::from_millis(5);
+const SETTLE: Duration = Duration::from_millis(50);
+const WINDOW: u16 = 1024;
+
+/// source ports are picked from the dynamic range, where they won't clash with anything listening
+const DYNAMIC_PORTS: u16 = 49152;
+
+/// what the scan thread needs to build its SYNs and read the answers
+struct Scan {
+    target: Ipv4Addr,
+    src_port: u16,
+    seq: u32,
+    me: (Ipv4Addr, MacAddr),
+    dst_mac: MacAddr,
+    queue: Sender<Vec<u8>>,
+    wait: Duration,
+    pacing: ScanOptions,
+    reporter: Reporter
+}
+
+/// what each port turned out to be, unanswered ports are filtered
+#[derive(Default)]
+struct Answers {
+    open: BTreeSet<u16>,
+    closed: BTreeSet<u16>
+}
+
+pub(crate) fn portscan(args: &[&str], env: &HookEnv, framework: &mut Framework) -> Result<Option<Module>, String> {
+    let usage = format!("usage: portscan <ip> <ports> [wait secs] {}", SCAN_USAGE);
+    let mut rng = env.rng.clone();
+    let (mut pacing, args) = ScanOptions::parse(args, PROBE_GAP, rng.split())?;
+
+    let (target, ports, wait) = match args[..] {
+        [target, ports] => (target, ports, None),
+        [target, ports, wait] => (target, ports, Some(wait)),
+        _ => return Err(usage)
+    };
+
+    let target = match target.parse::<Ipv4Addr>() {
+        Ok(target) => target,
+        Err(_) => return Err(format!("{}: not an IPv4 address", target))
+    };
+
+    let ports = parse_ports(ports)?;
+
+    let wait = match wait {
+        Some(secs) => match secs.parse::<u64>() {
+            Ok(secs) => Duration::from_secs(secs),
+            Err(_) => return Err(format!("{}: not a number of seconds", secs))
+        },
+
+        None => Duration::from_secs(DEFAULT_WAIT_SECS)
+    };
+
+    // SYNs can wait on anything more urgent queued alongside them
+    let queue = match framework.get_packet_queue_on(&env.interface, SendPriority::Low) {
+        Ok(queue) => queue,
+        Err(err) => return Err(format!("portscan: {}", err))
+    };
+
+    // hooks are only ever run on interfaces the Framework has a HostMgr for
+    let hosts = framework.hosts_for(&env.interface).unwrap();
+    let me = hosts.get_myself();
+    let me = me.lock().unwrap();
+
+    let dst_mac = match hosts.read_nethosts().macs().get(&target) {
+        Some(&Some(mac)) => mac,
+        _ => hosts.get_gateway().lock().unwrap().hardw
+    };
+
+    let (killer, killed) = channel();
+    let (controls_tx, controls) = channel();
+    let mut targets = Targets::new(|port| port.parse::<u16>().ok().filter(|&port| port > 0), killed, controls, env.reporter.clone());
+    targets.queue(pacing.order(&ports));
+
+    let scan = Scan {
+        target,
+        src_port: DYNAMIC_PORTS + rng.below(u64::from(u16::MAX - DYNAMIC_PORTS)) as u16,
+        seq: rng.next_u64() as u32,
+        me: (me.proto, me.hardw),
+        dst_mac,
+        queue,
+        wait,
+        pacing,
+        reporter: env.reporter.clone()
+    };
+
+    let (replies_tx, replies) = channel();
+    let handle = thread::spawn(move || scan.run(replies, targets));
+
+    Ok(Some(Module::new(handle, killer)
+        .with_filter(PackFilter::IpProtocol(IpProtocol::Tcp, replies_tx))
+        .with_controls(controls_tx)))
+}
+
+/// 22,80,8000-8100 into the ports it names, in order and without repeats
+fn parse_ports(spec: &str) -> Result<Vec<u16>, String> {
+    let port = |port: &str| match port.trim().parse::<u16>() {
+        Ok(port) if port > 0 => Ok(port),
+        _ => Err(format!("{}: not a port", port))
+    };
+
+    let mut ports = BTreeSet::new();
+
+    for part in spec.split(',') {
+        match part.find('-') {
+            Some(dash) => {
+                let (first, last) = (port(&part[..dash])?, port(&part[dash + 1..])?);
+
+                if first > last {
+                    return Err(format!("{}: range ends before it starts", part));
+                }
+
+                ports.extend(first..=last);
+            },
+
+            None => {
+                ports.insert(port(part)?);
+            }
+        }
+    }
+
+    Ok(ports.into_iter().collect())
+}
+
+impl Scan {
+    fn run(mut self, replies: Receiver<Arc<Vec<u8>>>, mut ports: Targets<u16>) -> Result<ModuleOutcome, String> {
+        let mut answers = Answers::default();
+
+        // the filter is only subscribed once the hook returns, give that a moment before any reply can arrive
+        thread::sleep(SETTLE);
+
+        // ports added while waiting on the answers are sent SYNs, then waited on in turn
+        loop {
+            while let Some(port) = ports.next() {
+                if self.queue.send(self.syn(port)).is_err() {
+                    return Err(String::from("portscan: packet queue closed"));
+                }
+
+                self.collect(&replies, &ports, &mut answers);
+                self.pacing.pause();
+            }
+
+            let deadline = Instant::now() + self.wait;
+
+            while Instant::now() < deadline && !ports.stopped() && !ports.has_pending() {
+                if let Ok(reply) = replies.recv_timeout(Duration::from_millis(100)) {
+                    self.record(&reply, &ports, &mut answers);
+                }
+            }
+
+            if ports.stopped() || !ports.has_pending() {
+                break;
+            }
+        }
+
+        self.collect(&replies, &ports, &mut answers);
+        Ok(self.outcome(&answers, &ports))
+    }
+
+    fn syn(&self, port: u16) -> Vec<u8> {
+        let src = SocketAddrV4::new(self.me.0, self.src_port);
+        let dst = SocketAddrV4::new(self.target, port);
+        let segment = tcp::build_segment(src, dst, self.seq, 0, tcp::SYN, WINDOW, &[]);
+
+        let mut packet = ipv4::build_header(self.me.0, self.target, IpProtocol::Tcp, segment.len(), PROBE_TTL).to_vec();
+        packet.extend_from_slice(&segment);
+
+        ethernet::build_frame(self.dst_mac, self.me.1, ethernet::ETHERTYPE_IPV4, &packet)
+    }
+
+    /// records every reply that has already arrived
+    fn collect(&self, replies: &Receiver<Arc<Vec<u8>>>, ports: &Targets<u16>, answers: &mut Answers) {
+        for reply in replies.try_iter() {
+            self.record(&reply, ports, answers);
+        }
+    }
+
+    fn record(&self, packet: &[u8], ports: &Targets<u16>, answers: &mut Answers) {
+        let (header, segment) = match ipv4::parse(packet) {
+            Some((header, segment)) if header.src == self.target && header.dst == self.me.0 => (header, segment),
+            _ => return
+        };
+
+        let reply = match tcp::parse(segment) {
+            Some((reply, _)) if reply.dst_port == self.src_port && ports.contains(&reply.src_port) => reply,
+            _ => return
+        };
+
+        // an answer to our SYN acknowledges its one sequence number
+        if reply.flags & tcp::ACK != 0 && reply.ack != self.seq.wrapping_add(1) {
+            return;
+        }
+
+        if reply.flags & (tcp::SYN | tcp::ACK) == tcp::SYN | tcp::ACK {
+            if answers.open.insert(reply.src_port) {
+                self.reporter.report("open port", format!("{}:{}", header.src, reply.src_port));
+            }
+        } else if reply.flags & tcp::RST != 0 {
+            answers.closed.insert(reply.src_port);
+        }
+    }
+
+    fn outcome(&self, answers: &Answers, ports: &Targets<u16>) -> ModuleOutcome {
+        let list = |ports: &mut dyn Iterator<Item = &u16>| ports.map(|port| port.to_string()).collect::<Vec<String>>().join(",");
+
+        let filtered: Vec<&u16> = ports.probed().iter()
+            .filter(|port| !answers.open.contains(port) && !answers.closed.contains(port))
+            .collect();
+
+        let mut outcome = ModuleOutcome::new();
+
+        outcome.insert(String::from("host"), self.target.to_string());
+        outcome.insert(String::from("probed"), ports.probed().len().to_string());
+        outcome.insert(String::from("open"), list(&mut answers.open.iter()));
+        outcome.insert(String::from("closed"), list(&mut answers.closed.iter()));
+        outcome.insert(String::from("filtered"), list(&mut filtered.into_iter()));
+
+        outcome
+    }
+}
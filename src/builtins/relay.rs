@@ -0,0 +1,115 @@
+/*
+relay <out-iface>
+
+A bump in the wire. Every frame captured on the interface the hook was run on is sent out on
+out-iface exactly as it arrived, macs and all, so two segments behave as one. out-iface needs a
+sender of its own, see Framework::open_channel_on, and can't be the interface frames are read from,
+which would send every frame straight back where it came from. Nor can the hook be run on the
+unnamed default interface, whose modules are handed frames from every interface, out-iface's too. A relay puts its frames on the wire
+itself rather than asking the kernel to, so a capture on out-iface sees those frames go out, and a
+second relay running the other way would send them straight back again. Every relay remembers what
+it sent for RELAYED_WINDOW and none of them passes on a frame one of them sent in that time, which
+also drops genuine repeats of a frame within it. Runs until killed and reports how many frames were
+relayed and skipped.
+*/
+
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{
+    Hash,
+    Hasher
+};
+use std::sync::Mutex;
+use std::time::{
+    Duration,
+    Instant
+};
+
+use {
+    Framework,
+    HookEnv,
+    Module,
+    ModuleBuilder,
+    ModuleOutcome,
+    PackFilter,
+    SendPriority
+};
+
+/// how long a relayed frame is remembered, see the header above
+const RELAYED_WINDOW: Duration = Duration::from_secs(1);
+
+/// shared by every relay, created by the first frame any of them relays
+static RELAYED: Mutex<Option<Relayed>> = Mutex::new(None);
+
+/// content hashes of the frames relayed lately and when each was
+struct Relayed {
+    sent: HashMap<u64, Instant>,
+    pruned: Instant
+}
+
+pub(crate) fn relay(args: &[&str], env: &HookEnv, framework: &mut Framework) -> Result<Option<Module>, String> {
+    let out = match args {
+        [out] => *out,
+        _ => return Err(String::from("usage: relay <out-iface>"))
+    };
+
+    if env.interface.is_empty() {
+        return Err(String::from("relay: has to be run on a named interface, the default one sees every interface's frames"));
+    }
+
+    if out == env.interface {
+        return Err(format!("relay: {}: frames would go back out the interface they arrived on", out));
+    }
+
+    let queue = match framework.get_packet_queue_on(out, SendPriority::Normal) {
+        Ok(queue) => queue,
+        Err(err) => return Err(format!("relay: {}", err))
+    };
+
+    let module = ModuleBuilder::new(PackFilter::Entire).run_with((0u64, 0u64), move |&mut (ref mut relayed, ref mut skipped), frame| {
+        if !first_relay(&frame) {
+            *skipped += 1;
+            return Ok(());
+        }
+
+        if queue.send(frame.to_vec()).is_err() {
+            return Err(String::from("relay: packet queue closed"));
+        }
+
+        *relayed += 1;
+        Ok(())
+    }, |(relayed, skipped)| {
+        let mut outcome = ModuleOutcome::new();
+        outcome.insert(String::from("relayed"), relayed.to_string());
+        outcome.insert(String::from("skipped"), skipped.to_string());
+        outcome
+    });
+
+    Ok(Some(module))
+}
+
+/// false if some relay sent frame within the window, otherwise remembers it as relayed now
+fn first_relay(frame: &[u8]) -> bool {
+    let now = Instant::now();
+    let mut hasher = DefaultHasher::new();
+    frame.hash(&mut hasher);
+    let hash = hasher.finish();
+
+    let mut relayed = RELAYED.lock().unwrap();
+    let relayed = relayed.get_or_insert_with(|| Relayed { sent: HashMap::new(), pruned: now });
+
+    if let Some(&sent) = relayed.sent.get(&hash) {
+        if now.duration_since(sent) < RELAYED_WINDOW {
+            return false;
+        }
+    }
+
+    // at most once a window, so a busy link doesn't rescan the map for every frame
+    if now.duration_since(relayed.pruned) >= RELAYED_WINDOW {
+        relayed.sent.retain(|_, &mut sent| now.duration_since(sent) < RELAYED_WINDOW);
+        relayed.pruned = now;
+    }
+
+    relayed.sent.insert(hash, now);
+    true
+}
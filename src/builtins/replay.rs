@@ -0,0 +1,38 @@
+/*
+replay <file> [--speed realtime|max|multiplier]
+
+Feeds a pcap capture to the dispatcher as if it were arriving on the interface the hook was run on,
+paced by --speed (max, as fast as possible, by default; see pcap.rs). Starts no module of its own:
+whatever modules are running see the replayed frames, and the replay ends with the file or with
+Framework::stop.
+*/
+
+use pcap::{
+    PcapReader,
+    ReplaySpeed
+};
+use {
+    Framework,
+    HookEnv,
+    Module
+};
+
+const USAGE: &str = "usage: replay <file> [--speed realtime|max|multiplier]";
+
+pub(crate) fn replay(args: &[&str], env: &HookEnv, framework: &mut Framework) -> Result<Option<Module>, String> {
+    let (file, speed) = match args {
+        [file] => (file, ReplaySpeed::default()),
+        [file, "--speed", speed] => (file, ReplaySpeed::parse(speed)?),
+        _ => return Err(String::from(USAGE))
+    };
+
+    let reader = match PcapReader::open(file) {
+        Ok(reader) => reader.speed(speed),
+        Err(err) => return Err(format!("{}: {}", file, err))
+    };
+
+    framework.log(&format!("[*] Replaying {} on {} ({:?})", file, env.interface, speed));
+    framework.start_capture_on(&env.interface, reader);
+
+    Ok(None)
+}
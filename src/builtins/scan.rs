@@ -0,0 +1,221 @@
+/*
+Options shared by the discovery modules for ordering and pacing their probes. By default targets are
+probed in address order with a short fixed gap, which is fast but about as conspicuous as a scan can
+be. --shuffle probes them in a random order instead, --interval sets the gap between probes and
+--jitter adds up to that much again at random to every gap, so that neither the order nor the timing
+gives the sweep away. Both draw from the Rng the hook was handed, so they are repeatable with
+FrameworkConfig::rng_seed set.
+
+Targets is the queue a discovery module probes from. It publishes how many targets have been probed
+of how many were queued through the hook's Reporter after every probe, and picks up the ScanControl
+messages Framework::steer_scan sends: Abort stops the scan as the killer would, and Add queues more
+targets behind the rest, even while the scan is waiting on the replies to its last probes.
+*/
+
+use std::collections::{
+    BTreeSet,
+    VecDeque
+};
+use std::convert::TryFrom;
+use std::sync::mpsc::Receiver;
+use std::thread;
+use std::time::Duration;
+
+use subnet::Cidr;
+use {
+    Reporter,
+    Rng,
+    ScanControl
+};
+
+pub(crate) const SCAN_USAGE: &str = "[--shuffle] [--interval dur] [--jitter dur]";
+
+/// the widest IPv4 range a discovery module sweeps, a /16 is already 65534 hosts
+pub(crate) const MIN_PREFIX: u8 = 16;
+
+/// how a discovery module orders and paces its probes
+pub(crate) struct ScanOptions {
+    pub shuffle: bool,
+    pub interval: Duration,
+    pub jitter: Duration,
+    rng: Rng
+}
+
+impl ScanOptions {
+    /// pulls the scan flags out of args, returning them and the remaining positional args
+    /// interval is what's used when --interval isn't given, rng orders and jitters the probes
+    pub(crate) fn parse<'a>(args: &[&'a str], interval: Duration, rng: Rng) -> Result<(ScanOptions, Vec<&'a str>), String> {
+        let mut options = ScanOptions {
+            shuffle: false,
+            interval,
+            jitter: Duration::from_millis(0),
+            rng
+        };
+
+        let mut positional = Vec::new();
+        let mut args = args.iter();
+
+        while let Some(&arg) = args.next() {
+            match arg {
+                "--shuffle" => options.shuffle = true,
+                "--interval" => options.interval = parse_duration(args.next().cloned())?,
+                "--jitter" => options.jitter = parse_duration(args.next().cloned())?,
+                flag if flag.starts_with("--") => return Err(format!("{}: unknown scan option", flag)),
+                arg => positional.push(arg)
+            }
+        }
+
+        Ok((options, positional))
+    }
+
+    /// targets in the order they should be probed
+    pub(crate) fn order<T: Clone>(&mut self, targets: &[T]) -> Vec<T> {
+        let mut order = targets.to_vec();
+
+        if self.shuffle {
+            self.rng.shuffle(&mut order);
+        }
+
+        order
+    }
+
+    /// sleeps between one probe and the next
+    pub(crate) fn pause(&mut self) {
+        let mut gap = self.interval;
+        // a jitter past u64::MAX nanoseconds, some 584 years, is as good as that
+        let jitter = u64::try_from(self.jitter.as_nanos()).unwrap_or(u64::MAX);
+
+        if jitter > 0 {
+            gap = gap.saturating_add(Duration::from_nanos(self.rng.below(jitter.saturating_add(1))));
+        }
+
+        thread::sleep(gap);
+    }
+}
+
+/// what a discovery module has left to probe, see the header above
+pub(crate) struct Targets<T> {
+    pending: VecDeque<T>,
+    waiting: BTreeSet<T>, // what's in pending, so queue doesn't have to search it
+    known: BTreeSet<T>, // every target ever queued
+    probed: BTreeSet<T>,
+    parse: fn(&str) -> Option<T>, // for targets added with ScanControl::Add
+    killed: Receiver<()>,
+    controls: Receiver<ScanControl>,
+    reporter: Reporter,
+    stopped: bool
+}
+
+impl<T: Ord + Clone> Targets<T> {
+    /// nothing queued yet, see queue
+    pub(crate) fn new(parse: fn(&str) -> Option<T>, killed: Receiver<()>, controls: Receiver<ScanControl>, reporter: Reporter) -> Targets<T> {
+        Targets {
+            pending: VecDeque::new(),
+            waiting: BTreeSet::new(),
+            known: BTreeSet::new(),
+            probed: BTreeSet::new(),
+            parse,
+            killed,
+            controls,
+            reporter,
+            stopped: false
+        }
+    }
+
+    /// queues targets in the order given, skipping any still waiting to be probed
+    /// a target that was probed before is queued again, for modules that retry
+    pub(crate) fn queue(&mut self, targets: Vec<T>) {
+        for target in targets {
+            if self.waiting.insert(target.clone()) {
+                self.known.insert(target.clone());
+                self.pending.push_back(target);
+            }
+        }
+    }
+
+    /// the next target to probe, None once the queue is empty or the scan has been stopped
+    pub(crate) fn next(&mut self) -> Option<T> {
+        if self.stopped() {
+            return None;
+        }
+
+        let target = self.pending.pop_front()?;
+        self.waiting.remove(&target);
+        self.probed.insert(target.clone());
+        self.reporter.progress(self.probed.len(), self.known.len());
+
+        Some(target)
+    }
+
+    /// whether the module was killed or sent Abort, queueing whatever was added in the meantime
+    pub(crate) fn stopped(&mut self) -> bool {
+        if self.killed.try_recv().is_ok() {
+            self.stopped = true;
+        }
+
+        let controls: Vec<ScanControl> = self.controls.try_iter().collect();
+
+        for control in controls {
+            match control {
+                ScanControl::Abort => self.stopped = true,
+
+                ScanControl::Add(targets) => {
+                    let parse = self.parse;
+                    let added = targets.iter().filter_map(|target| parse(target)).filter(|target| !self.known.contains(target)).collect();
+                    self.queue(added);
+                }
+            }
+        }
+
+        self.stopped
+    }
+
+    /// true while there are targets waiting to be probed
+    pub(crate) fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// whether target was ever queued, for telling replies to the scan from anyone else's
+    pub(crate) fn contains(&self, target: &T) -> bool {
+        self.known.contains(target)
+    }
+
+    /// every target probed so far, sorted rather than in the order they were probed
+    pub(crate) fn probed(&self) -> &BTreeSet<T> {
+        &self.probed
+    }
+}
+
+/// Err for a cidr wider than MIN_PREFIX, which would take hours and flood the link
+pub(crate) fn check_width(module: &str, cidr: &Cidr) -> Result<(), String> {
+    if cidr.prefix < MIN_PREFIX {
+        return Err(format!("{}/{}: {} only sweeps prefixes of /{} or longer", cidr.addr, cidr.prefix, module, MIN_PREFIX));
+    }
+
+    Ok(())
+}
+
+/// 50ms, 2s or a bare number of milliseconds
+pub(crate) fn parse_duration(arg: Option<&str>) -> Result<Duration, String> {
+    let arg = match arg {
+        Some(arg) => arg,
+        None => return Err(String::from("scan option is missing its duration"))
+    };
+
+    let (number, unit) = if let Some(millis) = arg.strip_suffix("ms") {
+        (millis, 1)
+    } else if let Some(secs) = arg.strip_suffix('s') {
+        (secs, 1000)
+    } else {
+        (arg, 1)
+    };
+
+    match number.parse::<u64>() {
+        Ok(number) => match number.checked_mul(unit) {
+            Some(millis) => Ok(Duration::from_millis(millis)),
+            None => Err(format!("{}: duration too large", arg))
+        },
+
+        Err(_) => Err(format!("{}: not a duration", arg))
+    }
+}
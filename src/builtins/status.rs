@@ -0,0 +1,54 @@
+/*
+status
+
+Logs an overview of the Framework in one go: the interface the hook was run on, how many hooks are
+bound, every running module with its state and packet count, the size of that interface's host
+table split into hosts with and without a known mac, and the traffic totals. Starts no module.
+*/
+
+use {
+    Framework,
+    HookEnv,
+    Module
+};
+
+pub(crate) fn status(args: &[&str], env: &HookEnv, framework: &mut Framework) -> Result<Option<Module>, String> {
+    if !args.is_empty() {
+        return Err(String::from("usage: status"));
+    }
+
+    let (hosts, resolved) = {
+        let nethosts = match framework.hosts_for(&env.interface) {
+            Some(hosts) => hosts.read_nethosts(),
+            None => return Err(format!("{}: No such interface", env.interface))
+        };
+
+        (nethosts.len(), nethosts.resolved_len())
+    };
+
+    let mut names: Vec<String> = framework.modules().keys().cloned().collect();
+    names.sort();
+
+    let totals = framework.traffic_totals();
+
+    framework.log(&format!("[*] Interface: {}", env.interface));
+    framework.log(&format!("[*] Hooks: {}", framework.hook_count()));
+    framework.log(&format!("[*] Modules: {}", names.len()));
+
+    for name in names {
+        if let Some(report) = framework.inspect_module(&name) {
+            framework.log(&format!("    {} ({}): {}, {} packets", report.name, report.source_hook, report.state(), report.packets));
+        }
+    }
+
+    framework.log(&format!("[*] Hosts: {} ({} resolved, {} unresolved)", hosts, resolved, hosts - resolved));
+    framework.log(&format!(
+        "[*] Traffic: {} frames ({} bytes) received, {} frames ({} bytes) sent",
+        totals.frames_received,
+        totals.bytes_received,
+        totals.frames_sent,
+        totals.bytes_sent
+    ));
+
+    Ok(None)
+}
@@ -0,0 +1,82 @@
+/*
+talkers
+
+Counts every IPv4 packet seen against its source address in the HostMgr's traffic rates, which is
+what HostMgr::host_rates reads from. Frames already waiting are counted together under one lock so
+the count keeps up with a busy link. Logs nothing while running; once killed it reports the busiest
+hosts of the last RATE_WINDOW_SECS seconds, at most the number given (10 by default).
+*/
+
+use std::sync::{
+    Arc,
+    Mutex,
+    mpsc::channel,
+    mpsc::Receiver,
+    mpsc::RecvTimeoutError
+};
+use std::thread;
+use std::time::Duration;
+
+use packet::ipv4;
+use {
+    HookEnv,
+    HostMgr,
+    HostRates,
+    Module,
+    ModuleOutcome,
+    PackFilter
+};
+
+const DEFAULT_TOP: usize = 10;
+
+pub(crate) fn talkers(args: &[&str], _env: &HookEnv, hosts: &mut HostMgr) -> Result<Option<Module>, String> {
+    let top = match args {
+        [] => DEFAULT_TOP,
+        [count] => match count.parse::<usize>() {
+            Ok(count) => count,
+            Err(_) => return Err(format!("{}: not a number of hosts", count))
+        },
+        _ => return Err(String::from("usage: talkers [top hosts]"))
+    };
+
+    let rates = hosts.get_rates();
+    let (killer, killed) = channel();
+    let (packets_tx, packets) = channel();
+
+    let handle = thread::spawn(move || count(packets, killed, rates, top));
+
+    Ok(Some(Module::new(handle, killer).with_filter(PackFilter::Payload(packets_tx))))
+}
+
+fn count(
+    packets: Receiver<Arc<Vec<u8>>>,
+    killed: Receiver<()>,
+    rates: Arc<Mutex<HostRates>>,
+    top: usize
+) -> Result<ModuleOutcome, String> {
+    while killed.try_recv().is_err() {
+        let first = match packets.recv_timeout(Duration::from_millis(250)) {
+            Ok(packet) => packet,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break
+        };
+
+        let mut rates = rates.lock().unwrap();
+
+        for packet in Some(first).into_iter().chain(packets.try_iter()) {
+            if let Some((header, _)) = ipv4::parse(&packet) {
+                rates.record(header.src, packet.len());
+            }
+        }
+    }
+
+    let busiest: Vec<String> = rates.lock().unwrap().packet_rates().iter()
+        .take(top)
+        .map(|&(host, rate)| format!("{}={}", host, rate))
+        .collect();
+
+    let mut outcome = ModuleOutcome::new();
+    outcome.insert(String::from("top"), busiest.join(","));
+
+    Ok(outcome)
+}
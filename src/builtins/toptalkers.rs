@@ -0,0 +1,150 @@
+/*
+toptalkers [hosts] [interval secs]
+
+Logs the busiest IPv4 senders every interval seconds (5 by default), at most the number of hosts
+given (10 by default), busiest first. Each line has the host's packets per second over the last
+RATE_WINDOW_SECS seconds, its mac if the host table has one and an OS family guessed from the TTL of
+the last packet it sent. Only watches, nothing is sent. Counts into rates of its own rather than the
+HostMgr's, so it can run alongside talkers without either counting a packet twice. Once killed it
+reports the last ranking it logged.
+*/
+
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+use std::sync::{
+    Arc,
+    RwLock,
+    mpsc::channel,
+    mpsc::Receiver,
+    mpsc::RecvTimeoutError
+};
+use std::thread;
+use std::time::{
+    Duration,
+    Instant
+};
+
+use packet::ipv4;
+use {
+    Framework,
+    HookEnv,
+    HostRates,
+    Logger,
+    Module,
+    ModuleOutcome,
+    ModuleOutput,
+    NetPairList,
+    PackFilter
+};
+use super::OUTPUT_LINES;
+
+const DEFAULT_TOP: usize = 10;
+const DEFAULT_INTERVAL_SECS: u64 = 5;
+const USAGE: &str = "usage: toptalkers [hosts] [interval secs]";
+
+pub(crate) fn toptalkers(args: &[&str], env: &HookEnv, framework: &mut Framework) -> Result<Option<Module>, String> {
+    let number = |arg: &str| match arg.parse::<u64>() {
+        Ok(number) if number > 0 => Ok(number),
+        _ => Err(format!("{}: not a positive number\n{}", arg, USAGE))
+    };
+
+    let (top, interval) = match args {
+        [] => (DEFAULT_TOP, DEFAULT_INTERVAL_SECS),
+        [top] => (number(top)? as usize, DEFAULT_INTERVAL_SECS),
+        [top, interval] => (number(top)? as usize, number(interval)?),
+        _ => return Err(String::from(USAGE))
+    };
+
+    let nethosts = match framework.hosts_for(&env.interface) {
+        Some(hosts) => hosts.get_nethosts(),
+        None => return Err(format!("{}: No such interface", env.interface))
+    };
+
+    let watch = Watch {
+        top,
+        interval: Duration::from_secs(interval),
+        nethosts,
+        logger: framework.logger(),
+        output: ModuleOutput::new(OUTPUT_LINES)
+    };
+
+    let output = watch.output.clone();
+    let (killer, killed) = channel();
+    let (packets_tx, packets) = channel();
+
+    let handle = thread::spawn(move || watch.run(packets, killed));
+
+    Ok(Some(Module::new(handle, killer)
+        .with_filter(PackFilter::Payload(packets_tx))
+        .with_output(output)))
+}
+
+struct Watch {
+    top: usize,
+    interval: Duration,
+    nethosts: Arc<RwLock<NetPairList>>,
+    logger: Logger,
+    output: ModuleOutput
+}
+
+impl Watch {
+    fn run(self, packets: Receiver<Arc<Vec<u8>>>, killed: Receiver<()>) -> Result<ModuleOutcome, String> {
+        let mut rates = HostRates::new();
+        let mut ttls: HashMap<Ipv4Addr, u8> = HashMap::new(); // of the last packet each host sent
+        let mut ranking = Vec::new();
+        let mut logged = Instant::now();
+
+        while killed.try_recv().is_err() {
+            match packets.recv_timeout(Duration::from_millis(250)) {
+                Ok(first) => {
+                    for packet in Some(first).into_iter().chain(packets.try_iter()) {
+                        if let Some((header, _)) = ipv4::parse(&packet) {
+                            rates.record(header.src, packet.len());
+                            ttls.insert(header.src, header.ttl);
+                        }
+                    }
+                },
+
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => break
+            }
+
+            if logged.elapsed() >= self.interval {
+                let busy = rates.packet_rates();
+                ranking = self.log_ranking(&busy, &ttls);
+                logged = Instant::now();
+
+                // packet_rates forgot whoever went quiet, their ttls can go too
+                ttls.retain(|host, _| busy.iter().any(|&(busy, _)| busy == *host));
+            }
+        }
+
+        let mut outcome = ModuleOutcome::new();
+        outcome.insert(String::from("top"), ranking.join(","));
+
+        Ok(outcome)
+    }
+
+    /// logs the busiest of rates, which is sorted busiest first, returning them as host=rate
+    fn log_ranking(&self, rates: &[(Ipv4Addr, u64)], ttls: &HashMap<Ipv4Addr, u8>) -> Vec<String> {
+        let busiest = &rates[..rates.len().min(self.top)];
+        let nethosts = self.nethosts.read().unwrap();
+
+        self.logger.log(&format!("[toptalkers] {} busiest hosts", busiest.len()));
+
+        for &(host, rate) in busiest {
+            let mac = match nethosts.macs().get(&host) {
+                Some(&Some(mac)) => mac.to_string(),
+                _ => String::from("-")
+            };
+
+            let os = ttls.get(&host).map_or("-", |&ttl| ipv4::guess_os_from_ttl(ttl));
+            let line = format!("{:<15} {:>8} pkt/s  {:<17}  {}", host, rate, mac, os);
+
+            self.logger.log(&format!("[toptalkers] {}", line));
+            self.output.push(line);
+        }
+
+        busiest.iter().map(|&(host, rate)| format!("{}={}", host, rate)).collect()
+    }
+}
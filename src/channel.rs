@@ -0,0 +1,242 @@
+/*
+Bounded frame channels for modules that would rather lose frames, or hold the dispatcher up, than
+let an unbounded queue grow behind a slow consumer. PackFilter::bounded wraps any filter so that what
+it matches is delivered into one of these instead of a plain mpsc channel, and the module reads it
+through the FrameReceiver it gets back, which has the same recv/try_recv/recv_timeout/try_iter as an
+mpsc Receiver.
+
+What happens once the channel is full is up to its ChannelPolicy. DropOldest is the right choice for
+most modules: anything displaying or reacting to live traffic wants the newest frames. DropNewest
+keeps what is already queued and turns the latest frame away. Block makes the dispatcher wait for
+room, so nothing is lost, but each dispatch worker delivers a frame to every module in turn: a Block
+module that falls behind stalls the worker waiting on it, and with it delivery to all the others of
+every conversation that worker handles. With FrameworkConfig::dispatch_workers above 1 the other
+workers carry on only until the capture loop hands the stalled one a frame its full queue has no
+room for, then capture waits and frames pile up in the capture source until the module catches up.
+Only use Block for something like a forensic capture that must see every frame and can keep up with
+the link.
+*/
+
+use std::collections::VecDeque;
+use std::sync::{
+    Arc,
+    Condvar,
+    Mutex,
+    atomic::AtomicBool,
+    atomic::AtomicU64,
+    atomic::Ordering,
+    mpsc::RecvError,
+    mpsc::RecvTimeoutError,
+    mpsc::TryRecvError
+};
+use std::time::{
+    Duration,
+    Instant
+};
+
+/// how often a blocked delivery checks whether it should give up
+const BLOCK_RECHECK: Duration = Duration::from_millis(10);
+
+/// what happens to a frame delivered to a full channel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChannelPolicy {
+    DropOldest, // the oldest queued frame makes room for it
+    DropNewest, // it is dropped and the queue left as it was
+    Block // the dispatcher waits for the module to make room
+}
+
+/// how a single delivery into a bounded channel went
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Delivery {
+    Sent,
+    Overflowed, // the channel was full and a frame was dropped, the new one or the oldest
+    Disconnected
+}
+
+struct FrameQueue {
+    frames: Mutex<VecDeque<Arc<Vec<u8>>>>,
+    capacity: usize,
+    policy: ChannelPolicy,
+    ready: Condvar, // a frame was queued or the sender went away
+    space: Condvar, // a frame was taken or the receiver went away
+    sender_alive: AtomicBool,
+    receiver_alive: AtomicBool,
+    dropped: AtomicU64
+}
+
+/// the dispatcher's end of a bounded channel, kept inside the PackFilter
+pub struct BoundedSender(Arc<FrameQueue>);
+
+/// a module's end of a bounded channel
+pub struct FrameReceiver(Arc<FrameQueue>);
+
+/// somewhere to send into a bounded channel from outside its filter, so the dispatcher can wait on a
+/// full Block channel without holding the filter's lock; unlike the BoundedSender it comes from,
+/// dropping it doesn't tell the receiver the filter is gone
+pub(crate) struct BoundedOutlet(Arc<FrameQueue>);
+
+/// a channel holding at most capacity frames, 0 is taken as 1
+pub(crate) fn bounded(capacity: usize, policy: ChannelPolicy) -> (BoundedSender, FrameReceiver) {
+    let queue = Arc::new(FrameQueue {
+        frames: Mutex::new(VecDeque::new()),
+        capacity: capacity.max(1),
+        policy,
+        ready: Condvar::new(),
+        space: Condvar::new(),
+        sender_alive: AtomicBool::new(true),
+        receiver_alive: AtomicBool::new(true),
+        dropped: AtomicU64::new(0)
+    });
+
+    (BoundedSender(queue.clone()), FrameReceiver(queue))
+}
+
+impl BoundedSender {
+    pub(crate) fn capacity(&self) -> usize {
+        self.0.capacity
+    }
+
+    pub(crate) fn policy(&self) -> ChannelPolicy {
+        self.0.policy
+    }
+
+    pub(crate) fn outlet(&self) -> BoundedOutlet {
+        BoundedOutlet(self.0.clone())
+    }
+}
+
+impl BoundedOutlet {
+    /// queues frame according to the channel's policy, a Block delivery stops waiting once give_up returns true
+    pub(crate) fn send(&self, frame: Arc<Vec<u8>>, give_up: &dyn Fn() -> bool) -> Delivery {
+        let queue = &self.0;
+        let mut frames = queue.frames.lock().unwrap();
+
+        if !queue.receiver_alive.load(Ordering::Relaxed) {
+            return Delivery::Disconnected;
+        }
+
+        let mut delivery = Delivery::Sent;
+
+        if frames.len() >= queue.capacity {
+            match queue.policy {
+                ChannelPolicy::DropOldest => {
+                    frames.pop_front();
+                    delivery = Delivery::Overflowed;
+                },
+
+                ChannelPolicy::DropNewest => {
+                    queue.dropped.fetch_add(1, Ordering::Relaxed);
+                    return Delivery::Overflowed;
+                },
+
+                ChannelPolicy::Block => while frames.len() >= queue.capacity {
+                    if !queue.receiver_alive.load(Ordering::Relaxed) {
+                        return Delivery::Disconnected;
+                    }
+
+                    if give_up() {
+                        queue.dropped.fetch_add(1, Ordering::Relaxed);
+                        return Delivery::Overflowed;
+                    }
+
+                    frames = queue.space.wait_timeout(frames, BLOCK_RECHECK).unwrap().0;
+                }
+            }
+        }
+
+        if delivery == Delivery::Overflowed {
+            queue.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+
+        frames.push_back(frame);
+        queue.ready.notify_one();
+        delivery
+    }
+}
+
+impl Drop for BoundedSender {
+    fn drop(&mut self) {
+        // taking the lock makes sure a receiver about to wait sees the flag first
+        let _frames = self.0.frames.lock().unwrap();
+        self.0.sender_alive.store(false, Ordering::Relaxed);
+        self.0.ready.notify_all();
+    }
+}
+
+impl FrameReceiver {
+    /// waits for the next frame, failing once the channel is empty and its filter has been dropped
+    pub fn recv(&self) -> Result<Arc<Vec<u8>>, RecvError> {
+        let queue = &self.0;
+        let mut frames = queue.frames.lock().unwrap();
+
+        loop {
+            if let Some(frame) = frames.pop_front() {
+                queue.space.notify_one();
+                return Ok(frame);
+            }
+
+            if !queue.sender_alive.load(Ordering::Relaxed) {
+                return Err(RecvError);
+            }
+
+            frames = queue.ready.wait(frames).unwrap();
+        }
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Arc<Vec<u8>>, RecvTimeoutError> {
+        let queue = &self.0;
+        let deadline = Instant::now() + timeout;
+        let mut frames = queue.frames.lock().unwrap();
+
+        loop {
+            if let Some(frame) = frames.pop_front() {
+                queue.space.notify_one();
+                return Ok(frame);
+            }
+
+            if !queue.sender_alive.load(Ordering::Relaxed) {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+
+            let now = Instant::now();
+
+            if now >= deadline {
+                return Err(RecvTimeoutError::Timeout);
+            }
+
+            frames = queue.ready.wait_timeout(frames, deadline - now).unwrap().0;
+        }
+    }
+
+    pub fn try_recv(&self) -> Result<Arc<Vec<u8>>, TryRecvError> {
+        let queue = &self.0;
+
+        match queue.frames.lock().unwrap().pop_front() {
+            Some(frame) => {
+                queue.space.notify_one();
+                Ok(frame)
+            },
+
+            None if queue.sender_alive.load(Ordering::Relaxed) => Err(TryRecvError::Empty),
+            None => Err(TryRecvError::Disconnected)
+        }
+    }
+
+    /// every frame already queued, without waiting for more
+    pub fn try_iter(&self) -> impl Iterator<Item = Arc<Vec<u8>>> + '_ {
+        ::std::iter::from_fn(move || self.try_recv().ok())
+    }
+
+    /// frames turned away or pushed out because the channel was full
+    pub fn dropped(&self) -> u64 {
+        self.0.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for FrameReceiver {
+    fn drop(&mut self) {
+        let _frames = self.0.frames.lock().unwrap();
+        self.0.receiver_alive.store(false, Ordering::Relaxed);
+        self.0.space.notify_all();
+    }
+}
@@ -0,0 +1,87 @@
+/*
+Remote control over TCP. Clients send newline delimited `hook arg1 arg2` commands and get one result
+line back per command. The listener and its connections run on their own threads, but they never
+touch the Framework themselves: each command is handed over a channel to whoever owns the Framework,
+which runs it from Framework::handle_control_commands and sends the reply back the same way.
+*/
+
+use std::io::{
+    self,
+    BufRead,
+    BufReader,
+    Write
+};
+use std::net::{
+    IpAddr,
+    Ipv4Addr,
+    SocketAddr,
+    TcpListener,
+    TcpStream
+};
+use std::sync::mpsc::{
+    channel,
+    Receiver,
+    Sender
+};
+use std::thread;
+
+pub const CONTROL_PORT: u16 = 7979;
+
+/// one command line read from a client and where to send its result
+pub struct ControlRequest {
+    pub line: String,
+    pub reply: Sender<String>
+}
+
+/// loopback only by default, anything else has to be asked for explicitly
+pub fn default_control_addr() -> SocketAddr {
+    SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), CONTROL_PORT)
+}
+
+/// binds addr and starts accepting clients, returning the bound address and the request stream
+pub(crate) fn listen(addr: SocketAddr) -> io::Result<(SocketAddr, Receiver<ControlRequest>)> {
+    let listener = TcpListener::bind(addr)?;
+    let bound = listener.local_addr()?;
+    let (requests, commands) = channel();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => continue
+            };
+
+            let requests = requests.clone();
+            thread::spawn(move || serve_client(stream, requests));
+        }
+    });
+
+    Ok((bound, commands))
+}
+
+fn serve_client(stream: TcpStream, requests: Sender<ControlRequest>) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let reader = BufReader::new(stream);
+
+    for line in reader.lines() {
+        let line = line?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (reply, result) = channel();
+
+        if requests.send(ControlRequest { line, reply }).is_err() {
+            // the framework stopped taking commands
+            break;
+        }
+
+        match result.recv() {
+            Ok(result) => writeln!(writer, "{}", result)?,
+            Err(_) => break
+        }
+    }
+
+    Ok(())
+}
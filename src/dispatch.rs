@@ -0,0 +1,1599 @@
+/*
+The packet path. Frames read off the datalink channel are handed to Dispatcher::dispatch, which
+checks every subscribed module's PackFilter and sends it the part of the frame it asked for. Going
+the other way, modules queue frames on the Framework's packet queues and the send consumer writes
+them out, always taking what is queued at a higher SendPriority first. Both ends keep counters so the Framework can report on them.
+
+One send consumer serves every interface. Frames queued for an added interface, through
+Framework::get_packet_queue_on or a QueuedFrame tagged with on_interface, are written to the sink
+Framework::start_sender_on opened for it, and every other frame to the sink the consumer was
+started with. A frame tagged with an interface that has no sink is dropped and counted, never sent
+out some other interface instead.
+
+The capture loop and send consumer talk to the network through PacketSource and PacketSink rather
+than pnet directly, so anything that can produce or swallow frames can stand in for an interface.
+
+With FrameworkConfig::dispatch_workers above 1, the capture loop only reads: each frame is copied
+onto the queue of one of that many worker threads, which do the filtering and delivering. A frame
+goes to the worker its IPv4 or IPv6 address pair hashes to, either direction alike, or its mac pair
+for anything else, so every frame of a conversation is dispatched by the same worker and reaches a
+module in the order it was captured. Frames of different conversations are dispatched side by side
+and may reach a module in a different order than they were captured in.
+
+With FrameworkConfig::snaplen set, every frame is cut to that many bytes as it is read, before any
+worker or filter sees it. The received byte counters still count each frame at the length it was
+captured at, so traffic totals don't shrink with the snaplen.
+
+Every subscription's delivered counter is sampled by one rate thread every
+FrameworkConfig::rate_interval, which folds the frames delivered since the last sample into an
+exponential moving average of frames a second, see Module::rate_pps. The thread only ever reads the
+counters and writes the averages, so dispatch never waits on it, and it exits once the Dispatcher is
+dropped.
+*/
+
+use std::collections::{
+    BTreeMap,
+    HashMap,
+    VecDeque
+};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{
+    Hash,
+    Hasher
+};
+use std::io;
+use std::net::IpAddr;
+use std::thread;
+use std::sync::{
+    Arc,
+    Mutex,
+    RwLock,
+    Weak,
+    atomic::AtomicBool,
+    atomic::AtomicU64,
+    atomic::Ordering,
+    mpsc::{
+        self,
+        Receiver,
+        Sender,
+        SyncSender
+    }
+};
+use std::time::{
+    Duration,
+    Instant
+};
+
+use pnet::datalink::{
+    DataLinkReceiver,
+    DataLinkSender,
+    MacAddr
+};
+
+use channel::{
+    self,
+    BoundedOutlet,
+    BoundedSender,
+    ChannelPolicy,
+    Delivery,
+    FrameReceiver
+};
+use packet::{
+    ethernet,
+    ipv4,
+    ipv4::IpProtocol,
+    ipv6,
+    tcp,
+    udp
+};
+use os;
+use super::Logger;
+use trace::{
+    self,
+    ModuleSpan
+};
+
+/// length of an untagged ethernet header
+pub const ETHER_HEADER_LEN: usize = 14;
+
+/// what a module wants to be handed out of each frame
+pub enum PackFilter {
+    Closed, // nothing, the module works off the host table alone
+    Entire(Sender<Arc<Vec<u8>>>), // the whole frame
+    EtherFrame(Sender<Arc<Vec<u8>>>), // only the ethernet header
+    Payload(Sender<Arc<Vec<u8>>>), // everything after the ethernet header
+    IpProtocol(IpProtocol, Sender<Arc<Vec<u8>>>), // the IPv4 packet, only if it carries this protocol and isn't a later fragment
+    UdpPorts(Vec<u16>, Sender<Arc<Vec<u8>>>), // the IPv4 packet, only if it is UDP to or from one of these ports, first fragments only
+    Icmpv6(Sender<Arc<Vec<u8>>>), // the IPv6 packet, only if ICMPv6 directly follows the fixed header
+    DstClass(Vec<DstClass>, Sender<Arc<Vec<u8>>>), // the whole frame, only if its destination is one of these kinds
+    EtherType { // the whole frame, only if it carries this ethertype, either outright or inside VLAN tags
+        ethertype: u16,
+        sender: Sender<Arc<Vec<u8>>>
+    },
+    MacMatch { // the whole frame, only if the first prefix_bits of field's mac are addr's, see PackFilter::mac_match
+        addr: MacAddr,
+        prefix_bits: u8,
+        field: MacField,
+        sender: Sender<Arc<Vec<u8>>>
+    },
+    NewFlow { // the whole frame, only if it is the first TCP or UDP frame of its flow seen lately, see FlowSet
+        sender: Sender<Arc<Vec<u8>>>
+    },
+    Fragmented { // the IPv4 or IPv6 packet, only if it is a fragment, the first included
+        sender: Sender<Arc<Vec<u8>>>
+    },
+    Bounded(Box<PackFilter>, BoundedSender) // what the inner filter matches, into a bounded channel, see PackFilter::bounded
+}
+
+impl PackFilter {
+    /// a filter matching what make builds, delivering into a channel of at most capacity frames
+    /// the channel overflows according to policy, see channel.rs for which to pick
+    /// `PackFilter::bounded(256, ChannelPolicy::DropOldest, |tx| PackFilter::IpProtocol(IpProtocol::Icmp, tx))`
+    pub fn bounded<F>(capacity: usize, policy: ChannelPolicy, make: F) -> (PackFilter, FrameReceiver)
+        where F: FnOnce(Sender<Arc<Vec<u8>>>) -> PackFilter
+    {
+        // only the inner filter's matching is used, nothing is ever sent on its own channel
+        let (unused, _) = mpsc::channel();
+        let (sender, frames) = channel::bounded(capacity, policy);
+
+        (PackFilter::Bounded(Box::new(make(unused)), sender), frames)
+    }
+
+    /// a MacMatch filter, or an error if prefix_bits is longer than a mac
+    /// `PackFilter::mac_match(MacAddr(0x00, 0x1b, 0x63, 0, 0, 0), 24, MacField::Src, tx)` for everything one vendor sends
+    pub fn mac_match(addr: MacAddr, prefix_bits: u8, field: MacField, sender: Sender<Arc<Vec<u8>>>) -> Result<PackFilter, String> {
+        if prefix_bits > MAC_BITS {
+            return Err(format!("{}: prefix longer than the {} bits of a mac", prefix_bits, MAC_BITS));
+        }
+
+        Ok(PackFilter::MacMatch {
+            addr,
+            prefix_bits,
+            field,
+            sender
+        })
+    }
+
+    /// what the filter matches, in a few words, for reports
+    pub fn describe(&self) -> String {
+        match *self {
+            PackFilter::Closed => String::from("none"),
+            PackFilter::Entire(_) => String::from("entire frames"),
+            PackFilter::EtherFrame(_) => String::from("ethernet headers"),
+            PackFilter::Payload(_) => String::from("ethernet payloads"),
+            PackFilter::IpProtocol(protocol, _) => format!("IPv4 {:?} packets", protocol),
+
+            PackFilter::UdpPorts(ref ports, _) => {
+                let ports: Vec<String> = ports.iter().map(|port| port.to_string()).collect();
+                format!("IPv4 UDP packets on ports {}", ports.join(","))
+            },
+
+            PackFilter::Icmpv6(_) => String::from("IPv6 ICMPv6 packets"),
+
+            PackFilter::DstClass(ref classes, _) => {
+                let classes: Vec<String> = classes.iter().map(|class| format!("{:?}", class).to_lowercase()).collect();
+                format!("frames sent {}", classes.join(", "))
+            },
+
+            PackFilter::EtherType { ethertype, .. } => format!("frames of ethertype {:#06x}", ethertype),
+
+            PackFilter::MacMatch { addr, prefix_bits, field, .. } => {
+                format!("frames with a {} mac in {}/{}", format!("{:?}", field).to_lowercase(), addr, prefix_bits)
+            },
+
+            PackFilter::NewFlow { .. } => String::from("first frames of TCP and UDP flows"),
+            PackFilter::Fragmented { .. } => String::from("IPv4 and IPv6 fragments"),
+
+            PackFilter::Bounded(ref filter, ref sender) => {
+                format!("{}, at most {} queued ({:?})", filter.describe(), sender.capacity(), sender.policy())
+            }
+        }
+    }
+
+    /// whether only the first frame of each flow gets through, which the dispatcher decides, not view
+    fn new_flows_only(&self) -> bool {
+        match *self {
+            PackFilter::NewFlow { .. } => true,
+            PackFilter::Bounded(ref filter, _) => filter.new_flows_only(),
+            _ => false
+        }
+    }
+
+    /// the part of frame this filter forwards, if any
+    /// only which part is decided here, FrameViews makes the copy once for every filter wanting it
+    fn view(&self, frame: &[u8]) -> Option<View> {
+        match *self {
+            PackFilter::Closed => None,
+            PackFilter::Bounded(ref filter, _) => filter.view(frame),
+            PackFilter::Entire(_) => Some(View::Entire),
+            PackFilter::EtherFrame(_) if frame.len() >= ETHER_HEADER_LEN => Some(View::Header),
+            PackFilter::EtherFrame(_) => None,
+            PackFilter::Payload(_) if frame.len() >= ETHER_HEADER_LEN => Some(View::Payload),
+            PackFilter::Payload(_) => None,
+
+            // the IPv4 and IPv6 packets are everything after the ethernet header, the same bytes as Payload
+            // a fragment past the first starts mid datagram, handing it on would have its data read as a header
+            PackFilter::IpProtocol(protocol, _) => {
+                let packet = ipv4_packet(frame)?;
+
+                match ipv4::parse(packet) {
+                    Some((ref header, _)) if header.protocol == protocol && !header.is_later_fragment() => Some(View::Payload),
+                    _ => None
+                }
+            },
+
+            PackFilter::UdpPorts(ref ports, _) => {
+                let packet = ipv4_packet(frame)?;
+
+                match ipv4::parse(packet) {
+                    Some((ref header, datagram)) if header.protocol == IpProtocol::Udp && !header.is_later_fragment() => match udp::parse(datagram) {
+                        Some((ref udp, _)) if ports.contains(&udp.src_port) || ports.contains(&udp.dst_port) => Some(View::Payload),
+                        _ => None
+                    },
+
+                    _ => None
+                }
+            },
+
+            PackFilter::Icmpv6(_) => {
+                if ethernet::ethertype(frame) != Some(ethernet::ETHERTYPE_IPV6) {
+                    return None;
+                }
+
+                let packet = frame.get(ETHER_HEADER_LEN..)?;
+
+                match ipv6::parse(packet) {
+                    Some((ref header, _)) if header.next_header == ipv6::NEXT_HEADER_ICMPV6 => Some(View::Payload),
+                    _ => None
+                }
+            },
+
+            PackFilter::DstClass(ref classes, _) => match DstClass::of(frame) {
+                Some(class) if classes.contains(&class) => Some(View::Entire),
+                _ => None
+            },
+
+            // matching the outer ethertype too lets a filter on the tag itself see every tagged frame
+            PackFilter::EtherType { ethertype, .. } => {
+                if ethernet::ethertype(frame) == Some(ethertype) || ethernet::inner_ethertype(frame) == Some(ethertype) {
+                    Some(View::Entire)
+                } else {
+                    None
+                }
+            },
+
+            PackFilter::MacMatch { addr, prefix_bits, field, .. } => {
+                let mac = match field {
+                    MacField::Dst => frame.get(..6)?,
+                    MacField::Src => frame.get(6..12)?
+                };
+
+                if mac_prefix_matches(mac, addr, prefix_bits) {
+                    Some(View::Entire)
+                } else {
+                    None
+                }
+            },
+
+            // every TCP or UDP frame matches here, the subscription's FlowSet lets only the first through
+            PackFilter::NewFlow { .. } => flow_hash(frame).map(|_| View::Entire),
+
+            PackFilter::Fragmented { .. } => {
+                let packet = frame.get(ETHER_HEADER_LEN..)?;
+
+                let fragment = match ethernet::ethertype(frame) {
+                    Some(ethernet::ETHERTYPE_IPV4) => match ipv4::parse(packet) {
+                        Some((header, _)) => header.is_fragment(),
+                        None => false
+                    },
+
+                    Some(ethernet::ETHERTYPE_IPV6) => match ipv6::parse(packet) {
+                        Some((header, payload)) => ipv6::is_fragment(header.next_header, payload),
+                        None => false
+                    },
+
+                    _ => false
+                };
+
+                if fragment {
+                    Some(View::Payload)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// where the filter's matches go, None for Closed
+    fn outlet(&self) -> Option<Outlet> {
+        let sender = match *self {
+            PackFilter::Closed => return None,
+            PackFilter::Bounded(_, ref sender) => return Some(Outlet::Bounded(sender.outlet())),
+            PackFilter::Entire(ref sender) |
+            PackFilter::EtherFrame(ref sender) |
+            PackFilter::Payload(ref sender) |
+            PackFilter::IpProtocol(_, ref sender) |
+            PackFilter::UdpPorts(_, ref sender) |
+            PackFilter::Icmpv6(ref sender) |
+            PackFilter::DstClass(_, ref sender) |
+            PackFilter::EtherType { ref sender, .. } |
+            PackFilter::MacMatch { ref sender, .. } |
+            PackFilter::NewFlow { ref sender } |
+            PackFilter::Fragmented { ref sender } => sender
+        };
+
+        Some(Outlet::Plain(sender.clone()))
+    }
+}
+
+/// a filter's channel taken out of it, so a delivery waiting on a full Block channel doesn't hold
+/// the subscription's filter lock, and set_filter or describe_filter with it
+enum Outlet {
+    Plain(Sender<Arc<Vec<u8>>>),
+    Bounded(BoundedOutlet)
+}
+
+impl Outlet {
+    /// hands view to the module, give_up is checked while a full Block channel is waited on
+    fn deliver(&self, view: Arc<Vec<u8>>, give_up: &dyn Fn() -> bool) -> Delivery {
+        match *self {
+            Outlet::Bounded(ref outlet) => outlet.send(view, give_up),
+            Outlet::Plain(ref sender) => match sender.send(view) {
+                Ok(()) => Delivery::Sent,
+                Err(_) => Delivery::Disconnected
+            }
+        }
+    }
+}
+
+/// which part of a frame a filter forwards
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum View {
+    Entire,
+    Header, // the ethernet header, frames shorter than one have none
+    Payload // everything after the ethernet header
+}
+
+/// the views of one frame, each copied out and put in an Arc the first time a filter asks for it
+///
+/// every module wanting a view is handed a clone of the same Arc, so a frame costs at most two
+/// allocations (the copy and its Arc) per distinct view, three views at most, rather than two for
+/// every module it is delivered to: with ten modules on Payload or IpProtocol filters that is two
+/// allocations a frame instead of twenty
+struct FrameViews<'a> {
+    frame: &'a [u8],
+    entire: Option<Arc<Vec<u8>>>,
+    header: Option<Arc<Vec<u8>>>,
+    payload: Option<Arc<Vec<u8>>>
+}
+
+impl<'a> FrameViews<'a> {
+    fn new(frame: &'a [u8]) -> FrameViews<'a> {
+        FrameViews {
+            frame,
+            entire: None,
+            header: None,
+            payload: None
+        }
+    }
+
+    /// view only ever comes from PackFilter::view, which checked the frame is long enough for it;
+    /// should that ever not hold the view comes out empty, since the frame came off the wire and
+    /// panicking on it would take the capture loop down with it
+    fn get(&mut self, view: View) -> Arc<Vec<u8>> {
+        let frame = self.frame;
+
+        let (cached, bytes) = match view {
+            View::Entire => (&mut self.entire, frame),
+            View::Header => (&mut self.header, frame.get(..ETHER_HEADER_LEN).unwrap_or_default()),
+            View::Payload => (&mut self.payload, frame.get(ETHER_HEADER_LEN..).unwrap_or_default())
+        };
+
+        cached.get_or_insert_with(|| Arc::new(bytes.to_vec())).clone()
+    }
+}
+
+/// what kind of address a frame is sent to, going by its destination mac
+/// broadcast is kept apart from multicast even though its group bit is set too
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DstClass {
+    Unicast,
+    Multicast, // group bit, the lowest of the first octet, set
+    Broadcast // ff:ff:ff:ff:ff:ff
+}
+
+impl DstClass {
+    /// None if the frame is too short to have a destination
+    pub fn of(frame: &[u8]) -> Option<DstClass> {
+        let dst = frame.get(..6)?;
+
+        if dst.iter().all(|&octet| octet == 0xff) {
+            Some(DstClass::Broadcast)
+        } else if dst[0] & 1 == 1 {
+            Some(DstClass::Multicast)
+        } else {
+            Some(DstClass::Unicast)
+        }
+    }
+}
+
+/// flows a NewFlow filter remembers before forgetting the least recently seen
+const FLOW_CAPACITY: usize = 4096;
+
+/// how long a flow can go quiet before its next frame counts as the first of a new one
+const FLOW_IDLE: Duration = Duration::from_secs(60);
+
+/// the flows a NewFlow filter has let through, each forgotten once it has gone FLOW_IDLE without a
+/// frame or FLOW_CAPACITY others have been seen since; a forgotten flow's next frame is let through
+/// as if it were new, and so is the first frame seen of a flow that started before the module did
+struct FlowSet {
+    seen: HashMap<u64, (u64, Instant)>, // flow hash -> its place in order and when it was last seen
+    order: BTreeMap<u64, u64>, // place -> flow hash, least recently seen first
+    next: u64
+}
+
+impl FlowSet {
+    fn new() -> FlowSet {
+        FlowSet {
+            seen: HashMap::new(),
+            order: BTreeMap::new(),
+            next: 0
+        }
+    }
+
+    /// whether flow is new, either way remembering it as seen now
+    fn first_sighting(&mut self, flow: u64) -> bool {
+        let now = Instant::now();
+
+        // order is by when each flow was last seen, so the idle ones are all at the front
+        while let Some((&place, &oldest)) = self.order.iter().next() {
+            if now.duration_since(self.seen[&oldest].1) < FLOW_IDLE {
+                break;
+            }
+
+            self.order.remove(&place);
+            self.seen.remove(&oldest);
+        }
+
+        let new = match self.seen.remove(&flow) {
+            Some((place, _)) => {
+                self.order.remove(&place);
+                false
+            },
+
+            None => true
+        };
+
+        if new && self.seen.len() >= FLOW_CAPACITY {
+            if let Some((&place, &oldest)) = self.order.iter().next() {
+                self.order.remove(&place);
+                self.seen.remove(&oldest);
+            }
+        }
+
+        self.seen.insert(flow, (self.next, now));
+        self.order.insert(self.next, flow);
+        self.next += 1;
+
+        new
+    }
+}
+
+/// a hash of the protocol, addresses and ports of an IPv4 or IPv6 TCP or UDP frame, the same both
+/// ways, None for anything else, an IPv4 fragment past the first or an IPv6 packet with extension
+/// headers, fragment headers among them, ahead of its transport
+fn flow_hash(frame: &[u8]) -> Option<u64> {
+    let (protocol, src, dst, transport) = match ethernet::ethertype(frame) {
+        Some(ethernet::ETHERTYPE_IPV4) => {
+            let (header, segment) = ipv4::parse(frame.get(ETHER_HEADER_LEN..)?)?;
+
+            if header.is_later_fragment() {
+                return None;
+            }
+
+            (header.protocol, IpAddr::V4(header.src), IpAddr::V4(header.dst), segment)
+        },
+
+        Some(ethernet::ETHERTYPE_IPV6) => {
+            let (header, segment) = ipv6::parse(frame.get(ETHER_HEADER_LEN..)?)?;
+            (IpProtocol::from_number(header.next_header), IpAddr::V6(header.src), IpAddr::V6(header.dst), segment)
+        },
+
+        _ => return None
+    };
+
+    let (src_port, dst_port) = match protocol {
+        IpProtocol::Tcp => tcp::parse(transport).map(|(header, _)| (header.src_port, header.dst_port))?,
+        IpProtocol::Udp => udp::parse(transport).map(|(header, _)| (header.src_port, header.dst_port))?,
+        _ => return None
+    };
+
+    let (first, second) = if (src, src_port) <= (dst, dst_port) {
+        ((src, src_port), (dst, dst_port))
+    } else {
+        ((dst, dst_port), (src, src_port))
+    };
+
+    let mut hasher = DefaultHasher::new();
+    (protocol.number(), first, second).hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+/// which of a frame's macs a MacMatch filter looks at
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MacField {
+    Src,
+    Dst
+}
+
+const MAC_BITS: u8 = 48;
+
+/// whether the first prefix_bits bits of mac are those of addr
+/// a prefix too long for a mac, only possible if the filter was built by hand, matches nothing
+fn mac_prefix_matches(mac: &[u8], addr: MacAddr, prefix_bits: u8) -> bool {
+    if prefix_bits > MAC_BITS {
+        return false;
+    }
+
+    let MacAddr(a, b, c, d, e, f) = addr;
+    let whole = usize::from(prefix_bits / 8);
+    let partial = prefix_bits % 8;
+    let addr = [a, b, c, d, e, f];
+
+    if mac.get(..whole) != addr.get(..whole) {
+        return false;
+    }
+
+    // the bits of the next octet still inside the prefix, counting from the top
+    let mask = !(0xffu8 >> partial);
+
+    match (mac.get(whole), addr.get(whole)) {
+        _ if partial == 0 => true,
+        (Some(mac), Some(addr)) => mac & mask == addr & mask,
+        _ => false
+    }
+}
+
+/// everything after the ethernet header, if the frame carries IPv4
+fn ipv4_packet(frame: &[u8]) -> Option<&[u8]> {
+    if ethernet::ethertype(frame) == Some(ethernet::ETHERTYPE_IPV4) {
+        frame.get(ETHER_HEADER_LEN..)
+    } else {
+        None
+    }
+}
+
+/// a module's standing in the dispatcher
+pub(crate) struct Subscription {
+    interface: String, // only frames captured here are delivered, empty for every interface
+    filter: Mutex<PackFilter>, // only locked while a single frame is checked against it
+    delivered: AtomicU64,
+    paused: AtomicBool, // frames are still matched but dropped rather than delivered
+    paused_dropped: AtomicU64,
+    disconnected: AtomicBool, // the filter's receiver is gone, nothing more is sent until the filter is replaced
+    overflowed: AtomicU64, // frames a bounded filter dropped because its channel was full
+    closed: AtomicBool, // the module is being removed, a delivery blocked on it gives up
+    flows: Mutex<FlowSet>, // what a NewFlow filter has already let through, empty for any other filter
+    sampled: AtomicU64, // delivered as of the rate thread's last sample
+    rate: AtomicU64, // the bits of the f64 frames a second average, only written by the rate thread
+    span: ModuleSpan
+}
+
+impl Subscription {
+    pub(crate) fn delivered(&self) -> u64 {
+        self.delivered.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn describe_filter(&self) -> String {
+        self.filter.lock().unwrap().describe()
+    }
+
+    /// swaps in a new filter, taking effect from the next frame dispatched
+    pub(crate) fn set_filter(&self, filter: PackFilter) {
+        *self.filter.lock().unwrap() = filter;
+        *self.flows.lock().unwrap() = FlowSet::new();
+        self.disconnected.store(false, Ordering::Relaxed);
+    }
+
+    pub(crate) fn is_disconnected(&self) -> bool {
+        self.disconnected.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    pub(crate) fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// frames that matched while paused and were dropped
+    pub(crate) fn paused_dropped(&self) -> u64 {
+        self.paused_dropped.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn overflowed(&self) -> u64 {
+        self.overflowed.load(Ordering::Relaxed)
+    }
+
+    /// lets a dispatch blocked on this subscription's full channel go, ahead of it being unsubscribed
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Ordering::Relaxed);
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+
+    /// whether frame starts a flow this subscription's NewFlow filter hasn't let through lately
+    fn first_of_flow(&self, frame: &[u8]) -> bool {
+        match flow_hash(frame) {
+            Some(flow) => self.flows.lock().unwrap().first_sighting(flow),
+            None => false
+        }
+    }
+
+    /// the moving average of frames delivered a second, 0 until the rate thread has sampled it
+    pub(crate) fn rate_pps(&self) -> f64 {
+        f64::from_bits(self.rate.load(Ordering::Relaxed))
+    }
+
+    /// folds what was delivered in the elapsed seconds since the last sample into the average
+    fn sample(&self, elapsed: f64, alpha: f64) {
+        let delivered = self.delivered();
+        let since = delivered.saturating_sub(self.sampled.swap(delivered, Ordering::Relaxed));
+        let rate = alpha * since as f64 / elapsed + (1.0 - alpha) * self.rate_pps();
+
+        self.rate.store(rate.to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// fans incoming frames out to subscribed modules
+pub struct Dispatcher {
+    subscriptions: RwLock<Vec<(String, Arc<Subscription>)>>, // only written on subscribing and unsubscribing, dispatch workers share the read lock
+    frames_received: AtomicU64,
+    bytes_received: AtomicU64,
+    capture_drops: AtomicU64, // as last reported by the capture source
+    drops_reported: AtomicBool
+}
+
+impl Default for Dispatcher {
+    fn default() -> Dispatcher {
+        Dispatcher::new()
+    }
+}
+
+impl Dispatcher {
+    pub fn new() -> Dispatcher {
+        Dispatcher {
+            subscriptions: RwLock::new(Vec::new()),
+            frames_received: AtomicU64::new(0),
+            bytes_received: AtomicU64::new(0),
+            capture_drops: AtomicU64::new(0),
+            drops_reported: AtomicBool::new(false)
+        }
+    }
+
+    pub(crate) fn subscribe(&self, name: &str, filter: PackFilter) -> Arc<Subscription> {
+        self.subscribe_on("", name, filter, ModuleSpan::none())
+    }
+
+    pub(crate) fn subscribe_on(&self, interface: &str, name: &str, filter: PackFilter, span: ModuleSpan) -> Arc<Subscription> {
+        let subscription = Arc::new(Subscription {
+            interface: String::from(interface),
+            filter: Mutex::new(filter),
+            delivered: AtomicU64::new(0),
+            paused: AtomicBool::new(false),
+            paused_dropped: AtomicU64::new(0),
+            disconnected: AtomicBool::new(false),
+            overflowed: AtomicU64::new(0),
+            closed: AtomicBool::new(false),
+            flows: Mutex::new(FlowSet::new()),
+            sampled: AtomicU64::new(0),
+            rate: AtomicU64::new(0f64.to_bits()),
+            span
+        });
+
+        self.subscriptions.write().unwrap().push((String::from(name), subscription.clone()));
+        subscription
+    }
+
+    pub(crate) fn unsubscribe(&self, name: &str) {
+        self.subscriptions.write().unwrap().retain(|(subscriber, _)| subscriber != name);
+    }
+
+    /// hands frame to every module whose filter wants it, returning how many it was sent to
+    /// the frame isn't tagged with an interface, so modules on every interface get it
+    pub fn dispatch(&self, frame: &[u8]) -> usize {
+        self.dispatch_from("", frame)
+    }
+
+    /// like dispatch, for a frame captured on interface
+    pub fn dispatch_from(&self, interface: &str, frame: &[u8]) -> usize {
+        self.dispatch_captured(interface, frame, frame.len())
+    }
+
+    /// dispatch_from for a frame that may have been cut short of the len bytes it was captured at,
+    /// which is what the received byte counters go by
+    pub(crate) fn dispatch_captured(&self, interface: &str, frame: &[u8], len: usize) -> usize {
+        self.frames_received.fetch_add(1, Ordering::Relaxed);
+        self.bytes_received.fetch_add(len as u64, Ordering::Relaxed);
+
+        let mut deliveries = 0;
+        let mut nameless_gone = false;
+        let mut views = FrameViews::new(frame);
+
+        for (name, subscription) in self.subscriptions.read().unwrap().iter() {
+            if !interface.is_empty() && !subscription.interface.is_empty() && subscription.interface != interface {
+                continue;
+            }
+
+            if subscription.is_disconnected() {
+                continue;
+            }
+
+            // the filter is only locked to match the frame, a Block delivery waits with it let go
+            let (view, outlet) = {
+                let filter = subscription.filter.lock().unwrap();
+
+                // a Closed module works off the host table alone, it is never handed a frame or counted as
+                // paused_dropped, though it stays subscribed in case set_filter opens it later
+                if let PackFilter::Closed = *filter {
+                    continue;
+                }
+
+                match filter.view(frame) {
+                    // later frames of a flow don't match a NewFlow filter at all, paused or not
+                    Some(_) if filter.new_flows_only() && !subscription.first_of_flow(frame) => continue,
+
+                    Some(_) if subscription.is_paused() => {
+                        subscription.paused_dropped.fetch_add(1, Ordering::Relaxed);
+                        subscription.span.dropped_paused(frame.len());
+                        continue;
+                    },
+
+                    Some(view) => match filter.outlet() {
+                        Some(outlet) => (views.get(view), outlet),
+                        None => continue
+                    },
+
+                    None => continue
+                }
+            };
+
+            match outlet.deliver(view, &|| subscription.is_closed()) {
+                Delivery::Sent => {
+                    subscription.delivered.fetch_add(1, Ordering::Relaxed);
+                    subscription.span.delivered(frame.len());
+                    deliveries += 1;
+                },
+
+                Delivery::Overflowed => {
+                    subscription.overflowed.fetch_add(1, Ordering::Relaxed);
+                },
+
+                // the module is left for Framework::reap_finished_modules, which unsubscribes it
+                Delivery::Disconnected => {
+                    subscription.disconnected.store(true, Ordering::Relaxed);
+                    subscription.span.disconnected();
+                    nameless_gone |= name.is_empty();
+                }
+            }
+        }
+
+        // nameless subscribers have no module to be removed with, so they go once their receiver does
+        if nameless_gone {
+            self.subscriptions.write().unwrap().retain(|(name, subscription)| !name.is_empty() || !subscription.is_disconnected());
+        }
+
+        trace::frame_received(interface, len, deliveries);
+        deliveries
+    }
+
+    pub fn frames_received(&self) -> u64 {
+        self.frames_received.load(Ordering::Relaxed)
+    }
+
+    /// full length of every frame received, headers included, however much a snaplen cut off
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received.load(Ordering::Relaxed)
+    }
+
+    /// frames received against frames the capture source says it dropped before we saw them
+    pub fn capture_stats(&self) -> CaptureStats {
+        CaptureStats {
+            received: self.frames_received(),
+            dropped: self.capture_drops.load(Ordering::Relaxed),
+            drops_reported: self.drops_reported.load(Ordering::Relaxed)
+        }
+    }
+
+    fn record_drops(&self, dropped: u64) {
+        self.capture_drops.store(dropped, Ordering::Relaxed);
+        self.drops_reported.store(true, Ordering::Relaxed);
+    }
+
+    /// frames delivered so far to each subscriber
+    pub fn delivered(&self) -> Vec<(String, u64)> {
+        self.subscriptions.read().unwrap().iter()
+            .map(|(name, subscription)| (name.clone(), subscription.delivered()))
+            .collect()
+    }
+
+    fn sample_rates(&self, elapsed: f64, alpha: f64) {
+        for (_, subscription) in self.subscriptions.read().unwrap().iter() {
+            subscription.sample(elapsed, alpha);
+        }
+    }
+}
+
+/// how well capture is keeping up with the link
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CaptureStats {
+    pub received: u64,
+    pub dropped: u64,
+    pub drops_reported: bool // false when the source can't tell, dropped is then always 0
+}
+
+/// frame and byte totals for everything received and sent
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TrafficTotals {
+    pub frames_received: u64,
+    pub bytes_received: u64,
+    pub frames_sent: u64,
+    pub bytes_sent: u64
+}
+
+/// counters kept by the send consumer
+pub struct SendStats {
+    frames_sent: AtomicU64,
+    bytes_sent: AtomicU64,
+    send_drops: AtomicU64,
+    suppressed: AtomicU64,
+    deduplicated: AtomicU64,
+    expired: AtomicU64
+}
+
+impl Default for SendStats {
+    fn default() -> SendStats {
+        SendStats::new()
+    }
+}
+
+impl SendStats {
+    pub fn new() -> SendStats {
+        SendStats {
+            frames_sent: AtomicU64::new(0),
+            bytes_sent: AtomicU64::new(0),
+            send_drops: AtomicU64::new(0),
+            suppressed: AtomicU64::new(0),
+            deduplicated: AtomicU64::new(0),
+            expired: AtomicU64::new(0)
+        }
+    }
+
+    /// frames a dry run logged instead of sending
+    pub fn suppressed(&self) -> u64 {
+        self.suppressed.load(Ordering::Relaxed)
+    }
+
+    pub fn frames_sent(&self) -> u64 {
+        self.frames_sent.load(Ordering::Relaxed)
+    }
+
+    /// full length of every frame sent, headers included
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent.load(Ordering::Relaxed)
+    }
+
+    /// frames taken off the queue that failed to go out
+    pub fn send_drops(&self) -> u64 {
+        self.send_drops.load(Ordering::Relaxed)
+    }
+
+    /// frames dropped for being identical to one sent within the dedup window
+    pub fn deduplicated(&self) -> u64 {
+        self.deduplicated.load(Ordering::Relaxed)
+    }
+
+    /// frames dropped because their expiry passed while they were queued
+    pub fn expired(&self) -> u64 {
+        self.expired.load(Ordering::Relaxed)
+    }
+}
+
+/// anything frames can be read from
+pub trait PacketSource: Send {
+    fn next_frame(&mut self) -> io::Result<&[u8]>;
+
+    /// frames lost before they could be read, since the source was opened
+    /// None for sources that don't keep count
+    fn dropped(&self) -> Option<u64> {
+        None
+    }
+}
+
+// pnet doesn't expose the kernel's drop counters and a bare receiver doesn't know its interface,
+// so it reports none, see InterfaceSource
+impl PacketSource for Box<dyn DataLinkReceiver> {
+    fn next_frame(&mut self) -> io::Result<&[u8]> {
+        self.next()
+    }
+}
+
+/// how often InterfaceSource rereads its interface's drop counter
+const DROPS_REFRESH: Duration = Duration::from_secs(1);
+
+/// a pnet receiver together with the interface it reads, which lets it report the drops the
+/// kernel counted on that interface since it was opened, where the platform counts them
+pub(crate) struct InterfaceSource {
+    receiver: Box<dyn DataLinkReceiver>,
+    interface: String,
+    opened_with: Option<u64>, // the counter as it stood when opened, earlier drops weren't ours
+    dropped: Option<u64>,
+    refreshed: Instant
+}
+
+impl InterfaceSource {
+    pub(crate) fn new(receiver: Box<dyn DataLinkReceiver>, interface: &str) -> InterfaceSource {
+        InterfaceSource {
+            receiver,
+            interface: String::from(interface),
+            opened_with: os::rx_dropped(interface),
+            dropped: None,
+            refreshed: Instant::now()
+        }
+    }
+}
+
+impl PacketSource for InterfaceSource {
+    fn next_frame(&mut self) -> io::Result<&[u8]> {
+        // dropped is asked after every frame, the counter is only reread once in a while
+        if let Some(opened_with) = self.opened_with {
+            if self.dropped.is_none() || self.refreshed.elapsed() >= DROPS_REFRESH {
+                self.dropped = os::rx_dropped(&self.interface).map(|now| now.saturating_sub(opened_with));
+                self.refreshed = Instant::now();
+            }
+        }
+
+        self.receiver.next()
+    }
+
+    fn dropped(&self) -> Option<u64> {
+        self.dropped
+    }
+}
+
+/// anything frames can be written to
+pub trait PacketSink: Send {
+    fn send_frame(&mut self, frame: &[u8]) -> io::Result<()>;
+}
+
+impl PacketSink for Box<dyn DataLinkSender> {
+    fn send_frame(&mut self, frame: &[u8]) -> io::Result<()> {
+        match self.send_to(frame, None) {
+            Some(result) => result,
+            None => Err(io::Error::other("no room in the write buffer"))
+        }
+    }
+}
+
+/// frames handed over a channel by a capture loop living outside the library, see Framework::init_task_mpscs
+pub(crate) struct ChannelSource {
+    frames: Receiver<Arc<Vec<u8>>>,
+    frame: Arc<Vec<u8>>
+}
+
+impl ChannelSource {
+    pub fn new(frames: Receiver<Arc<Vec<u8>>>) -> ChannelSource {
+        ChannelSource { frames, frame: Arc::new(Vec::new()) }
+    }
+}
+
+impl PacketSource for ChannelSource {
+    fn next_frame(&mut self) -> io::Result<&[u8]> {
+        // the timeout lets the capture loop notice it's been stopped even while no frames come in
+        match self.frames.recv_timeout(Duration::from_millis(100)) {
+            Ok(frame) => {
+                self.frame = frame;
+                Ok(&self.frame)
+            },
+
+            Err(mpsc::RecvTimeoutError::Timeout) => Err(io::Error::new(io::ErrorKind::TimedOut, "no frame yet")),
+            Err(mpsc::RecvTimeoutError::Disconnected) => Err(io::Error::new(io::ErrorKind::BrokenPipe, "capture loop has gone"))
+        }
+    }
+}
+
+/// passes frames on to a send loop living outside the library, see Framework::init_task_mpscs
+pub(crate) struct ChannelSink(pub Sender<Vec<u8>>);
+
+impl PacketSink for ChannelSink {
+    fn send_frame(&mut self, frame: &[u8]) -> io::Result<()> {
+        self.0.send(frame.to_vec()).map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "send loop has gone"))
+    }
+}
+
+/// samples every subscription's delivered counter each interval until the dispatcher is dropped,
+/// see the header above; alpha is the weight the latest interval gets in the average
+pub(crate) fn rate_loop(dispatcher: Weak<Dispatcher>, interval: Duration, alpha: f64) {
+    let mut last = Instant::now();
+
+    loop {
+        thread::sleep(interval);
+
+        let dispatcher = match dispatcher.upgrade() {
+            Some(dispatcher) => dispatcher,
+            None => return
+        };
+
+        let now = Instant::now();
+        dispatcher.sample_rates(now.duration_since(last).as_secs_f64(), alpha);
+        last = now;
+    }
+}
+
+/// frames each dispatch worker can have waiting before the capture loop waits on it
+const WORKER_QUEUE_LEN: usize = 1024;
+
+/// reads frames from source into the dispatcher until capturing is cleared or the source fails
+/// with more than one worker, frames are dispatched by that many threads, and with a snaplen they
+/// are cut to it first, see the header above
+pub(crate) fn capture_loop<S: PacketSource>(source: S, interface: &str, dispatcher: Arc<Dispatcher>, capturing: Arc<AtomicBool>, workers: usize, snaplen: Option<usize>) {
+    if workers <= 1 {
+        return read_frames(source, &dispatcher, &capturing, snaplen, |frame, len| { dispatcher.dispatch_captured(interface, frame, len); });
+    }
+
+    let mut queues: Vec<SyncSender<(Vec<u8>, usize)>> = Vec::with_capacity(workers);
+    let mut handles = Vec::with_capacity(workers);
+
+    for _ in 0..workers {
+        let (queue, frames) = mpsc::sync_channel::<(Vec<u8>, usize)>(WORKER_QUEUE_LEN);
+        let dispatcher = dispatcher.clone();
+        let interface = String::from(interface);
+
+        queues.push(queue);
+        handles.push(thread::spawn(move || {
+            for (frame, len) in frames {
+                dispatcher.dispatch_captured(&interface, &frame, len);
+            }
+        }));
+    }
+
+    read_frames(source, &dispatcher, &capturing, snaplen, |frame, len| {
+        // a worker only goes away by panicking, its frames are lost with it
+        let _ = queues[conversation_hash(frame) as usize % workers].send((frame.to_vec(), len));
+    });
+
+    // the workers finish what they were queued, then see their queues close
+    drop(queues);
+
+    for handle in handles {
+        let _ = handle.join();
+    }
+}
+
+/// the same for both directions of a conversation between two hosts, see the header above
+fn conversation_hash(frame: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    let (first, second) = match ethernet::ethertype(frame) {
+        Some(ethernet::ETHERTYPE_IPV4) => (frame.get(26..30), frame.get(30..34)),
+        Some(ethernet::ETHERTYPE_IPV6) => (frame.get(22..38), frame.get(38..54)),
+        _ => (frame.get(..6), frame.get(6..12))
+    };
+
+    if first <= second {
+        (first, second).hash(&mut hasher);
+    } else {
+        (second, first).hash(&mut hasher);
+    }
+
+    hasher.finish()
+}
+
+/// hands every frame read from source to dispatch, cut to snaplen, along with the length it was
+/// read at, until capturing is cleared or the source fails
+fn read_frames<S, D>(mut source: S, dispatcher: &Dispatcher, capturing: &AtomicBool, snaplen: Option<usize>, mut dispatch: D)
+    where S: PacketSource,
+          D: FnMut(&[u8], usize)
+{
+    while capturing.load(Ordering::Relaxed) {
+        match source.next_frame() {
+            Ok(frame) => dispatch(match snaplen {
+                Some(snaplen) if frame.len() > snaplen => &frame[..snaplen],
+                _ => frame
+            }, frame.len()),
+
+            // the read timeout expiring just gives us a chance to check capturing again
+            Err(ref e) if e.kind() == io::ErrorKind::TimedOut || e.kind() == io::ErrorKind::WouldBlock => (),
+
+            Err(_) => break
+        }
+
+        if let Some(dropped) = source.dropped() {
+            dispatcher.record_drops(dropped);
+        }
+    }
+}
+
+/// how urgently a queued frame should go out, see Framework::get_packet_queue_at
+///
+/// the send consumer always drains higher priorities first, so a module that keeps the High or
+/// Normal queue full starves everything below it: Low frames only go out once the queues above are
+/// empty. FrameworkConfig::send_fairness bounds how long a lower priority waits
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SendPriority {
+    High, // traffic that has to keep flowing under load, like the frames keeping a poison in place
+    #[default]
+    Normal, // what get_packet_queue hands out
+    Low // bulk traffic that can wait, like scan probes
+}
+
+impl SendPriority {
+    /// every priority, highest first
+    pub const ALL: [SendPriority; 3] = [SendPriority::High, SendPriority::Normal, SendPriority::Low];
+
+    pub(crate) fn level(self) -> usize {
+        self as usize
+    }
+}
+
+/// a frame for the send consumer along with how to treat it, see Framework::get_send_queue
+/// `QueuedFrame::new(frame).priority(SendPriority::High).expires_in(Duration::from_secs(2))`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueuedFrame {
+    pub frame: Vec<u8>,
+    pub priority: SendPriority,
+    pub expires: Option<Instant>, // dropped instead of sent if still queued at this point, None never expires
+    pub interface: Option<String> // the interface to send on, None for the configured one
+}
+
+impl QueuedFrame {
+    /// Normal priority, never expires, the same as sending frame on get_packet_queue
+    pub fn new(frame: Vec<u8>) -> QueuedFrame {
+        QueuedFrame {
+            frame,
+            priority: SendPriority::Normal,
+            expires: None,
+            interface: None
+        }
+    }
+
+    pub fn priority(mut self, priority: SendPriority) -> QueuedFrame {
+        self.priority = priority;
+        self
+    }
+
+    pub fn expires_at(mut self, deadline: Instant) -> QueuedFrame {
+        self.expires = Some(deadline);
+        self
+    }
+
+    /// expires timeout from now
+    pub fn expires_in(self, timeout: Duration) -> QueuedFrame {
+        self.expires_at(Instant::now() + timeout)
+    }
+
+    /// sent on interface rather than the configured one, see the header above
+    pub fn on_interface(mut self, interface: &str) -> QueuedFrame {
+        self.interface = Some(String::from(interface));
+        self
+    }
+
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires.is_some_and(|deadline| now >= deadline)
+    }
+}
+
+/// how the send consumer treats what it takes off the queues
+pub(crate) struct SendOptions {
+    pub dry_run: bool,
+    pub batch_size: usize,
+    pub dedup_window: Option<Duration>,
+    pub fairness: usize, // frames sent ahead of a waiting lower priority before one of its frames goes, 0 never
+    pub interface: String // the configured interface, whose frames go to the sink the consumer was started with
+}
+
+/// the sinks of the added interfaces, see Framework::start_sender_on
+pub(crate) type Sinks = Arc<Mutex<HashMap<String, Box<dyn PacketSink>>>>;
+
+/// the send consumer's inputs
+pub(crate) struct SendQueues {
+    pub tagged: Sender<QueuedFrame>, // what the rest feed, for modules that set a frame's priority or expiry themselves
+    pub plain: Vec<Sender<Vec<u8>>>, // a queue for each SendPriority, indexed highest first
+    pub on: HashMap<String, Vec<Sender<Vec<u8>>>> // likewise for each added interface with a sink
+}
+
+/// each plain queue gets a thread passing its frames on, which ends once every clone of the queue is dropped
+pub(crate) fn send_queues() -> (SendQueues, Receiver<QueuedFrame>) {
+    let (tagged, frames) = mpsc::channel();
+    let plain = plain_queues(&tagged, None);
+
+    (SendQueues { tagged, plain, on: HashMap::new() }, frames)
+}
+
+/// a queue for each SendPriority feeding tagged, its frames tagged with interface too if given
+pub(crate) fn plain_queues(tagged: &Sender<QueuedFrame>, interface: Option<&str>) -> Vec<Sender<Vec<u8>>> {
+    SendPriority::ALL.iter().map(|&priority| {
+        let (queue, untagged) = mpsc::channel::<Vec<u8>>();
+        let tagged = tagged.clone();
+        let interface = interface.map(String::from);
+
+        thread::spawn(move || for frame in untagged {
+            let mut queued = QueuedFrame::new(frame).priority(priority);
+            queued.interface = interface.clone();
+
+            if tagged.send(queued).is_err() {
+                break;
+            }
+        });
+
+        queue
+    }).collect()
+}
+
+/// writes queued frames to sink until every sender for the queue is gone
+/// in a dry run nothing reaches sink, each frame is hexdumped to the logger instead
+/// runs until every sender of queue has been dropped, a frame that fails to send is logged and counted
+/// frames are taken batch_size at a time, highest priority first, then the queues are checked again
+/// frames tagged with an added interface go to its sink in sinks instead, see the header above
+pub(crate) fn send_loop<K: PacketSink>(mut sink: K, queue: Receiver<QueuedFrame>, sinks: Sinks, stats: Arc<SendStats>, options: SendOptions, logger: Logger) {
+    let batch_size = options.batch_size.max(1);
+    let mut dedup = options.dedup_window.map(Dedup::new);
+    let mut waiting: Vec<VecDeque<QueuedFrame>> = SendPriority::ALL.iter().map(|_| VecDeque::new()).collect();
+    let mut passed_over = vec![0; waiting.len()]; // frames sent ahead of each priority while it had some waiting
+
+    loop {
+        // only waited on when nothing is left over, the rest is whatever had already been queued
+        if waiting.iter().all(VecDeque::is_empty) {
+            match queue.recv() {
+                Ok(queued) => waiting[queued.priority.level()].push_back(queued),
+                Err(_) => break
+            }
+        }
+
+        for queued in queue.try_iter() {
+            waiting[queued.priority.level()].push_back(queued);
+        }
+
+        // a frame of higher priority queued during a batch only waits for the rest of that batch
+        for _ in 0..batch_size {
+            let level = match next_level(&waiting, &mut passed_over, options.fairness) {
+                Some(level) => level,
+                None => break
+            };
+
+            let queued = waiting[level].pop_front().unwrap();
+
+            // checked as late as possible, a frame can sit behind higher priorities for a while
+            if queued.is_expired(Instant::now()) {
+                stats.expired.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+
+            let frame = queued.frame;
+
+            if let Some(ref mut dedup) = dedup {
+                if dedup.is_repeat(&frame) {
+                    stats.deduplicated.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+            }
+
+            match queued.interface {
+                Some(ref interface) if *interface != options.interface => match sinks.lock().unwrap().get_mut(interface) {
+                    Some(sink) => send_frame(sink.as_mut(), &frame, &stats, options.dry_run, &logger),
+
+                    None => {
+                        stats.send_drops.fetch_add(1, Ordering::Relaxed);
+                        logger.log(&format!("failed to send {} byte frame: {}: no sender open on this interface", frame.len(), interface));
+                    }
+                },
+
+                _ => send_frame(&mut sink, &frame, &stats, options.dry_run, &logger)
+            }
+        }
+    }
+}
+
+/// the priority to send from next: the highest with frames waiting, unless fairness says a lower one
+/// has been passed over long enough, in which case the lowest such
+fn next_level<T>(waiting: &[VecDeque<T>], passed_over: &mut [usize], fairness: usize) -> Option<usize> {
+    let highest = waiting.iter().position(|frames| !frames.is_empty())?;
+    let starved = (highest + 1..waiting.len()).rev()
+        .find(|&level| fairness > 0 && !waiting[level].is_empty() && passed_over[level] >= fairness);
+    let level = starved.unwrap_or(highest);
+
+    passed_over[level] = 0;
+
+    for lower in level + 1..waiting.len() {
+        if waiting[lower].is_empty() {
+            passed_over[lower] = 0;
+        } else {
+            passed_over[lower] += 1;
+        }
+    }
+
+    Some(level)
+}
+
+/// content hashes of recently sent frames and when each was last let through
+struct Dedup {
+    window: Duration,
+    sent: HashMap<u64, Instant>,
+    pruned: Instant
+}
+
+impl Dedup {
+    fn new(window: Duration) -> Dedup {
+        Dedup {
+            window,
+            sent: HashMap::new(),
+            pruned: Instant::now()
+        }
+    }
+
+    /// whether frame matches one let through within the window, remembering it if not
+    fn is_repeat(&mut self, frame: &[u8]) -> bool {
+        let now = Instant::now();
+        let mut hasher = DefaultHasher::new();
+        frame.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        // only frames actually let through restart the window, so a steady repeat still goes out once a window
+        if let Some(&sent) = self.sent.get(&hash) {
+            if now.duration_since(sent) < self.window {
+                return true;
+            }
+        }
+
+        // at most once a window, so a queue of distinct frames doesn't rescan the map for each one
+        if now.duration_since(self.pruned) >= self.window {
+            let window = self.window;
+            self.sent.retain(|_, &mut sent| now.duration_since(sent) < window);
+            self.pruned = now;
+        }
+
+        self.sent.insert(hash, now);
+        false
+    }
+}
+
+fn send_frame(sink: &mut dyn PacketSink, frame: &[u8], stats: &SendStats, dry_run: bool, logger: &Logger) {
+    if dry_run {
+        stats.suppressed.fetch_add(1, Ordering::Relaxed);
+        trace::frame_suppressed(frame.len());
+        logger.log(&format!("[dry-run] suppressed {} byte frame\n{}", frame.len(), hexdump(frame)));
+        return;
+    }
+
+    match sink.send_frame(frame) {
+        Ok(()) => {
+            stats.frames_sent.fetch_add(1, Ordering::Relaxed);
+            stats.bytes_sent.fetch_add(frame.len() as u64, Ordering::Relaxed);
+            trace::frame_sent(frame.len());
+        },
+
+        Err(err) => {
+            stats.send_drops.fetch_add(1, Ordering::Relaxed);
+            trace::send_failed(frame.len(), &err);
+            logger.log(&format!("failed to send {} byte frame: {}", frame.len(), err));
+        }
+    }
+}
+
+/// offset, hex and ascii columns, 16 bytes to a line
+pub fn hexdump(bytes: &[u8]) -> String {
+    let mut dump = String::new();
+
+    for (line, chunk) in bytes.chunks(16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let ascii: String = chunk.iter()
+            .map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' })
+            .collect();
+
+        dump.push_str(&format!("{:04x}  {:<47}  {}\n", line * 16, hex.join(" "), ascii));
+    }
+
+    dump
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{
+        HashMap,
+        VecDeque
+    };
+    use std::io;
+    use std::sync::{
+        Arc,
+        Mutex,
+        atomic::AtomicBool,
+        atomic::AtomicUsize,
+        atomic::Ordering,
+        mpsc
+    };
+    use std::thread;
+    use std::time::Duration;
+
+    use super::{
+        ChannelPolicy,
+        Dispatcher,
+        PackFilter,
+        PacketSink,
+        PacketSource,
+        QueuedFrame,
+        SendOptions,
+        SendStats,
+        capture_loop,
+        send_loop
+    };
+    use Logger;
+
+    const FRAME: [u8; 14] = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 2, 0, 0, 0, 0, 1, 0x08, 0x00];
+
+    /// hands out the frames it was made with, then reports the link gone so the capture loop ends
+    struct MockSource {
+        frames: VecDeque<Vec<u8>>,
+        frame: Vec<u8>
+    }
+
+    impl MockSource {
+        fn new(count: usize) -> MockSource {
+            MockSource {
+                frames: (0..count).map(|_| FRAME.to_vec()).collect(),
+                frame: Vec::new()
+            }
+        }
+    }
+
+    impl PacketSource for MockSource {
+        fn next_frame(&mut self) -> io::Result<&[u8]> {
+            match self.frames.pop_front() {
+                Some(frame) => {
+                    self.frame = frame;
+                    Ok(&self.frame)
+                },
+
+                None => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "out of frames"))
+            }
+        }
+    }
+
+    /// fails every frame, counting how many it was handed
+    struct BrokenSink(Arc<AtomicUsize>);
+
+    impl PacketSink for BrokenSink {
+        fn send_frame(&mut self, _frame: &[u8]) -> io::Result<()> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Err(io::Error::other("link is down"))
+        }
+    }
+
+    fn options() -> SendOptions {
+        SendOptions {
+            dry_run: false,
+            batch_size: 1,
+            dedup_window: None,
+            fairness: 0,
+            interface: String::from("eth0")
+        }
+    }
+
+    fn quiet() -> Logger {
+        let logger = Logger::new();
+        let _ = logger.replace(Box::new(|_: &str| ()));
+        logger
+    }
+
+    #[test]
+    fn closed_filters_get_nothing_while_entire_gets_everything() {
+        let dispatcher = Arc::new(Dispatcher::new());
+        let (entire_tx, entire_rx) = mpsc::channel();
+
+        let closed = dispatcher.subscribe("control", PackFilter::Closed);
+        let entire = dispatcher.subscribe("sniffer", PackFilter::Entire(entire_tx));
+
+        capture_loop(MockSource::new(5), "eth0", dispatcher.clone(), Arc::new(AtomicBool::new(true)), 1, None);
+
+        assert_eq!(dispatcher.frames_received(), 5);
+        assert_eq!(closed.delivered(), 0);
+        assert_eq!(closed.paused_dropped(), 0);
+        assert_eq!(entire.delivered(), 5);
+        assert_eq!(entire_rx.try_iter().count(), 5);
+    }
+
+    #[test]
+    fn cut_frames_are_counted_at_their_captured_length() {
+        for &workers in &[1, 2] {
+            let dispatcher = Arc::new(Dispatcher::new());
+            let (entire_tx, entire_rx) = mpsc::channel();
+
+            let _entire = dispatcher.subscribe("sniffer", PackFilter::Entire(entire_tx));
+
+            capture_loop(MockSource::new(3), "eth0", dispatcher.clone(), Arc::new(AtomicBool::new(true)), workers, Some(6));
+
+            assert_eq!(dispatcher.bytes_received(), 3 * FRAME.len() as u64);
+            assert_eq!(entire_rx.try_iter().map(|frame| frame.len()).collect::<Vec<_>>(), vec![6, 6, 6]);
+        }
+    }
+
+    #[test]
+    fn a_stalled_block_delivery_leaves_the_filter_unlocked() {
+        let dispatcher = Arc::new(Dispatcher::new());
+        let (filter, frames) = PackFilter::bounded(1, ChannelPolicy::Block, PackFilter::Entire);
+        let blocked = dispatcher.subscribe("forensic", filter);
+
+        assert_eq!(dispatcher.dispatch(&FRAME), 1);
+
+        let stalled = dispatcher.clone();
+        let dispatching = thread::spawn(move || stalled.dispatch(&FRAME));
+
+        // the second frame has no room, so the dispatch above waits until the module reads one
+        thread::sleep(Duration::from_millis(50));
+        assert!(!dispatching.is_finished());
+        assert_eq!(blocked.describe_filter(), "entire frames, at most 1 queued (Block)");
+
+        assert!(frames.recv().is_ok());
+        assert_eq!(dispatching.join().unwrap(), 1);
+    }
+
+    #[test]
+    fn send_errors_are_counted_and_the_consumer_keeps_going() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let stats = Arc::new(SendStats::new());
+        let (queue, frames) = mpsc::channel();
+
+        let sink = BrokenSink(attempts.clone());
+        let sinks = Arc::new(Mutex::new(HashMap::new()));
+        let consumer_stats = stats.clone();
+        let consumer = thread::spawn(move || send_loop(sink, frames, sinks, consumer_stats, options(), quiet()));
+
+        for _ in 0..3 {
+            queue.send(QueuedFrame::new(FRAME.to_vec())).unwrap();
+        }
+
+        // a closed queue is a clean shutdown, the consumer returns rather than panicking
+        drop(queue);
+        consumer.join().unwrap();
+
+        assert_eq!(attempts.load(Ordering::Relaxed), 3);
+        assert_eq!(stats.send_drops(), 3);
+        assert_eq!(stats.frames_sent(), 0);
+    }
+
+    #[test]
+    fn disconnected_filters_are_skipped_until_reaped() {
+        let dispatcher = Dispatcher::new();
+        let (dead_tx, dead_rx) = mpsc::channel();
+        let (live_tx, live_rx) = mpsc::channel();
+
+        let dead = dispatcher.subscribe("dead", PackFilter::Entire(dead_tx));
+        dispatcher.subscribe("live", PackFilter::Entire(live_tx));
+        drop(dead_rx);
+
+        assert_eq!(dispatcher.dispatch(&FRAME), 1);
+        assert!(dead.is_disconnected());
+
+        // marked once, after that it isn't tried again, while its sibling goes on getting every frame
+        assert_eq!(dispatcher.dispatch(&FRAME), 1);
+        assert_eq!(dead.delivered(), 0);
+        assert_eq!(live_rx.try_iter().count(), 2);
+
+        // a named subscription stays until its module is reaped, which unsubscribes it
+        assert!(dispatcher.delivered().iter().any(|(name, _)| name == "dead"));
+    }
+
+    #[test]
+    fn nameless_subscriptions_go_with_their_receiver() {
+        let dispatcher = Dispatcher::new();
+        let (tx, rx) = mpsc::channel();
+
+        dispatcher.subscribe("", PackFilter::Entire(tx));
+        drop(rx);
+
+        assert_eq!(dispatcher.dispatch(&FRAME), 0);
+        assert!(dispatcher.delivered().is_empty());
+    }
+}
@@ -0,0 +1,65 @@
+/*
+Runtime errors from modules that carry on regardless. A module that can only fail by returning Err
+from its thread is only noticed once it is reaped, so a module that hits something worth knowing
+about mid-run, an interface going down or a write failing, sends it down the errors channel in its
+HookEnv instead, tagged with its own name, and keeps going unless it decides otherwise. The
+Framework logs each error as it arrives and keeps the newest RECENT_ERRORS_LEN of them for
+Framework::recent_errors.
+*/
+
+use std::collections::VecDeque;
+use std::sync::{
+    Arc,
+    Mutex,
+    mpsc::channel,
+    mpsc::Sender
+};
+use std::thread;
+
+use Logger;
+
+/// errors Framework::recent_errors keeps
+pub const RECENT_ERRORS_LEN: usize = 100;
+
+/// the Framework's end of the errors channel, oldest error first
+pub(crate) struct ErrorLog {
+    errors: Arc<Mutex<VecDeque<(String, String)>>>,
+    sender: Sender<(String, String)>
+}
+
+impl ErrorLog {
+    /// starts the thread logging errors through logger as they arrive, which ends once the
+    /// Framework and every module holding a sender are gone
+    pub(crate) fn new(logger: Logger) -> ErrorLog {
+        let errors = Arc::new(Mutex::new(VecDeque::new()));
+        let (sender, received) = channel::<(String, String)>();
+        let kept = errors.clone();
+
+        thread::spawn(move || {
+            for (module, error) in received {
+                logger.log(&format!("[!] {}: {}", module, error));
+
+                let mut kept = kept.lock().unwrap();
+
+                if kept.len() >= RECENT_ERRORS_LEN {
+                    kept.pop_front();
+                }
+
+                kept.push_back((module, error));
+            }
+        });
+
+        ErrorLog {
+            errors,
+            sender
+        }
+    }
+
+    pub(crate) fn sender(&self) -> Sender<(String, String)> {
+        self.sender.clone()
+    }
+
+    pub(crate) fn recent(&self) -> Vec<(String, String)> {
+        self.errors.lock().unwrap().iter().cloned().collect()
+    }
+}
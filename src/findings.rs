@@ -0,0 +1,115 @@
+/*
+Structured findings. Rather than leaving what a module turned up in log lines, a module reports it
+through the Reporter its hook was handed in HookEnv, one key and value at a time, and the finding
+lands in the Framework's buffer tagged with the hook and interface it came from and when, where a
+frontend can list it with Framework::findings. The buffer keeps the newest
+FrameworkConfig::findings_len findings, so a module reporting in a loop can't grow it without bound.
+
+A long running module, like a scan, can also publish how far along it is with Reporter::progress.
+Only the latest count is kept, and Framework::scan_progress reads it back for the module the hook
+returned.
+*/
+
+use std::collections::VecDeque;
+use std::sync::{
+    Arc,
+    Mutex
+};
+use std::time::SystemTime;
+
+/// findings kept unless FrameworkConfig::findings_len says otherwise
+pub const DEFAULT_FINDINGS_LEN: usize = 1000;
+
+/// one thing a module found, like `cred` => `example.com admin:hunter2`
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Finding {
+    pub hook: String, // the hook that started the reporting module
+    pub interface: String,
+    pub key: String,
+    pub value: String,
+    pub at: SystemTime
+}
+
+/// the Framework's bounded buffer of findings, oldest first
+pub(crate) struct Findings {
+    findings: Arc<Mutex<VecDeque<Finding>>>,
+    capacity: usize
+}
+
+impl Findings {
+    /// capacity 0 keeps none
+    pub(crate) fn new(capacity: usize) -> Findings {
+        Findings {
+            findings: Arc::new(Mutex::new(VecDeque::new())),
+            capacity
+        }
+    }
+
+    /// a handle reporting into this buffer on behalf of hook
+    pub(crate) fn reporter(&self, hook: &str, interface: &str) -> Reporter {
+        Reporter {
+            findings: self.findings.clone(),
+            capacity: self.capacity,
+            hook: String::from(hook),
+            interface: String::from(interface),
+            progress: Arc::new(Mutex::new(None))
+        }
+    }
+
+    pub(crate) fn list(&self) -> Vec<Finding> {
+        self.findings.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub(crate) fn clear(&self) {
+        self.findings.lock().unwrap().clear();
+    }
+}
+
+/// what a module reports its findings through, see the header above
+#[derive(Clone)]
+pub struct Reporter {
+    findings: Arc<Mutex<VecDeque<Finding>>>,
+    capacity: usize,
+    hook: String,
+    interface: String,
+    progress: Arc<Mutex<Option<(usize, usize)>>> // done and total, shared by every clone
+}
+
+impl Reporter {
+    /// a reporter whose findings go nowhere but itself, for a HookEnv built outside a Framework
+    pub fn detached(hook: &str, interface: &str) -> Reporter {
+        Findings::new(DEFAULT_FINDINGS_LEN).reporter(hook, interface)
+    }
+
+    /// adds a finding, dropping the oldest one kept if the buffer is full
+    pub fn report<K: Into<String>, V: Into<String>>(&self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut findings = self.findings.lock().unwrap();
+
+        if findings.len() >= self.capacity {
+            findings.pop_front();
+        }
+
+        findings.push_back(Finding {
+            hook: self.hook.clone(),
+            interface: self.interface.clone(),
+            key: key.into(),
+            value: value.into(),
+            at: SystemTime::now()
+        });
+    }
+
+    /// how far along the module is, done of total, replacing whatever it published last
+    pub fn progress(&self, done: usize, total: usize) {
+        *self.progress.lock().unwrap() = Some((done, total));
+    }
+
+    /// the last progress published through this reporter or any clone of it
+    pub(crate) fn last_progress(&self) -> Option<(usize, usize)> {
+        *self.progress.lock().unwrap()
+    }
+}
@@ -0,0 +1,164 @@
+/*
+Host tables on disk, as written by Framework::save_hosts and read back by Framework::load_hosts.
+Every file starts with a header naming the format version it was written in,
+
+    rustneedle hosts 1
+    eth0    10.0.0.1    aa:bb:cc:dd:ee:ff
+    eth0    10.0.0.7    -
+
+followed by one host a line: the interface its table belongs to, its address and its mac, `-` if it
+was never learned, separated by tabs since the default interface's name is empty. A file from a
+newer version than this one reads is refused outright rather than half understood; once the format
+changes, older versions are migrated in HostsFile::check_version. HostsFile derives serde's traits
+with the serde feature, so a frontend keeping hosts in some other format gets the same envelope and
+should call check_version on whatever it deserializes.
+*/
+
+use std::net::Ipv4Addr;
+
+use HostSnapshot;
+
+/// the version save_hosts writes, and the newest load_hosts reads
+pub const HOSTS_FORMAT_VERSION: u32 = 1;
+
+const MAGIC: &str = "rustneedle hosts";
+
+/// a host table tagged with the format version it was written in
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HostsFile {
+    pub version: u32,
+    pub hosts: Vec<HostSnapshot>
+}
+
+impl HostsFile {
+    /// hosts in the current format
+    pub fn new(hosts: Vec<HostSnapshot>) -> HostsFile {
+        HostsFile {
+            version: HOSTS_FORMAT_VERSION,
+            hosts
+        }
+    }
+
+    /// the file brought up to the current format, or an error if it can't be
+    pub fn check_version(self) -> Result<HostsFile, String> {
+        match self.version {
+            HOSTS_FORMAT_VERSION => Ok(self),
+
+            0 => Err(String::from("host table has no format version, it wasn't written by save_hosts")),
+
+            version => Err(format!(
+                "host table is in format version {}, this build reads up to version {}, upgrade to load it",
+                version,
+                HOSTS_FORMAT_VERSION
+            ))
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{} {}\n", MAGIC, self.version);
+
+        for host in &self.hosts {
+            let mac = host.mac.as_ref().map_or("-", |mac| mac.as_str());
+            text.push_str(&format!("{}\t{}\t{}\n", host.interface, host.ip, mac));
+        }
+
+        text
+    }
+
+    /// reads a file written by to_text, checking its version before anything past the header
+    pub fn parse(text: &str) -> Result<HostsFile, String> {
+        let mut lines = text.lines();
+
+        let version = match lines.next().and_then(|header| header.strip_prefix(MAGIC)) {
+            Some(version) => match version.trim().parse::<u32>() {
+                Ok(version) => version,
+                Err(_) => return Err(format!("{}: not a format version", version.trim()))
+            },
+
+            None => return Err(String::from("not a rustneedle host table"))
+        };
+
+        // a newer file's lines may not mean what this build would read them as
+        HostsFile { version, hosts: Vec::new() }.check_version()?;
+
+        let mut hosts = Vec::new();
+
+        for (number, line) in lines.enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            let fields: Vec<&str> = line.split('\t').collect();
+
+            let host = match fields[..] {
+                [interface, ip, mac] => match ip.parse::<Ipv4Addr>() {
+                    Ok(ip) => HostSnapshot {
+                        interface: String::from(interface),
+                        ip,
+                        mac: if mac == "-" { None } else { Some(String::from(mac)) }
+                    },
+
+                    Err(_) => return Err(format!("line {}: {}: not an IPv4 address", number + 2, ip))
+                },
+
+                _ => return Err(format!("line {}: expected an interface, an address and a mac", number + 2))
+            };
+
+            hosts.push(host);
+        }
+
+        Ok(HostsFile {
+            version,
+            hosts
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::{
+        HOSTS_FORMAT_VERSION,
+        HostsFile
+    };
+    use HostSnapshot;
+
+    fn hosts() -> HostsFile {
+        HostsFile::new(vec![
+            HostSnapshot {
+                interface: String::new(),
+                ip: Ipv4Addr::new(10, 0, 0, 1),
+                mac: Some(String::from("aa:bb:cc:dd:ee:ff"))
+            },
+
+            HostSnapshot {
+                interface: String::from("eth1"),
+                ip: Ipv4Addr::new(10, 0, 1, 7),
+                mac: None
+            }
+        ])
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let text = hosts().to_text();
+
+        assert!(text.starts_with(&format!("rustneedle hosts {}\n", HOSTS_FORMAT_VERSION)));
+        assert_eq!(HostsFile::parse(&text), Ok(hosts()));
+    }
+
+    #[test]
+    fn newer_versions_are_refused() {
+        let mut newer = hosts();
+        newer.version = HOSTS_FORMAT_VERSION + 1;
+
+        let err = HostsFile::parse(&newer.to_text()).unwrap_err();
+        assert!(err.contains(&format!("format version {}", HOSTS_FORMAT_VERSION + 1)), "{}", err);
+        assert!(newer.check_version().is_err());
+    }
+
+    #[test]
+    fn files_without_a_header_are_refused() {
+        assert!(HostsFile::parse("eth0\t10.0.0.1\t-\n").is_err());
+        assert!(HostsFile::parse("rustneedle hosts 0\n").is_err());
+        assert!(HostsFile::parse("rustneedle hosts one\n").is_err());
+    }
+}
@@ -4,14 +4,45 @@ Built as a library so that it can be used as a dependency for
 plugins.
 */
 
-use std::collections::HashMap;
-use std::net::Ipv4Addr;
-use std::time::Duration;
-use std::thread::JoinHandle;
+use std::collections::{
+    BTreeSet,
+    HashMap,
+    HashSet,
+    VecDeque
+};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::net::{
+    Ipv4Addr,
+    Ipv6Addr,
+    SocketAddr
+};
+#[cfg(feature = "ctrlc")]
+use std::process;
+use std::path::{
+    Path,
+    PathBuf
+};
+use std::time::{
+    Duration,
+    Instant
+};
+use std::thread::{
+    self,
+    JoinHandle
+};
 use std::sync::{
     Arc,
     Mutex,
     MutexGuard,
+    RwLock,
+    RwLockReadGuard,
+    RwLockWriteGuard,
+    atomic::AtomicBool,
+    atomic::Ordering,
+    mpsc::channel,
     mpsc::Receiver,
     mpsc::Sender,
     mpsc::SendError
@@ -20,13 +51,137 @@ use std::sync::{
 extern crate libloading;
 use libloading::Library;
 
+#[cfg(feature = "serde")]
+extern crate serde;
+
+#[cfg(feature = "tracing")]
+extern crate tracing;
+
+#[cfg(feature = "ctrlc")]
+extern crate ctrlc;
+
 extern crate pnet;
 use pnet::datalink::{
+    self,
+    Channel,
     Config,
     MacAddr,
     ChannelType::Layer2
 };
 
+pub mod packet;
+pub mod subnet;
+
+use subnet::Cidr;
+
+mod builder;
+pub use builder::ModuleBuilder;
+
+mod builtins;
+
+mod channel;
+pub use channel::{
+    ChannelPolicy,
+    FrameReceiver
+};
+
+mod control;
+pub use control::{
+    ControlRequest,
+    CONTROL_PORT,
+    default_control_addr
+};
+
+mod dispatch;
+pub use dispatch::{
+    CaptureStats,
+    Dispatcher,
+    DstClass,
+    MacField,
+    PackFilter,
+    PacketSink,
+    PacketSource,
+    QueuedFrame,
+    SendPriority,
+    SendStats,
+    TrafficTotals,
+    ETHER_HEADER_LEN,
+    hexdump
+};
+use dispatch::{
+    SendOptions,
+    SendQueues,
+    Sinks,
+    Subscription
+};
+
+mod logger;
+pub use logger::{
+    LogSink,
+    Logger
+};
+
+mod os;
+pub use os::{
+    detect_default_gateway,
+    os_arp_table
+};
+
+mod plugin;
+pub use plugin::{
+    LibraryReload,
+    PluginInfo,
+    PluginMeta,
+    RUSTNEEDLE_ABI
+};
+use plugin::{
+    AbiVersion,
+    InfoLoader
+};
+
+mod pool;
+pub use pool::ModulePool;
+use pool::TaskSlot;
+
+mod findings;
+pub use findings::{
+    Finding,
+    Reporter,
+    DEFAULT_FINDINGS_LEN
+};
+use findings::Findings;
+
+mod errors;
+pub use errors::RECENT_ERRORS_LEN;
+use errors::ErrorLog;
+
+mod rng;
+pub use rng::Rng;
+
+mod hostfile;
+pub use hostfile::{
+    HostsFile,
+    HOSTS_FORMAT_VERSION
+};
+
+mod pcap;
+pub use pcap::{
+    PcapReader,
+    ReplaySpeed
+};
+
+mod oui;
+pub use oui::OuiTable;
+
+mod rates;
+pub use rates::{
+    HostRates,
+    RATE_WINDOW_SECS
+};
+
+mod trace;
+use trace::ModuleSpan;
+
 pub const BROADCAST: MacAddr = MacAddr(0xff, 0xff, 0xff, 0xff, 0xff, 0xff); 
 
 pub const DLINKCFG: Config = Config {
@@ -38,6 +193,30 @@ pub const DLINKCFG: Config = Config {
     bpf_fd_attempts: 1000,
 };
 
+/// workers started the first time a hook asks for the module pool
+pub const POOL_WORKERS: usize = 4;
+
+/// read timeout used by FrameworkConfig::nonblocking_reads
+pub const NONBLOCKING_READ_TIMEOUT: Duration = Duration::from_millis(10);
+
+/// most queued frames the send loop writes back to back before waiting on the queue again
+pub const DEFAULT_SEND_BATCH: usize = 64;
+
+/// how often Module::stop_within checks whether the module has exited
+const STOP_POLL: Duration = Duration::from_millis(10);
+
+/// commands a Framework remembers unless configured otherwise
+pub const DEFAULT_HISTORY_LEN: usize = 500;
+
+/// how often module packet rates are sampled unless configured otherwise, see Module::rate_pps
+pub const DEFAULT_RATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// the weight the latest sample gets in a module's rate unless configured otherwise
+pub const DEFAULT_RATE_ALPHA: f64 = 0.3;
+
+/// how long stop_module waits on a module's ack unless configured otherwise, see Module::with_ack
+pub const DEFAULT_ACK_TIMEOUT: Duration = Duration::from_secs(1);
+
 /// represents an Ip and Mac address pair that must be known
 pub struct KnownPair {
     pub proto: Ipv4Addr,
@@ -51,26 +230,76 @@ impl KnownPair {
             hardw: hw
         }
     }
+
+    /// whether this pair's address is inside cidr
+    pub fn is_local_to(&self, cidr: Cidr) -> bool {
+        cidr.contains(self.proto)
+    }
 }
 
 /// represents a list of pairs that may or may not be known
 pub struct NetPairList {
     hosts: Vec<Ipv4Addr>,
-    macs: HashMap<Ipv4Addr, Option<MacAddr>>
+    macs: HashMap<Ipv4Addr, Option<MacAddr>>,
+    seen: HashMap<Ipv4Addr, Instant> // when set_host last heard of each host
 }
 
 impl NetPairList {
     fn new() -> NetPairList {
         NetPairList {
             hosts: Vec::new(),
-            macs: HashMap::new()
+            macs: HashMap::new(),
+            seen: HashMap::new()
+        }
+    }
+
+    /// every usable host in cidr, none of their macs known yet
+    pub fn from_cidr(cidr: Cidr) -> NetPairList {
+        let hosts = subnet::hosts_in(cidr);
+        let mut list = NetPairList::new();
+        list.reserve(hosts.size_hint().0);
+
+        for host in hosts {
+            list.insert(host);
+        }
+
+        list
+    }
+
+    /// every address from start to end inclusive, none of their macs known yet, empty if end comes before start
+    pub fn from_range(start: Ipv4Addr, end: Ipv4Addr) -> NetPairList {
+        let hosts = subnet::range(start, end);
+        let mut list = NetPairList::new();
+        list.reserve(hosts.size_hint().0);
+
+        for host in hosts {
+            list.insert(host);
         }
+
+        list
     }
 
     pub fn len(&self) -> usize {
         self.hosts.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.hosts.is_empty()
+    }
+
+    /// makes room for additional more hosts without reallocating
+    pub fn reserve(&mut self, additional: usize) {
+        self.hosts.reserve(additional);
+        self.macs.reserve(additional);
+    }
+
+    /// gives back the memory left over once a large list has been pruned
+    pub fn shrink_to_fit(&mut self) {
+        self.hosts.shrink_to_fit();
+        self.macs.shrink_to_fit();
+        self.seen.shrink_to_fit();
+    }
+
     pub fn hosts(&self) -> &Vec<Ipv4Addr> {
         &self.hosts
     }
@@ -91,15 +320,56 @@ impl NetPairList {
 
     pub fn set_host(&mut self, host: Ipv4Addr, mac: MacAddr) {
         self.macs.insert(host, Some(mac));
+        self.seen.insert(host, Instant::now());
+    }
+
+    /// when set_host was last called for host, None if it never was
+    pub fn last_seen(&self, host: &Ipv4Addr) -> Option<Instant> {
+        self.seen.get(host).cloned()
+    }
+
+    /// hosts in the list whose mac isn't known yet, in the order they were added
+    pub fn unresolved(&self) -> Vec<Ipv4Addr> {
+        self.hosts.iter().cloned().filter(|host| !matches!(self.macs.get(host), Some(Some(_)))).collect()
+    }
+
+    /// how many hosts in the list have a known mac; set_host can learn one for a host without
+    /// listing it, and those aren't counted
+    pub fn resolved_len(&self) -> usize {
+        self.hosts.iter().filter(|host| matches!(self.macs.get(host), Some(Some(_)))).count()
     }
 
+    /// the poisons needed to sit between gateway and every resolved host, as (victim, impersonated):
+    /// the victim is sent a reply claiming impersonated's address is at our mac, so each host gets
+    /// one impersonating the gateway and the gateway one impersonating the host, host by host in
+    /// address order; unresolved hosts can't be addressed and are left out, as are the gateway and me
+    pub fn spoof_targets(&self, gateway: &KnownPair, me: &KnownPair) -> Vec<(KnownPair, KnownPair)> {
+        let mut hosts: Vec<(Ipv4Addr, MacAddr)> = self.macs.iter()
+            .filter_map(|(&host, &mac)| mac.map(|mac| (host, mac)))
+            .filter(|&(host, _)| host != gateway.proto && host != me.proto)
+            .collect();
+
+        hosts.sort_by_key(|&(host, _)| host);
+
+        let gateway = || KnownPair::new(gateway.proto, gateway.hardw);
+        let mut targets = Vec::with_capacity(hosts.len() * 2);
+
+        for (host, mac) in hosts {
+            targets.push((KnownPair::new(host, mac), gateway()));
+            targets.push((gateway(), KnownPair::new(host, mac)));
+        }
+
+        targets
+    }
 }
 
 /// contains safe shared references to hosts on the network
 pub struct HostMgr {
     gateway: Arc<Mutex<KnownPair>>,
     myself: Arc<Mutex<KnownPair>>,
-    nethosts: Arc<Mutex<NetPairList>>
+    nethosts: Arc<RwLock<NetPairList>>, // read far more than written, so lookups don't wait on each other
+    neighbors: Arc<Mutex<HashMap<Ipv6Addr, MacAddr>>>, // IPv6 hosts found through neighbor discovery
+    rates: Arc<Mutex<HostRates>> // apart from nethosts so counting a frame never waits on a scan
 }
 
 impl HostMgr {
@@ -107,7 +377,9 @@ impl HostMgr {
         HostMgr {
             gateway: Arc::new(Mutex::new(gate)),
             myself: Arc::new(Mutex::new(me)),
-            nethosts: Arc::new(Mutex::new(NetPairList::new()))
+            nethosts: Arc::new(RwLock::new(NetPairList::new())),
+            neighbors: Arc::new(Mutex::new(HashMap::new())),
+            rates: Arc::new(Mutex::new(HostRates::new()))
         }
     }
 
@@ -119,10 +391,18 @@ impl HostMgr {
         self.myself.clone()
     }
 
-    pub fn get_nethosts(&self) -> Arc<Mutex<NetPairList>> {
+    pub fn get_nethosts(&self) -> Arc<RwLock<NetPairList>> {
         self.nethosts.clone()
     }
 
+    pub fn get_neighbors(&self) -> Arc<Mutex<HashMap<Ipv6Addr, MacAddr>>> {
+        self.neighbors.clone()
+    }
+
+    pub fn get_rates(&self) -> Arc<Mutex<HostRates>> {
+        self.rates.clone()
+    }
+
     //pub fn acquire(&mut self) -> (MutexGuard<>)
 
     pub fn acquire_gateway(&mut self) -> MutexGuard<KnownPair> {
@@ -133,8 +413,32 @@ impl HostMgr {
         self.myself.lock().unwrap()
     }
 
-    pub fn acquire_nethosts(&mut self) -> MutexGuard<NetPairList> {
-        self.nethosts.lock().unwrap()
+    pub fn acquire_nethosts(&mut self) -> RwLockWriteGuard<'_, NetPairList> {
+        self.write_nethosts()
+    }
+
+    /// shared access to the host table, any number of readers can hold one at once
+    pub fn read_nethosts(&self) -> RwLockReadGuard<'_, NetPairList> {
+        self.nethosts.read().unwrap()
+    }
+
+    /// exclusive access to the host table, waits out every reader
+    pub fn write_nethosts(&self) -> RwLockWriteGuard<'_, NetPairList> {
+        self.nethosts.write().unwrap()
+    }
+
+    pub fn acquire_neighbors(&mut self) -> MutexGuard<'_, HashMap<Ipv6Addr, MacAddr>> {
+        self.neighbors.lock().unwrap()
+    }
+
+    pub fn acquire_rates(&mut self) -> MutexGuard<'_, HostRates> {
+        self.rates.lock().unwrap()
+    }
+
+    /// packets per second each host sent over the last RATE_WINDOW_SECS, busiest first
+    /// only counted while the talkers module is running
+    pub fn host_rates(&self) -> Vec<(Ipv4Addr, u64)> {
+        self.rates.lock().unwrap().packet_rates()
     }
 }
 
@@ -143,7 +447,69 @@ impl Clone for HostMgr {
         HostMgr {
             gateway: self.gateway.clone(),
             myself: self.myself.clone(),
-            nethosts: self.nethosts.clone()
+            nethosts: self.nethosts.clone(),
+            neighbors: self.neighbors.clone(),
+            rates: self.rates.clone()
+        }
+    }
+}
+
+/// every topic's subscribers, shared by all clones of a Blackboard
+type Topics = Arc<Mutex<HashMap<String, Vec<Sender<Vec<u8>>>>>>;
+
+/// topic based message board that lets modules share data without knowing about each other
+pub struct Blackboard {
+    topics: Topics
+}
+
+impl Blackboard {
+    pub fn new() -> Blackboard {
+        Blackboard {
+            topics: Arc::new(Mutex::new(HashMap::new()))
+        }
+    }
+
+    /// hands msg to every live subscriber of topic, returning how many received it
+    pub fn publish(&self, topic: &str, msg: Vec<u8>) -> usize {
+        let mut topics = self.topics.lock().unwrap();
+
+        match topics.get_mut(topic) {
+            Some(subscribers) => {
+                // subscribers whose receiver was dropped are forgotten here
+                subscribers.retain(|sub| sub.send(msg.clone()).is_ok());
+                subscribers.len()
+            },
+
+            None => 0
+        }
+    }
+
+    pub fn subscribe(&self, topic: &str) -> Receiver<Vec<u8>> {
+        let (sender, receiver) = channel();
+
+        self.topics.lock().unwrap()
+            .entry(String::from(topic))
+            .or_default()
+            .push(sender);
+
+        receiver
+    }
+
+    pub fn topics(&self) -> Vec<String> {
+        self.topics.lock().unwrap().keys().cloned().collect()
+    }
+}
+
+impl Default for Blackboard {
+    fn default() -> Blackboard {
+        Blackboard::new()
+    }
+}
+
+impl Clone for Blackboard {
+    fn clone(&self) -> Self {
+        Blackboard {
+            topics: self.topics.clone()
         }
     }
 }
@@ -153,7 +519,7 @@ Here's where it gets interesting. The meat of this program is the hook system. H
 either by dylibs loaded at runtime or from within the core, and represent something the user can
 run from the command line. When a hook is loaded, it comes with a name String that is mapped
 to it within the Framework. A hook is simply a function that accepts a reference to a collection
-of arg strings and environment information, then returns either success or failure. On success,
+of arg strings and environment information (a HookEnv), then returns either success or failure. On success,
 a hook has the option of returning a Module, a threaded addition which will run in the background.
 A module has the option of being continually handed shared references to packets or parts of
 packets based on a PackFilter. When packets come in over the network, references for the packet's
@@ -162,195 +528,2647 @@ read them and queue packet creation requests as needed.
 
 Modules have some additional features built in: each has an mpsc::Receiver<()> whose intent is to
 be able to kill the module and cause cleanup. Each is also initialized with the option of an
-mpsc::Sender(vec<u8>) that can be used to queue packet send requests.
+mpsc::Sender(vec<u8>) that can be used to queue packet send requests. A module asks for packets by
+attaching a PackFilter with Module::with_filter; once the Framework has picked the module's name, it
+subscribes the filter with the Dispatcher under that name and unsubscribes it when the module goes.
 
 Hooks are organized by what level information they need. Some may only need access to the HostMgr,
-while others may require framework level access.
+while others may require framework level access, and standalone hooks need nothing beyond their
+args and HookEnv.
+
+Each interface the Framework captures on has a HostMgr of its own: the configured interface's is
+hosts(), others are added with add_interface. A hook is always run on one interface, named in its
+HookEnv, and HostMgr hooks are handed that interface's HostMgr. Frames are tagged with the interface
+they were captured on, and a module is only handed frames from the interface it was started on.
+
+Modules that want to cooperate (a scanner feeding a spoofer, say) can do so through the Framework's
+Blackboard. A framework level hook clones the board into its module thread, which can then publish
+discoveries under a topic or subscribe to topics other modules publish to. Modules that don't care
+about each other simply never touch it.
 */
 
+/// stats a module reports when it finishes, e.g. {"hosts": "37"} from a scan
+pub type ModuleOutcome = HashMap<String, String>;
+
+/// what a Module::deferred module runs once the Framework starts it
+type ModuleTask = Box<dyn FnOnce(Receiver<()>) -> Result<ModuleOutcome, String> + Send>;
+
+/// what a module is actually running on
+enum Runner {
+    Thread(JoinHandle<Result<ModuleOutcome, String>>),
+    Pooled(Arc<TaskSlot>),
+    Deferred(ModuleTask, Receiver<()>) // not started yet, see Module::deferred
+}
+
+/// when a module last pinged its heartbeat
+struct Pulse {
+    pings: Receiver<()>,
+    last: Instant
+}
+
+/// the last few lines a module put out, shared between its thread and the Framework
+/// the thread pushes to its clone, a frontend reads them back through Module::recent_output
+pub struct ModuleOutput {
+    lines: Arc<Mutex<VecDeque<String>>>,
+    capacity: usize
+}
+
+impl ModuleOutput {
+    /// keeps at most capacity lines, 0 is taken as 1
+    pub fn new(capacity: usize) -> ModuleOutput {
+        let capacity = capacity.max(1);
+
+        ModuleOutput {
+            lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity
+        }
+    }
+
+    /// adds line, dropping the oldest once the buffer is full
+    pub fn push<S: Into<String>>(&self, line: S) {
+        let mut lines = self.lines.lock().unwrap();
+
+        if lines.len() == self.capacity {
+            lines.pop_front();
+        }
+
+        lines.push_back(line.into());
+    }
+
+    /// oldest first
+    pub fn lines(&self) -> Vec<String> {
+        self.lines.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl Clone for ModuleOutput {
+    fn clone(&self) -> Self {
+        ModuleOutput {
+            lines: self.lines.clone(),
+            capacity: self.capacity
+        }
+    }
+}
+
+/// a message steering a running scan, sent with Framework::steer_scan
+/// targets are written the way the scan's own args write them; ones it can't parse, or has already queued, are skipped
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScanControl {
+    Abort, // stop probing and report what has been found so far
+    Add(Vec<String>) // probe these too, after whatever is still queued
+}
+
 pub struct Module {
-    handle: JoinHandle<Result<(), String>>,
-    killer: Sender<()>
+    runner: Runner,
+    killer: Sender<()>,
+    pulse: Option<Pulse>,
+    name: String, // the key the Framework keeps it under, set by insert_module
+    source_hook: String, // set by the Framework once the hook that built the module returns it
+    interface: String, // likewise, the interface the hook was run on
+    args: Vec<String>, // and the args it was run with
+    filter: Option<PackFilter>, // handed to the dispatcher when the module is inserted
+    subscription: Option<Arc<Subscription>>,
+    span: ModuleSpan,
+    started: Instant,
+    on_cleanup: Option<Box<dyn FnOnce() + Send>>, // run once the module is joined, or given up on
+    output: Option<ModuleOutput>,
+    reporter: Option<Reporter>, // the one the hook's env had, set by the Framework like source_hook
+    controls: Option<Sender<ScanControl>>,
+    acks: Option<Receiver<()>> // sent () by the module's thread right before it returns, see with_ack
 }
 
 impl Module {
     pub fn new(
-        handle: JoinHandle<Result<(), String>>,
+        handle: JoinHandle<Result<ModuleOutcome, String>>,
         killer: Sender<()>,
     ) -> Module {
-        Module {
-            handle: handle,
-            killer: killer
+        Module::from_runner(Runner::Thread(handle), killer)
+    }
+
+    fn pooled(slot: Arc<TaskSlot>, killer: Sender<()>) -> Module {
+        Module::from_runner(Runner::Pooled(slot), killer)
+    }
+
+    /// a module whose task isn't started until the Framework takes it in, on the module pool if the
+    /// hook that built it was marked with Framework::pool_hook and on a thread of its own otherwise;
+    /// task is handed the receiving end of the module's killer
+    pub fn deferred<F>(task: F) -> Module
+        where F: FnOnce(Receiver<()>) -> Result<ModuleOutcome, String> + Send + 'static
+    {
+        let (killer, killed) = channel();
+        Module::from_runner(Runner::Deferred(Box::new(task), killed), killer)
+    }
+
+    /// starts a deferred module's task, on pool if there is one; a module already running is left as it is
+    fn start(mut self, pool: Option<&ModulePool>) -> Module {
+        if let Runner::Deferred(task, killed) = self.runner {
+            self.runner = match pool {
+                Some(pool) => Runner::Pooled(pool.start(task, killed)),
+                None => Runner::Thread(thread::spawn(move || task(killed)))
+            };
         }
+
+        self
     }
 
-    pub fn handle(&mut self) -> &mut JoinHandle<Result<(), String>> {
-        &mut self.handle
+    fn from_runner(runner: Runner, killer: Sender<()>) -> Module {
+        Module {
+            runner,
+            killer,
+            pulse: None,
+            name: String::new(),
+            source_hook: String::new(),
+            interface: String::new(),
+            args: Vec::new(),
+            filter: None,
+            subscription: None,
+            span: ModuleSpan::none(),
+            started: Instant::now(),
+            on_cleanup: None,
+            output: None,
+            reporter: None,
+            controls: None,
+            acks: None
+        }
     }
 
-    pub fn kill(&mut self) -> Result<(), SendError<()>> {
-        self.killer.send(())
+    /// asks for frames matching filter to be delivered to the module once it is running
+    pub fn with_filter(mut self, filter: PackFilter) -> Module {
+        self.filter = Some(filter);
+        self
     }
-}
 
-pub enum Hook {
-    Framework((fn(&[&str], &mut Framework) -> Result<Option<Module>, String>)),
-    HostMgr((fn(&[&str], &mut HostMgr) -> Result<Option<Module>, String>))
-}
+    /// replaces the filter of a running module, handing it back if the module was never subscribed
+    pub fn set_filter(&self, filter: PackFilter) -> Result<(), PackFilter> {
+        match self.subscription {
+            Some(ref subscription) => {
+                subscription.set_filter(filter);
+                Ok(())
+            },
 
-type HookLoader = unsafe fn() -> Vec<(&'static str, Hook)>;
+            None => Err(filter)
+        }
+    }
 
-pub struct Framework {
-    running: bool,
-    module_drop: Option<Sender<Sender<Arc<Vec<u8>>>>>, // drop module's packet receiver to packets be passed to module
-    packet_queue: Option<Sender<Vec<u8>>>, // send packets to this to have them be sent to net
-    libraries: Vec<Library>,
-    hosts: HostMgr,
-    names: Vec<&'static str>,
-    hooks: HashMap<String, Hook>,
-    modules: HashMap<String, Module>
-}
+    /// frames the dispatcher has handed this module so far
+    pub fn packets(&self) -> u64 {
+        match self.subscription {
+            Some(ref subscription) => subscription.delivered(),
+            None => 0
+        }
+    }
 
-impl Framework {
-    pub fn new(hostmgr: HostMgr) -> Framework {
-        Framework {
-            running: true,
-            module_drop: None,
-            packet_queue: None,
-            libraries: Vec::new(),
-            hosts: hostmgr,
-            names: Vec::new(),
-            hooks: HashMap::new(),
-            modules: HashMap::new()
+    /// frames handed to this module a second, as a moving average over the Framework's rate samples,
+    /// see FrameworkConfig::rate_interval; 0 for a module without a filter or not yet sampled
+    pub fn rate_pps(&self) -> f64 {
+        match self.subscription {
+            Some(ref subscription) => subscription.rate_pps(),
+            None => 0.0
         }
     }
 
-    // external field reference access
+    /// frames a bounded filter dropped because the module's channel was full
+    pub fn overflowed(&self) -> u64 {
+        match self.subscription {
+            Some(ref subscription) => subscription.overflowed(),
+            None => 0
+        }
+    }
 
-    pub fn hosts(&self) -> &HostMgr {
-        &self.hosts
+    /// whether the module has dropped the receiving end of its filter while still subscribed
+    pub fn is_disconnected(&self) -> bool {
+        match self.subscription {
+            Some(ref subscription) => subscription.is_disconnected(),
+            None => false
+        }
     }
 
-    pub fn names(&self) -> &Vec<&'static str> {
-        &self.names
+    pub fn is_paused(&self) -> bool {
+        match self.subscription {
+            Some(ref subscription) => subscription.is_paused(),
+            None => false
+        }
     }
 
-    pub fn hooks(&self) -> &HashMap<String, Hook> {
-        &self.hooks
+    /// frames that matched the module's filter while it was paused
+    pub fn paused_dropped(&self) -> u64 {
+        match self.subscription {
+            Some(ref subscription) => subscription.paused_dropped(),
+            None => 0
+        }
     }
 
-    pub fn modules(&self) -> &HashMap<String, Module> {
-        &self.modules
+    /// the name the Framework runs the module under, empty until it has been inserted
+    pub fn name(&self) -> &str {
+        &self.name
     }
 
-    pub fn is_running(&self) -> bool {
-        self.running
-    }   
+    /// name of the hook that started this module
+    pub fn source_hook(&self) -> &str {
+        &self.source_hook
+    }
 
-    pub fn stop(&mut self) {
-        self.running = false
+    /// interface the module was started on, it is only handed frames captured there
+    pub fn interface(&self) -> &str {
+        &self.interface
     }
 
-    // util
+    /// args the hook that started this module was run with
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
 
-    pub fn hook_up(&mut self, name: &'static str, hook: Hook) -> Result<(), ()> {
-        if !self.hooks.contains_key(name) {
-            self.names.push(name);
-            self.hooks.insert(String::from(name), hook);
-            Ok(())
-        } else {
-            Err(())
+    /// how long ago the hook built the module
+    pub fn uptime(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// what the module's filter matches, "none" if it has no filter
+    pub fn filter_description(&self) -> String {
+        match (&self.subscription, &self.filter) {
+            (Some(subscription), _) => subscription.describe_filter(),
+            (None, Some(filter)) => filter.describe(),
+            (None, None) => String::from("none")
         }
     }
 
-    pub fn load_hooks_from(&mut self, lib: Library) -> Result<(), Vec<String>> {
-        let mut errors = Vec::new();
-        self.libraries.push(lib);
+    /// teardown that has to happen however the module ends, like restoring the caches a spoofer poisoned
+    /// it runs once: after the module's thread has exited, when the module is joined or stopped, or
+    /// straight away when stop_within gives up waiting on it
+    pub fn with_cleanup<F: FnOnce() + Send + 'static>(mut self, cleanup: F) -> Module {
+        self.on_cleanup = Some(Box::new(cleanup));
+        self
+    }
 
-        let hooks = unsafe { match self.libraries.last().unwrap().get::<HookLoader>(b"load") {
-            Ok(load) => load(),
-            Err(e) => return Err(vec![e.to_string()])
-        }};
+    pub fn has_cleanup(&self) -> bool {
+        self.on_cleanup.is_some()
+    }
 
-        for (name, hook) in hooks.into_iter() {
-            if self.hook_up(name, hook).is_err() {
-                errors.push(format!("{} already bound", name));
-            }
-        };
+    /// lets the Framework read back what the module's thread pushes to its clone of output
+    pub fn with_output(mut self, output: ModuleOutput) -> Module {
+        self.output = Some(output);
+        self
+    }
 
-        if errors.len() > 0 {
-            Err(errors)
-        } else {
-            Ok(())
-        }
+    /// lets the Framework steer the module with ScanControl messages, see Framework::steer_scan
+    pub fn with_controls(mut self, controls: Sender<ScanControl>) -> Module {
+        self.controls = Some(controls);
+        self
     }
 
-    pub fn try_run_hook(&mut self, name: &str, args: &[&str]) -> Result<(), String> {
-        let mut name = name.to_owned();
+    /// opts the module into acknowledging its stop: its thread sends () on the matching Sender right
+    /// before it returns, so stop_acked can tell a module that saw the killer and wound down from one
+    /// that has to be waited out; ModuleBuilder modules do this on their own
+    pub fn with_ack(mut self, acks: Receiver<()>) -> Module {
+        self.acks = Some(acks);
+        self
+    }
 
-        if self.hooks.contains_key(&name) {
-            match match self.hooks[&name] {
-                Hook::Framework(func) => func(args, self),
-                Hook::HostMgr(func) => func(args, &mut self.hosts)
-            } {
-                Ok(modopt) => match modopt {
-                    Some(module) => {
-                        // if name in use, find an acceptable name for new instance by incrementing
-                        let mut counter = 0;
-
-                        while self.modules.contains_key(&name) {
-                            name = format!("{}_{}", name, counter);
-                            counter += 1;
-                        }
+    /// the module's most recent output lines, oldest first, empty if it keeps none
+    pub fn recent_output(&self) -> Vec<String> {
+        match self.output {
+            Some(ref output) => output.lines(),
+            None => Vec::new()
+        }
+    }
 
-                        println!("[*] Started '{}'", name);
-                        self.modules.insert(name.clone(), module);
-                        Ok(())
-                    },
+    /// opts the module into health tracking, its thread should ping the matching Sender every loop
+    pub fn with_heartbeat(mut self, pings: Receiver<()>) -> Module {
+        self.pulse = Some(Pulse {
+            pings,
+            last: Instant::now()
+        });
 
-                    None => Ok(())
-                },
+        self
+    }
 
-                /*
-                because this Err is a Result<Option<...>, String> and not a Result<(), String>, rustc won't let me just
-                use "e => e". The Ok(Option<...>) has already been matched, so any other result would HAVE to be an
-                Err(string) since that's only other type left in the enum. But because this Err(String) is "from" a
-                different kind of Result, apparently I have to get the innter String out of the Err and then re-wrap it.
-                I'm sure there's a way to do this that doesn't look fucking stupid, but I can't think of the it atm.
-                */
-                Err(s) => Err(s)
-            }
-        } else {
-            Err(format!("{}: No such hook", name))
+    /// false only if the module has a heartbeat and hasn't pinged it within timeout
+    pub fn is_healthy(&mut self, timeout: Duration) -> bool {
+        match self.since_heartbeat() {
+            Some(quiet) => quiet < timeout,
+            None => true
         }
     }
 
-    pub fn init_task_mpscs(&mut self, mod_drop: Sender<Sender<Arc<Vec<u8>>>>, sender: Sender<Vec<u8>>) {
-        self.module_drop = Some(mod_drop);
-        self.packet_queue = Some(sender);
+    /// time since the module last pinged its heartbeat, None if it has none
+    pub fn since_heartbeat(&mut self) -> Option<Duration> {
+        match self.pulse {
+            Some(ref mut pulse) => {
+                // pings are only drained when asked, so the last beat is accurate to the polling interval
+                if pulse.pings.try_iter().count() > 0 {
+                    pulse.last = Instant::now();
+                }
+
+                Some(pulse.last.elapsed())
+            },
+
+            None => None
+        }
     }
 
-    pub fn try_kill(&mut self, name: &str) -> Result<(), String> {
-        if let Some(mut module) = self.modules.remove(name) {
-            if module.kill().is_err() {
-                Err(format!("{}: thread has already died", name))
-            } else {
-                Ok(())
-            }
+    /// everything known about the module, for Framework::inspect_module
+    pub fn report(&mut self, name: &str) -> ModuleReport {
+        ModuleReport {
+            name: String::from(name),
+            source_hook: self.source_hook.clone(),
+            interface: self.interface.clone(),
+            args: self.args.clone(),
+            filter: self.filter_description(),
+            pooled: self.is_pooled(),
+            uptime: self.uptime(),
+            packets: self.packets(),
+            rate_pps: self.rate_pps(),
+            overflowed: self.overflowed(),
+            paused: self.is_paused(),
+            paused_dropped: self.paused_dropped(),
+            disconnected: self.is_disconnected(),
+            finished: self.is_finished(),
+            since_heartbeat: self.since_heartbeat()
+        }
+    }
 
-        } else {
-            Err(format!("{}: No such module", name))
+    /// the module's dedicated thread, None if it runs on a ModulePool or hasn't been started
+    pub fn handle(&mut self) -> Option<&mut JoinHandle<Result<ModuleOutcome, String>>> {
+        match self.runner {
+            Runner::Thread(ref mut handle) => Some(handle),
+            Runner::Pooled(_) | Runner::Deferred(..) => None
         }
     }
 
-    pub fn insert_packet_tx(&mut self, packet_tx: Sender<Arc<Vec<u8>>>) -> Result<(), ()> {
-        match &self.module_drop {
-            Some(drop) => Ok(drop.send(packet_tx).unwrap()),
-            None => Err(())
+    pub fn is_pooled(&self) -> bool {
+        match self.runner {
+            Runner::Thread(_) | Runner::Deferred(..) => false,
+            Runner::Pooled(_) => true
         }
     }
 
-    pub fn get_packet_queue(&self) -> Option<Sender<Vec<u8>>> {
-        self.packet_queue.clone()
+    /// fails only if the module has already exited and dropped its end of the killer
+    pub fn kill(&mut self) -> Result<(), SendError<()>> {
+        self.killer.send(())
+    }
+
+    /// kills the module and waits for it to exit, returning what it reported
+    /// a module that already exited on its own has nothing to kill, its stored result is returned all the same
+    pub fn stop(mut self) -> Result<ModuleOutcome, String> {
+        let _ = self.kill();
+        self.join()
     }
-}
\ No newline at end of file
+
+    /// stop, first waiting up to timeout for the module to acknowledge it, see with_ack
+    /// a module that doesn't is joined all the same, however long that takes
+    pub fn stop_acked(mut self, timeout: Duration) -> (StopAck, Result<ModuleOutcome, String>) {
+        let _ = self.kill();
+
+        // an ack sent before the kill, by a module that had already returned, is still waiting here
+        let ack = match self.acks.take() {
+            Some(acks) => match acks.recv_timeout(timeout) {
+                Ok(()) => StopAck::Acked,
+                Err(_) => StopAck::Unacked
+            },
+
+            None => StopAck::NoAck
+        };
+
+        (ack, self.join())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        match self.runner {
+            Runner::Thread(ref handle) => handle.is_finished(),
+            Runner::Pooled(ref slot) => slot.is_done(),
+            Runner::Deferred(..) => false
+        }
+    }
+
+    /// kills the module and waits up to timeout for it to exit
+    /// a module that doesn't is left running on its own, and its cleanup is run without it
+    pub fn stop_within(mut self, timeout: Duration) -> Result<ModuleOutcome, String> {
+        let _ = self.kill();
+        let deadline = Instant::now() + timeout;
+
+        while !self.is_finished() {
+            if Instant::now() >= deadline {
+                if let Some(cleanup) = self.on_cleanup.take() {
+                    cleanup();
+                }
+
+                return Err(format!("didn't exit within {:?}, left running", timeout));
+            }
+
+            thread::sleep(STOP_POLL);
+        }
+
+        self.join()
+    }
+
+    /// waits for the module to exit, runs its cleanup, and returns its result
+    pub fn join(mut self) -> Result<ModuleOutcome, String> {
+        let cleanup = self.on_cleanup.take();
+
+        let result = match self.runner {
+            Runner::Thread(handle) => match handle.join() {
+                Ok(result) => result,
+                Err(_) => Err(String::from("module thread panicked"))
+            },
+
+            Runner::Pooled(slot) => slot.wait(),
+
+            // never taken in by a Framework, so it runs here, as often as not already killed by stop
+            Runner::Deferred(task, killed) => task(killed)
+        };
+
+        if let Some(cleanup) = cleanup {
+            cleanup();
+        }
+
+        self.span.exited(&result);
+        result
+    }
+}
+
+/// how a module took being stopped, see Module::stop_acked
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopAck {
+    Acked, // acknowledged in time, it saw the killer or had already returned on its own
+    Unacked, // had an ack but didn't send it in time, or panicked, and had to be joined regardless
+    NoAck // never opted into acknowledging, joined as before
+}
+
+/// a running module's details at one point in time, shown as a multi-line report by Display
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModuleReport {
+    pub name: String,
+    pub source_hook: String,
+    pub interface: String,
+    pub args: Vec<String>,
+    pub filter: String, // PackFilter::describe of its current filter
+    pub pooled: bool,
+    pub uptime: Duration,
+    pub packets: u64,
+    pub rate_pps: f64,
+    pub overflowed: u64,
+    pub paused: bool,
+    pub paused_dropped: u64,
+    pub disconnected: bool,
+    pub finished: bool, // exited, but not yet reaped
+    pub since_heartbeat: Option<Duration> // None if the module has no heartbeat
+}
+
+impl ModuleReport {
+    /// finished, disconnected, paused or running, whichever comes first
+    pub fn state(&self) -> &'static str {
+        if self.finished {
+            "finished"
+        } else if self.disconnected {
+            "disconnected"
+        } else if self.paused {
+            "paused"
+        } else {
+            "running"
+        }
+    }
+}
+
+impl fmt::Display for ModuleReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "module:    {}", self.name)?;
+        writeln!(f, "hook:      {} on {}", self.source_hook, self.interface)?;
+        writeln!(f, "args:      {}", self.args.join(" "))?;
+        writeln!(f, "filter:    {}", self.filter)?;
+        writeln!(f, "runner:    {}", if self.pooled { "module pool" } else { "own thread" })?;
+        writeln!(f, "state:     {}", self.state())?;
+        writeln!(f, "uptime:    {:.1}s", self.uptime.as_secs_f64())?;
+        writeln!(f, "packets:   {} ({} overflowed, {} dropped while paused)", self.packets, self.overflowed, self.paused_dropped)?;
+        writeln!(f, "rate:      ~{} pps", format_rate(self.rate_pps))?;
+
+        match self.since_heartbeat {
+            Some(quiet) => writeln!(f, "heartbeat: {:.1}s ago", quiet.as_secs_f64()),
+            None => writeln!(f, "heartbeat: none")
+        }
+    }
+}
+
+/// 950 as is, 1234 as 1.2k and 5600000 as 5.6M
+pub fn format_rate(rate: f64) -> String {
+    if rate >= 1_000_000.0 {
+        format!("{:.1}M", rate / 1_000_000.0)
+    } else if rate >= 1000.0 {
+        format!("{:.1}k", rate / 1000.0)
+    } else {
+        format!("{:.0}", rate)
+    }
+}
+
+/// what the frames an interface captures start with
+/// anything but Ethernet has no ethertype for the dispatcher to read, so only filters taking whole
+/// frames match those, and a module reading them has to strip the link header itself, see packet::radiotap
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkType {
+    Ethernet,
+    Ieee80211, // bare 802.11 frames
+    Radiotap, // a radiotap header then an 802.11 frame, as a monitor mode interface captures
+    Other(u32) // the platform's own number for it, an ARPHRD_ type on linux
+}
+
+impl LinkType {
+    /// the libpcap DLT_ number for this link type, None for Other
+    pub fn dlt(&self) -> Option<u32> {
+        match *self {
+            LinkType::Ethernet => Some(1),
+            LinkType::Ieee80211 => Some(105),
+            LinkType::Radiotap => Some(127),
+            LinkType::Other(_) => None
+        }
+    }
+}
+
+impl fmt::Display for LinkType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LinkType::Ethernet => write!(f, "ethernet"),
+            LinkType::Ieee80211 => write!(f, "802.11"),
+            LinkType::Radiotap => write!(f, "radiotap+802.11"),
+            LinkType::Other(number) => write!(f, "link type {}", number)
+        }
+    }
+}
+
+/// settings the Framework was started with
+#[derive(Clone, Debug)]
+pub struct FrameworkConfig {
+    pub interface: String,
+    pub data_dir: PathBuf, // wordlists, logs, captures and other files plugins keep around
+    pub dry_run: bool, // log queued frames instead of sending them
+    pub history_len: usize, // commands remembered by Framework::history, 0 remembers none
+    pub read_timeout: Duration, // longest the capture loop waits on a quiet link before checking for shutdown
+    pub batch_size: usize, // queued frames sent back to back, 1 sends every frame as it is queued
+    pub dedup_window: Option<Duration>, // drop a queued frame identical to one sent this recently, None sends every one
+    pub send_fairness: usize, // see FrameworkConfig::send_fairness
+    pub promiscuous: bool, // have open_channel make sure frames addressed to other hosts are captured too
+    pub findings_len: usize, // findings kept by Framework::findings, 0 keeps none
+    pub link_type: LinkType, // what open_channel expects the interface to capture
+    pub rng_seed: Option<u64>, // seeds every Rng the Framework hands out, None seeds from entropy, see rng.rs
+    pub dispatch_workers: usize, // threads the capture loop dispatches frames on, see FrameworkConfig::dispatch_workers
+    pub snaplen: Option<usize>, // captured frames are cut to this many bytes before dispatch, None keeps them whole
+    pub rate_interval: Option<Duration>, // how often module packet rates are sampled, None never samples them
+    pub rate_alpha: f64, // the weight the latest sample gets in a module's rate, see FrameworkConfig::rate_alpha
+    pub ack_timeout: Duration // how long stopping a module waits on its ack before joining it anyway
+}
+
+impl FrameworkConfig {
+    pub fn new(interface: &str) -> FrameworkConfig {
+        FrameworkConfig {
+            interface: String::from(interface),
+            data_dir: default_data_dir(),
+            dry_run: false,
+            history_len: DEFAULT_HISTORY_LEN,
+            read_timeout: DLINKCFG.read_timeout.unwrap(),
+            batch_size: DEFAULT_SEND_BATCH,
+            dedup_window: None,
+            send_fairness: 0,
+            promiscuous: false,
+            findings_len: DEFAULT_FINDINGS_LEN,
+            link_type: LinkType::Ethernet,
+            rng_seed: None,
+            dispatch_workers: 1,
+            snaplen: None,
+            rate_interval: Some(DEFAULT_RATE_INTERVAL),
+            rate_alpha: DEFAULT_RATE_ALPHA,
+            ack_timeout: DEFAULT_ACK_TIMEOUT
+        }
+    }
+
+    pub fn read_timeout(mut self, read_timeout: Duration) -> FrameworkConfig {
+        self.read_timeout = read_timeout;
+        self
+    }
+
+    /// polls the interface instead of waiting on it, so shutdown and capture changes take effect at once
+    pub fn nonblocking_reads(self) -> FrameworkConfig {
+        self.read_timeout(NONBLOCKING_READ_TIMEOUT)
+    }
+
+    /// DLINKCFG with these settings applied
+    pub fn datalink_config(&self) -> Config {
+        let mut config = DLINKCFG;
+        config.read_timeout = Some(self.read_timeout);
+        config
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> FrameworkConfig {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn history_len(mut self, history_len: usize) -> FrameworkConfig {
+        self.history_len = history_len;
+        self
+    }
+
+    pub fn findings_len(mut self, findings_len: usize) -> FrameworkConfig {
+        self.findings_len = findings_len;
+        self
+    }
+
+    /// 0 is taken as 1
+    pub fn batch_size(mut self, batch_size: usize) -> FrameworkConfig {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// a zero window is taken as off
+    pub fn dedup_window(mut self, window: Duration) -> FrameworkConfig {
+        self.dedup_window = if window == Duration::from_secs(0) { None } else { Some(window) };
+        self
+    }
+
+    /// how many frames of higher priority may go out ahead of a waiting lower priority frame before
+    /// one of the lower ones is sent anyway, 0 (the default) always sends the highest priority first
+    /// and lets a busy High queue starve Low entirely
+    pub fn send_fairness(mut self, frames: usize) -> FrameworkConfig {
+        self.send_fairness = frames;
+        self
+    }
+
+    /// needed to see other hosts' traffic on a hub or mirror port, open_channel fails if it can't be had
+    /// pnet asks for it anyway on linux and windows, where false leaves it on; elsewhere false leaves it off
+    pub fn promiscuous(mut self, promiscuous: bool) -> FrameworkConfig {
+        self.promiscuous = promiscuous;
+        self
+    }
+
+    /// Radiotap to sniff 802.11 on a monitor mode interface, which has to be put into monitor mode
+    /// beforehand (`iw dev wlan0 set type monitor` on linux); open_channel fails if the interface
+    /// captures anything else, and only linux is supported for anything but Ethernet
+    pub fn link_type(mut self, link_type: LinkType) -> FrameworkConfig {
+        self.link_type = link_type;
+        self
+    }
+
+    /// 1 (the default) dispatches every frame on the capture thread, in capture order; more hands
+    /// frames out to that many threads, keeping the frames of a conversation in order but not the
+    /// order between conversations, see dispatch.rs, 0 is taken as 1
+    pub fn dispatch_workers(mut self, workers: usize) -> FrameworkConfig {
+        self.dispatch_workers = workers;
+        self
+    }
+
+    /// cuts every captured frame to its first snaplen bytes before it is dispatched, as libpcap's
+    /// snaplen does, so modules that only read headers aren't handed whole jumbo frames; a module
+    /// reading payloads has to expect them cut short, the packet parsers return None on a truncated
+    /// header but a payload slice is simply whatever survived, and tcp::Reassembler sees gaps
+    pub fn snaplen(mut self, snaplen: usize) -> FrameworkConfig {
+        self.snaplen = Some(snaplen);
+        self
+    }
+
+    /// how often Module::rate_pps is brought up to date, every second unless set; shorter follows
+    /// bursts more closely at the cost of a noisier rate, a zero interval is taken as off and leaves
+    /// every rate at 0
+    pub fn rate_interval(mut self, interval: Duration) -> FrameworkConfig {
+        self.rate_interval = if interval == Duration::from_secs(0) { None } else { Some(interval) };
+        self
+    }
+
+    /// how much of a module's rate comes from the latest interval, the rest carried over from the
+    /// rate before it: 1 is the latest interval alone, smaller values smooth more and follow changes
+    /// more slowly, 0.3 unless set; taken as somewhere between 0 and 1
+    pub fn rate_alpha(mut self, alpha: f64) -> FrameworkConfig {
+        self.rate_alpha = alpha.clamp(0.0, 1.0);
+        self
+    }
+
+    /// how long stop_module and clear_modules give a module that acknowledges its stop to do so
+    /// before logging that it didn't and joining it regardless, see Module::with_ack
+    pub fn ack_timeout(mut self, timeout: Duration) -> FrameworkConfig {
+        self.ack_timeout = timeout;
+        self
+    }
+
+    /// makes shuffled scan orders, jitter and anything else random repeat from run to run
+    pub fn rng_seed(mut self, seed: u64) -> FrameworkConfig {
+        self.rng_seed = Some(seed);
+        self
+    }
+
+    pub fn data_dir<P: Into<PathBuf>>(mut self, dir: P) -> FrameworkConfig {
+        self.data_dir = dir.into();
+        self
+    }
+}
+
+/// per-user data location, following XDG on unix and APPDATA on windows
+fn default_data_dir() -> PathBuf {
+    if let Some(dir) = env::var_os("XDG_DATA_HOME") {
+        PathBuf::from(dir).join("rustneedle")
+    } else if let Some(home) = env::var_os("HOME") {
+        PathBuf::from(home).join(".local").join("share").join("rustneedle")
+    } else if let Some(appdata) = env::var_os("APPDATA") {
+        PathBuf::from(appdata).join("rustneedle")
+    } else {
+        PathBuf::from("rustneedle-data")
+    }
+}
+
+impl Default for FrameworkConfig {
+    fn default() -> Self {
+        FrameworkConfig::new("")
+    }
+}
+
+/// one command run through the Framework
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub hook: String, // as typed, so an alias is recorded as the alias
+    pub args: Vec<String>,
+    pub interface: String,
+    pub succeeded: bool
+}
+
+/// the state a Framework could be brought back to after a restart, see Framework::snapshot
+/// module threads can't be saved, so each module is kept as the hook run that would start it again
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameworkSnapshot {
+    pub hosts: Vec<HostSnapshot>, // every interface's host table, in the order hosts were found
+    pub modules: Vec<ModuleSnapshot> // sorted by name
+}
+
+/// one entry of a host table
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HostSnapshot {
+    pub interface: String,
+    pub ip: Ipv4Addr,
+    pub mac: Option<String> // aa:bb:cc:dd:ee:ff, None if it was never learned
+}
+
+/// a running module and what started it
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModuleSnapshot {
+    pub name: String,
+    pub source_hook: String,
+    pub interface: String,
+    pub args: Vec<String>
+}
+
+/// environment information handed to every hook alongside its args
+pub struct HookEnv {
+    pub interface: String, // the interface the hook was run on, see Framework::hosts_for
+    pub config: FrameworkConfig,
+    pub reporter: Reporter, // into Framework::findings, for an env the Framework built
+    pub errors: Sender<(String, String)>, // (module, error) into Framework::recent_errors, see errors.rs
+    pub rng: Rng // what the hook and its module draw any randomness from
+}
+
+impl HookEnv {
+    pub fn new(config: &FrameworkConfig) -> HookEnv {
+        HookEnv::on(config, &config.interface)
+    }
+
+    /// findings reported through an env built here stay with its reporter, Framework::findings never sees them,
+    /// and errors sent down its errors channel go nowhere; its rng is seeded straight from config.rng_seed
+    pub fn on(config: &FrameworkConfig, interface: &str) -> HookEnv {
+        HookEnv {
+            interface: String::from(interface),
+            config: config.clone(),
+            reporter: Reporter::detached("", interface),
+            errors: channel().0,
+            rng: Rng::from_seed(config.rng_seed)
+        }
+    }
+
+    /// path to name inside the data directory, which is created if it doesn't exist yet
+    pub fn data_path(&self, name: &str) -> PathBuf {
+        // a failure here resurfaces as soon as the caller tries to use the path
+        let _ = fs::create_dir_all(&self.config.data_dir);
+        self.config.data_dir.join(name)
+    }
+}
+
+/// what every hook returns: the module it started, if any
+pub type HookResult = Result<Option<Module>, String>;
+
+pub enum Hook {
+    Framework(fn(&[&str], &HookEnv, &mut Framework) -> HookResult),
+    HostMgr(fn(&[&str], &HookEnv, &mut HostMgr) -> HookResult),
+    Standalone(fn(&[&str], &HookEnv) -> HookResult) // touches nothing of the Framework's
+}
+
+/// how much of the Framework a hook is handed, widest first
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HookLevel {
+    Framework,
+    HostMgr,
+    Standalone
+}
+
+impl Hook {
+    pub fn level(&self) -> HookLevel {
+        match *self {
+            Hook::Framework(_) => HookLevel::Framework,
+            Hook::HostMgr(_) => HookLevel::HostMgr,
+            Hook::Standalone(_) => HookLevel::Standalone
+        }
+    }
+}
+
+type HookLoader = unsafe fn() -> Vec<(&'static str, Hook)>;
+
+/// called with a module's name, see Framework::on_module_started
+pub type ModuleCallback = Box<dyn Fn(&str) + Send>;
+
+/// called with a module's name and the error it failed with, see Framework::on_module_failed
+pub type FailureCallback = Box<dyn Fn(&str, &str) + Send>;
+
+/// the module lifecycle callbacks, shared with the threads that join killed modules
+#[derive(Default)]
+struct Lifecycle {
+    started: Option<ModuleCallback>,
+    stopped: Option<ModuleCallback>,
+    failed: Option<FailureCallback>
+}
+
+impl Lifecycle {
+    /// hands the way the named module ended to whichever callback it calls for
+    fn ended<T>(&self, name: &str, result: &Result<T, String>) {
+        match *result {
+            Ok(_) => if let Some(ref stopped) = self.stopped {
+                stopped(name);
+            },
+
+            Err(ref err) => if let Some(ref failed) = self.failed {
+                failed(name, err);
+            }
+        }
+    }
+
+    /// joins a module that has been told to stop on a thread of its own, so its cleanup runs and
+    /// the callbacks hear how it ended once it actually has
+    fn join_killed(lifecycle: Arc<Mutex<Lifecycle>>, name: String, module: Module) {
+        thread::spawn(move || {
+            let result = module.join();
+            lifecycle.lock().unwrap().ended(&name, &result);
+        });
+    }
+}
+
+pub struct Framework {
+    config: FrameworkConfig,
+    running: bool,
+    dispatcher: Arc<Dispatcher>, // hands incoming frames to module filters
+    capturing: Arc<AtomicBool>, // cleared to stop the capture loop
+    interrupted: Arc<AtomicBool>, // set by the signal handler, see install_signal_handler
+    packet_queues: Option<SendQueues>, // send packets to these to have them be sent to net
+    sinks: Sinks, // where frames queued for every other interface go, see start_sender_on
+    send_stats: Arc<SendStats>,
+    logger: Logger,
+    libraries: Vec<Option<Library>>, // None once unloaded, so indices stay put
+    hook_sources: HashMap<String, usize>, // hook name -> index in libraries, plugin hooks only
+    plugins: Vec<PluginInfo>,
+    hosts: HostMgr, // for config.interface
+    interfaces: HashMap<String, HostMgr>, // for every other interface added
+    blackboard: Blackboard,
+    names: Vec<&'static str>,
+    hooks: HashMap<String, Hook>,
+    modules: HashMap<String, Module>,
+    max_modules: usize, // 0 means no limit
+    pool: Option<ModulePool>,
+    pooled_hooks: HashSet<String>, // hooks whose deferred modules are started on the pool, see pool_hook
+    aliases: HashMap<String, String>,
+    hook_caps: HashMap<String, String>, // hook name -> capability needed to run it, untagged hooks need none
+    hook_tags: HashMap<String, BTreeSet<String>>, // hook name -> categories it is listed under, lowercased
+    allowed_caps: Option<HashSet<String>>, // None allows every hook, see set_allowed_caps
+    control: Option<Receiver<ControlRequest>>,
+    history: Vec<HistoryEntry>, // oldest first, never longer than config.history_len
+    findings: Findings, // what modules reported through their HookEnv's Reporter
+    errors: ErrorLog, // what modules sent down their HookEnv's errors channel
+    lifecycle: Arc<Mutex<Lifecycle>>, // see on_module_started
+    sampling: bool, // whether the rate thread has been started, see insert_module
+    rng: Rng // every other Rng is split off this one, see rng.rs
+}
+
+impl Framework {
+    pub fn new(hostmgr: HostMgr) -> Framework {
+        Framework::with_config(hostmgr, FrameworkConfig::default())
+    }
+
+    pub fn with_config(hostmgr: HostMgr, config: FrameworkConfig) -> Framework {
+        let findings = Findings::new(config.findings_len);
+        let logger = Logger::new();
+        let errors = ErrorLog::new(logger.clone());
+        let rng = Rng::from_seed(config.rng_seed);
+
+        Framework {
+            config,
+            running: true,
+            dispatcher: Arc::new(Dispatcher::new()),
+            capturing: Arc::new(AtomicBool::new(false)),
+            interrupted: Arc::new(AtomicBool::new(false)),
+            packet_queues: None,
+            sinks: Arc::new(Mutex::new(HashMap::new())),
+            send_stats: Arc::new(SendStats::new()),
+            logger,
+            libraries: Vec::new(),
+            hook_sources: HashMap::new(),
+            plugins: Vec::new(),
+            hosts: hostmgr,
+            interfaces: HashMap::new(),
+            blackboard: Blackboard::new(),
+            names: Vec::new(),
+            hooks: HashMap::new(),
+            modules: HashMap::new(),
+            max_modules: 0,
+            pool: None,
+            pooled_hooks: HashSet::new(),
+            aliases: HashMap::new(),
+            hook_caps: HashMap::new(),
+            hook_tags: HashMap::new(),
+            allowed_caps: None,
+            control: None,
+            history: Vec::new(),
+            findings,
+            errors,
+            lifecycle: Arc::new(Mutex::new(Lifecycle::default())),
+            sampling: false,
+            rng
+        }
+    }
+
+    // external field reference access
+
+    pub fn config(&self) -> &FrameworkConfig {
+        &self.config
+    }
+
+    pub fn hosts(&self) -> &HostMgr {
+        &self.hosts
+    }
+
+    /// the HostMgr of the named interface, hosts() for the configured one
+    pub fn hosts_for(&self, interface: &str) -> Option<&HostMgr> {
+        if interface == self.config.interface {
+            Some(&self.hosts)
+        } else {
+            self.interfaces.get(interface)
+        }
+    }
+
+    fn hosts_for_mut(&mut self, interface: &str) -> Option<&mut HostMgr> {
+        if interface == self.config.interface {
+            Some(&mut self.hosts)
+        } else {
+            self.interfaces.get_mut(interface)
+        }
+    }
+
+    /// gives another interface a gateway, self and host table of its own
+    pub fn add_interface(&mut self, interface: &str, hostmgr: HostMgr) -> Result<(), String> {
+        if self.hosts_for(interface).is_some() {
+            Err(format!("{}: interface already added", interface))
+        } else {
+            self.interfaces.insert(String::from(interface), hostmgr);
+            Ok(())
+        }
+    }
+
+    /// the configured interface followed by every added one, sorted
+    pub fn interfaces(&self) -> Vec<String> {
+        let mut added: Vec<String> = self.interfaces.keys().cloned().collect();
+        added.sort();
+        added.insert(0, self.config.interface.clone());
+        added
+    }
+
+    pub fn blackboard(&self) -> &Blackboard {
+        &self.blackboard
+    }
+
+    /// shared workers for lightweight modules, started on first use
+    pub fn module_pool(&mut self) -> &ModulePool {
+        self.pool.get_or_insert_with(|| ModulePool::new(POOL_WORKERS))
+    }
+
+    /// starts the Module::deferred modules the named hook returns on the module pool rather than a
+    /// thread each, whichever kind of hook it is; see pool.rs for which modules suit the pool
+    pub fn pool_hook(&mut self, hook: &str) -> Result<(), String> {
+        if !self.hooks.contains_key(hook) {
+            return Err(format!("{}: No such hook", hook));
+        }
+
+        self.pooled_hooks.insert(String::from(hook));
+        Ok(())
+    }
+
+    pub fn is_pooled_hook(&self, hook: &str) -> bool {
+        self.pooled_hooks.contains(hook)
+    }
+
+    pub fn names(&self) -> &Vec<&'static str> {
+        &self.names
+    }
+
+    pub fn hooks(&self) -> &HashMap<String, Hook> {
+        &self.hooks
+    }
+
+    pub fn modules(&self) -> &HashMap<String, Module> {
+        &self.modules
+    }
+
+    /// a detailed report on the named module, None if no such module is running
+    /// name can be any prefix that resolve_module settles on a single module
+    /// `println!("{}", framework.inspect_module("sniffer").unwrap())`
+    pub fn inspect_module(&mut self, name: &str) -> Option<ModuleReport> {
+        let name = self.resolve_module(name).ok()?;
+        self.modules.get_mut(&name).map(|module| module.report(&name))
+    }
+
+    /// the running module partial names, the way a shell resolves a job spec: a module called
+    /// exactly partial, otherwise the only one whose name starts with it
+    /// Err holds every candidate, sorted, when there is more than one and is empty when none match
+    /// every method stopping, steering, pausing or inspecting a module by name goes through this,
+    /// so they all settle on the same module; try_kill alone takes exact names
+    pub fn resolve_module(&self, partial: &str) -> Result<String, Vec<String>> {
+        if self.modules.contains_key(partial) {
+            return Ok(String::from(partial));
+        }
+
+        let mut candidates: Vec<String> = self.modules.keys()
+            .filter(|name| name.starts_with(partial))
+            .cloned()
+            .collect();
+
+        // an empty partial would otherwise pick the only module there is without naming it at all
+        if candidates.len() == 1 && !partial.is_empty() {
+            return Ok(candidates.remove(0));
+        }
+
+        candidates.sort();
+        Err(candidates)
+    }
+
+    /// resolve_module with its failures worded for a user
+    fn resolve_module_name(&self, partial: &str) -> Result<String, String> {
+        self.resolve_module(partial).map_err(|candidates| if candidates.is_empty() {
+            format!("{}: No such module", partial)
+        } else {
+            format!("{}: ambiguous, could be {}", partial, candidates.join(", "))
+        })
+    }
+
+    /// what the named hook (or alias) gets access to, None if there is no such hook
+    pub fn hook_level(&self, name: &str) -> Option<HookLevel> {
+        let name = self.aliases.get(name).map_or(name, |target| target.as_str());
+        self.hooks.get(name).map(|hook| hook.level())
+    }
+
+    pub fn hook_count(&self) -> usize {
+        self.hooks.len()
+    }
+
+    pub fn module_count(&self) -> usize {
+        self.modules.len()
+    }
+
+    pub fn has_hooks(&self) -> bool {
+        !self.hooks.is_empty()
+    }
+
+    pub fn has_modules(&self) -> bool {
+        !self.modules.is_empty()
+    }
+
+    /// whether a hook is registered under exactly name, aliases aren't followed
+    pub fn contains_hook(&self, name: &str) -> bool {
+        self.hooks.contains_key(name)
+    }
+
+    /// whether a module is running under exactly name, prefixes aren't resolved
+    pub fn contains_module(&self, name: &str) -> bool {
+        self.modules.contains_key(name)
+    }
+
+    /// alias -> hook name
+    pub fn aliases(&self) -> &HashMap<String, String> {
+        &self.aliases
+    }
+
+    /// the most recent commands run through try_run_hook, oldest first
+    pub fn history(&self) -> &[HistoryEntry] {
+        &self.history
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// everything modules have reported, oldest first, at most config.findings_len of them
+    pub fn findings(&self) -> Vec<Finding> {
+        self.findings.list()
+    }
+
+    pub fn clear_findings(&mut self) {
+        self.findings.clear();
+    }
+
+    /// a generator split off the Framework's own, repeatable from run to run with config.rng_seed set
+    pub fn rng(&mut self) -> Rng {
+        self.rng.split()
+    }
+
+    /// (module, error) for the newest RECENT_ERRORS_LEN errors modules reported while running, oldest first
+    pub fn recent_errors(&self) -> Vec<(String, String)> {
+        self.errors.recent()
+    }
+
+    /// the host tables and running modules as they are right now
+    pub fn snapshot(&self) -> FrameworkSnapshot {
+        let mut snapshot = FrameworkSnapshot::default();
+
+        for interface in self.interfaces() {
+            let nethosts = self.hosts_for(&interface).unwrap().read_nethosts();
+
+            for &ip in nethosts.hosts() {
+                snapshot.hosts.push(HostSnapshot {
+                    interface: interface.clone(),
+                    ip,
+                    mac: nethosts.macs().get(&ip).and_then(|mac| mac.map(|mac| mac.to_string()))
+                });
+            }
+        }
+
+        for (name, module) in &self.modules {
+            snapshot.modules.push(ModuleSnapshot {
+                name: name.clone(),
+                source_hook: module.source_hook.clone(),
+                interface: module.interface.clone(),
+                args: module.args.clone()
+            });
+        }
+
+        snapshot.modules.sort_by(|a, b| a.name.cmp(&b.name));
+        snapshot
+    }
+
+    /// loads snapshot's host tables then reruns the hook behind each of its modules, one result per module
+    /// a module whose hook or interface is gone fails on its own without stopping the rest
+    pub fn restore(&mut self, snapshot: &FrameworkSnapshot) -> Vec<Result<bool, String>> {
+        // hosts of a missing interface are skipped, its modules report it
+        self.load_host_snapshots(&snapshot.hosts);
+
+        snapshot.modules.iter().map(|module| {
+            let args: Vec<&str> = module.args.iter().map(|arg| arg.as_str()).collect();
+            self.try_run_hook_on(&module.interface, &module.source_hook, &args)
+        }).collect()
+    }
+
+    /// false once stopped, or once the operator has hit Ctrl-C with the signal handler installed
+    pub fn is_running(&self) -> bool {
+        self.running && !self.interrupted()
+    }   
+
+    /// whether Ctrl-C has been hit since install_signal_handler
+    pub fn interrupted(&self) -> bool {
+        self.interrupted.load(Ordering::Relaxed)
+    }
+
+    /// has Ctrl-C stop the capture and end is_running, so the frontend's loop can fall through to
+    /// shutdown and every module gets its cleanup, instead of the process dying with caches still
+    /// poisoned; a second Ctrl-C exits at once, for when a module's cleanup hangs
+    /// the handler is process wide, so this fails if one has already been installed
+    /// `while framework.is_running() { ... } framework.shutdown();`
+    #[cfg(feature = "ctrlc")]
+    pub fn install_signal_handler(&self) -> Result<(), String> {
+        let interrupted = self.interrupted.clone();
+        let capturing = self.capturing.clone();
+        let logger = self.logger.clone();
+
+        let handled = ctrlc::set_handler(move || {
+            if interrupted.swap(true, Ordering::Relaxed) {
+                process::exit(130); // 128 + SIGINT, as a shell would report it
+            }
+
+            capturing.store(false, Ordering::Relaxed);
+            logger.log("[*] Interrupted, shutting down (Ctrl-C again to exit at once)");
+        });
+
+        handled.map_err(|err| format!("couldn't install the signal handler: {}", err))
+    }
+
+    pub fn stop(&mut self) {
+        self.running = false;
+        self.capturing.store(false, Ordering::Relaxed);
+    }
+
+    /// stops capturing, stops every module and closes the packet queue
+    /// the capture thread notices within the configured read timeout
+    pub fn shutdown(&mut self) {
+        self.stop();
+        let _ = self.clear_modules();
+
+        // the send thread finishes once the modules' clones of the queues are gone too
+        self.packet_queues = None;
+    }
+
+    pub fn dispatcher(&self) -> Arc<Dispatcher> {
+        self.dispatcher.clone()
+    }
+
+    pub fn send_stats(&self) -> Arc<SendStats> {
+        self.send_stats.clone()
+    }
+
+    pub fn traffic_totals(&self) -> TrafficTotals {
+        TrafficTotals {
+            frames_received: self.dispatcher.frames_received(),
+            bytes_received: self.dispatcher.bytes_received(),
+            frames_sent: self.send_stats.frames_sent(),
+            bytes_sent: self.send_stats.bytes_sent()
+        }
+    }
+
+    /// frames received and, where the capture source reports them, frames it dropped
+    pub fn capture_stats(&self) -> CaptureStats {
+        self.dispatcher.capture_stats()
+    }
+
+    /// a handle modules can keep to log through whatever logger the Framework has installed
+    pub fn logger(&self) -> Logger {
+        self.logger.clone()
+    }
+
+    /// replaces where log lines go, for the Framework and every thread holding its Logger
+    /// hands back the sink it replaced, which set_logger takes again to put things back as they were;
+    /// a new Framework starts out logging to stdout
+    pub fn set_logger(&mut self, sink: LogSink) -> LogSink {
+        self.logger.replace(sink)
+    }
+
+    pub fn log(&self, line: &str) {
+        self.logger.log(line)
+    }
+
+    /// called with a module's name whenever one starts, under whatever name insert_module gave it
+    /// replaces any callback set before; unset, starting a module doesn't look for one
+    pub fn on_module_started(&mut self, callback: ModuleCallback) {
+        self.lifecycle.lock().unwrap().started = Some(callback);
+    }
+
+    /// called with a module's name once it is stopped, killed or reaped having exited cleanly
+    pub fn on_module_stopped(&mut self, callback: ModuleCallback) {
+        self.lifecycle.lock().unwrap().stopped = Some(callback);
+    }
+
+    /// called with a module's name and error once it is stopped or reaped having exited with one,
+    /// or having panicked, instead of on_module_stopped
+    pub fn on_module_failed(&mut self, callback: FailureCallback) {
+        self.lifecycle.lock().unwrap().failed = Some(callback);
+    }
+
+    /// hands the way the named module ended to whichever of the callbacks above it calls for
+    fn module_ended<T>(&self, name: &str, result: &Result<T, String>) {
+        self.lifecycle.lock().unwrap().ended(name, result);
+    }
+
+    /// joins a module that has been told to stop on a thread of its own, see Lifecycle::join_killed
+    fn join_in_background(&self, name: &str, module: Module) {
+        Lifecycle::join_killed(self.lifecycle.clone(), String::from(name), module);
+    }
+
+    pub fn max_modules(&self) -> usize {
+        self.max_modules
+    }
+
+    /// caps how many modules may run at once, 0 removes the cap; hooks that start no module still
+    /// run with the cap reached, only a module started past it is refused
+    pub fn set_max_modules(&mut self, max: usize) {
+        self.max_modules = max
+    }
+
+    // util
+
+    pub fn hook_up(&mut self, name: &'static str, hook: Hook) -> Result<(), ()> {
+        if !self.hooks.contains_key(name) {
+            // a real hook always wins over an alias of the same name
+            self.aliases.remove(name);
+            self.names.push(name);
+            self.hooks.insert(String::from(name), hook);
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// lets alias be typed in place of the target hook's name
+    pub fn alias(&mut self, alias: &str, target: &str) -> Result<(), String> {
+        if self.hooks.contains_key(alias) {
+            Err(format!("{}: already the name of a hook", alias))
+        } else if !self.hooks.contains_key(target) {
+            Err(format!("{}: No such hook", target))
+        } else {
+            self.aliases.insert(String::from(alias), String::from(target));
+            Ok(())
+        }
+    }
+
+    /// tags the named hook as needing cap, which set_allowed_caps then has to grant before the hook runs
+    /// a hook needs at most one capability, tagging it again replaces the old one
+    pub fn require_cap(&mut self, hook: &str, cap: &str) -> Result<(), String> {
+        if !self.hooks.contains_key(hook) {
+            return Err(format!("{}: No such hook", hook));
+        }
+
+        self.hook_caps.insert(String::from(hook), String::from(cap));
+        Ok(())
+    }
+
+    /// lists the named hook under tag, like "discovery", "mitm" or "passive", for frontends grouping
+    /// hooks in their help; a hook can carry any number of tags, matched regardless of case
+    pub fn tag_hook(&mut self, hook: &str, tag: &str) -> Result<(), String> {
+        if !self.hooks.contains_key(hook) {
+            return Err(format!("{}: No such hook", hook));
+        }
+
+        self.hook_tags.entry(String::from(hook)).or_default().insert(tag.to_lowercase());
+        Ok(())
+    }
+
+    /// the tags the named hook carries, sorted and lowercased
+    pub fn hook_tags(&self, hook: &str) -> Vec<&str> {
+        match self.hook_tags.get(hook) {
+            Some(tags) => tags.iter().map(|tag| tag.as_str()).collect(),
+            None => Vec::new()
+        }
+    }
+
+    /// the names of every hook tagged with tag, sorted, `help --category mitm` lists these
+    pub fn hooks_by_tag(&self, tag: &str) -> Vec<&str> {
+        let tag = tag.to_lowercase();
+
+        let mut hooks: Vec<&str> = self.hook_tags.iter()
+            .filter(|&(_, tags)| tags.contains(&tag))
+            .map(|(hook, _)| hook.as_str())
+            .collect();
+
+        hooks.sort();
+        hooks
+    }
+
+    /// writes every interface's host table to path, returning how many hosts were saved
+    pub fn save_hosts(&self, path: &Path) -> Result<usize, String> {
+        let file = HostsFile::new(self.snapshot().hosts);
+
+        match fs::write(path, file.to_text()) {
+            Ok(()) => Ok(file.hosts.len()),
+            Err(e) => Err(format!("{}: {}", path.display(), e))
+        }
+    }
+
+    /// adds the hosts in a file written by save_hosts to the host tables, returning how many were added
+    /// hosts of interfaces the Framework doesn't have are skipped, a file in a newer format is refused
+    pub fn load_hosts(&mut self, path: &Path) -> Result<usize, String> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => return Err(format!("{}: {}", path.display(), e))
+        };
+
+        match HostsFile::parse(&text) {
+            Ok(file) => Ok(self.load_host_snapshots(&file.hosts)),
+            Err(e) => Err(format!("{}: {}", path.display(), e))
+        }
+    }
+
+    /// inserts hosts into their interfaces' tables, returning how many had an interface to go in
+    fn load_host_snapshots(&mut self, snapshots: &[HostSnapshot]) -> usize {
+        let mut loaded = 0;
+
+        for host in snapshots {
+            let mut hosts = match self.hosts_for_mut(&host.interface) {
+                Some(hosts) => hosts.acquire_nethosts(),
+                None => continue
+            };
+
+            hosts.insert(host.ip);
+
+            if let Some(mac) = host.mac.as_ref().and_then(|mac| mac.parse::<MacAddr>().ok()) {
+                hosts.set_host(host.ip, mac);
+            }
+
+            loaded += 1;
+        }
+
+        loaded
+    }
+
+    /// the capability the named hook is tagged with, if any
+    pub fn hook_cap(&self, hook: &str) -> Option<&str> {
+        self.hook_caps.get(hook).map(|cap| cap.as_str())
+    }
+
+    /// refuses to run any tagged hook whose capability isn't in caps, untagged hooks still run
+    /// meant to keep a restricted frontend like a shared control socket from starting offensive
+    /// hooks by accident, it is no defence against a plugin or Framework hook that calls this itself
+    pub fn set_allowed_caps<I, S>(&mut self, caps: I) where I: IntoIterator<Item = S>, S: Into<String> {
+        self.allowed_caps = Some(caps.into_iter().map(|cap| cap.into()).collect());
+    }
+
+    /// lets every hook run again, as before any set_allowed_caps
+    pub fn allow_all_caps(&mut self) {
+        self.allowed_caps = None;
+    }
+
+    /// Err if the named hook's capability hasn't been granted
+    fn check_cap(&self, hook: &str) -> Result<(), String> {
+        match (self.hook_caps.get(hook), &self.allowed_caps) {
+            (Some(cap), Some(allowed)) if !allowed.contains(cap) => {
+                Err(format!("{}: Permission denied, needs the {} capability", hook, cap))
+            },
+
+            _ => Ok(())
+        }
+    }
+
+    /// registers every hook a plugin exports, refusing plugins built for a different RUSTNEEDLE_ABI
+    pub fn load_hooks_from(&mut self, lib: Library) -> Result<(), Vec<String>> {
+        let abi = unsafe { match lib.get::<AbiVersion>(b"abi_version") {
+            Ok(abi_version) => abi_version(),
+            Err(_) => return Err(vec![String::from("plugin exports no abi_version, rebuild it with rustneedle_plugin!")])
+        }};
+
+        // the library is dropped without load ever being called
+        if abi != RUSTNEEDLE_ABI {
+            return Err(vec![format!("plugin was built for ABI version {}, this is version {}", abi, RUSTNEEDLE_ABI)]);
+        }
+
+        // metadata is optional, the strings are copied since they live in the library
+        let info = unsafe { match lib.get::<InfoLoader>(b"plugin_info") {
+            Ok(plugin_info) => {
+                let meta = plugin_info();
+                (String::from(meta.name), String::from(meta.author), String::from(meta.version))
+            },
+
+            Err(_) => (String::from("unnamed"), String::new(), String::new())
+        }};
+
+        // resolved before the library is kept, so one without it is dropped rather than left mapped unlisted
+        let hooks = unsafe { match lib.get::<HookLoader>(b"load") {
+            Ok(load) => load(),
+            Err(e) => return Err(vec![e.to_string()])
+        }};
+
+        let index = self.libraries.len();
+        self.libraries.push(Some(lib));
+
+        let result = self.hook_up_all(hooks, Some(index));
+
+        let mut hooks: Vec<String> = self.hook_sources.iter()
+            .filter(|&(_, &source)| source == index)
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        hooks.sort();
+
+        self.plugins.push(PluginInfo {
+            library: index,
+            name: info.0,
+            author: info.1,
+            version: info.2,
+            hooks
+        });
+
+        result
+    }
+
+    /// every plugin still loaded, in the order they were loaded
+    pub fn plugins(&self) -> &[PluginInfo] {
+        &self.plugins
+    }
+
+    /// registers the hooks built into the core, such as pingsweep
+    /// the ones that transmit or harvest credentials are tagged with the capability in builtins::CAPS,
+    /// and every one with its categories in builtins::TAGS
+    pub fn load_builtins(&mut self) -> Result<(), Vec<String>> {
+        // a name already taken by some other hook keeps whatever tags that one has
+        let untaken = |&&(name, _): &&(&str, &str)| !self.hooks.contains_key(name);
+        let caps: Vec<(&str, &str)> = builtins::CAPS.iter().filter(untaken).cloned().collect();
+        let tags: Vec<(&str, &str)> = builtins::TAGS.iter().filter(untaken).cloned().collect();
+
+        let result = self.hook_up_all(builtins::hooks(), None);
+
+        for (name, cap) in caps {
+            let _ = self.require_cap(name, cap);
+        }
+
+        for (name, tag) in tags {
+            let _ = self.tag_hook(name, tag);
+        }
+
+        result
+    }
+
+    fn hook_up_all(&mut self, hooks: Vec<(&'static str, Hook)>, library: Option<usize>) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        for (name, hook) in hooks.into_iter() {
+            if self.hook_up(name, hook).is_err() {
+                errors.push(format!("{} already bound", name));
+            } else if let Some(index) = library {
+                self.hook_sources.insert(String::from(name), index);
+            }
+        };
+
+        if !errors.is_empty() {
+            Err(errors)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// index of the library that registered the named hook, None for hooks from the core
+    pub fn hook_library(&self, name: &str) -> Option<usize> {
+        self.hook_sources.get(name).cloned()
+    }
+
+    /// removes every hook the library at index registered and stops the modules they started,
+    /// then unloads it, returning the names of the hooks removed
+    pub fn unload_library(&mut self, index: usize) -> Vec<String> {
+        let mut removed: Vec<String> = self.hook_sources.iter()
+            .filter(|&(_, &source)| source == index)
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        removed.sort();
+
+        let spawned: Vec<String> = self.modules.iter()
+            .filter(|&(_, module)| removed.contains(&module.source_hook))
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        // modules run the library's code, so each has to have exited before it can go
+        for name in spawned.iter() {
+            let _ = self.stop_module(name);
+        }
+
+        for name in removed.iter() {
+            self.hooks.remove(name);
+            self.hook_sources.remove(name);
+            self.hook_caps.remove(name);
+            self.hook_tags.remove(name);
+            self.pooled_hooks.remove(name);
+        }
+
+        // names borrow their strings from the library too
+        self.names.retain(|name| !removed.iter().any(|gone| gone == name));
+        self.aliases.retain(|_, target| !removed.contains(target));
+
+        self.plugins.retain(|plugin| plugin.library != index);
+
+        if let Some(library) = self.libraries.get_mut(index) {
+            library.take();
+        }
+
+        removed
+    }
+
+    /// swaps the library at index for a fresh build at new_path, for working on a plugin without a restart
+    /// modules the old hooks started are stopped and the old library dropped before the new one is
+    /// opened, as the loader would otherwise hand back the copy already mapped; if the new build then
+    /// fails to load, the old one is gone regardless. aliases, capabilities, tags and pooling of hooks
+    /// the new build registers again are carried over, its modules aren't restarted
+    pub fn reload_library(&mut self, index: usize, new_path: &Path) -> Result<LibraryReload, Vec<String>> {
+        if !matches!(self.libraries.get(index), Some(Some(_))) {
+            return Err(vec![format!("{}: no library loaded at that index", index)]);
+        }
+
+        let aliases: Vec<(String, String)> = self.aliases.iter()
+            .filter(|&(_, target)| self.hook_sources.get(target) == Some(&index))
+            .map(|(alias, target)| (alias.clone(), target.clone()))
+            .collect();
+
+        let caps: Vec<(String, String)> = self.hook_caps.iter()
+            .filter(|&(hook, _)| self.hook_sources.get(hook) == Some(&index))
+            .map(|(hook, cap)| (hook.clone(), cap.clone()))
+            .collect();
+
+        let tags: Vec<(String, BTreeSet<String>)> = self.hook_tags.iter()
+            .filter(|&(hook, _)| self.hook_sources.get(hook) == Some(&index))
+            .map(|(hook, tags)| (hook.clone(), tags.clone()))
+            .collect();
+
+        let pooled: Vec<String> = self.pooled_hooks.iter()
+            .filter(|hook| self.hook_sources.get(*hook) == Some(&index))
+            .cloned()
+            .collect();
+
+        let old = self.unload_library(index);
+
+        let lib = match Library::new(new_path) {
+            Ok(lib) => lib,
+            Err(e) => return Err(vec![format!("{}: {}", new_path.display(), e)])
+        };
+
+        let library = self.libraries.len();
+        self.load_hooks_from(lib)?;
+
+        let mut new: Vec<String> = self.hook_sources.iter()
+            .filter(|&(_, &source)| source == library)
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        new.sort();
+
+        for (alias, target) in aliases {
+            if new.contains(&target) {
+                let _ = self.alias(&alias, &target);
+            }
+        }
+
+        for (hook, cap) in caps {
+            if new.contains(&hook) {
+                let _ = self.require_cap(&hook, &cap);
+            }
+        }
+
+        for (hook, tags) in tags {
+            if new.contains(&hook) {
+                self.hook_tags.insert(hook, tags);
+            }
+        }
+
+        for hook in pooled {
+            if new.contains(&hook) {
+                self.pooled_hooks.insert(hook);
+            }
+        }
+
+        Ok(LibraryReload {
+            library,
+            kept: new.iter().filter(|name| old.contains(name)).cloned().collect(),
+            added: new.iter().filter(|name| !old.contains(name)).cloned().collect(),
+            removed: old.iter().filter(|name| !new.contains(name)).cloned().collect()
+        })
+    }
+
+    /// runs the named hook, Ok(true) means it started a module
+    pub fn try_run_hook(&mut self, name: &str, args: &[&str]) -> Result<bool, String> {
+        let interface = self.config.interface.clone();
+        self.try_run_hook_on(&interface, name, args)
+    }
+
+    /// runs the named hook against an interface's HostMgr, any module it starts only sees that interface's frames
+    pub fn try_run_hook_on(&mut self, interface: &str, name: &str, args: &[&str]) -> Result<bool, String> {
+        let result = self.run_hook(interface, name, args);
+        self.record_history(interface, name, args, result.is_ok());
+        result
+    }
+
+    /// runs the named hook on a scratch thread and gives up on it once timeout has passed
+    /// only HostMgr and Standalone hooks can be run this way, a Framework hook needs the Framework itself and
+    /// that can't be handed to another thread. A hook that times out is abandoned rather than stopped: its thread
+    /// is left to finish on its own, and any module it returns after the timeout is killed straight away and
+    /// joined in the background, the way try_kill joins one
+    pub fn run_hook_with_timeout(&mut self, name: &str, args: &[&str], timeout: Duration) -> Result<bool, String> {
+        let interface = self.config.interface.clone();
+        let result = self.run_hook_timed(&interface, name, args, timeout);
+        self.record_history(&interface, name, args, result.is_ok());
+        result
+    }
+
+    fn run_hook_timed(&mut self, interface: &str, name: &str, args: &[&str], timeout: Duration) -> Result<bool, String> {
+        let name = match self.aliases.get(name) {
+            Some(target) => target.clone(),
+            None => name.to_owned()
+        };
+
+        let hook = match self.hooks.get(&name) {
+            Some(&Hook::HostMgr(func)) => Hook::HostMgr(func),
+            Some(&Hook::Standalone(func)) => Hook::Standalone(func),
+            Some(&Hook::Framework(_)) => return Err(format!("{}: Framework hooks can't be run with a timeout", name)),
+            None => return Err(format!("{}: No such hook", name))
+        };
+
+        self.check_cap(&name)?;
+
+        let mut hosts = match self.hosts_for(interface) {
+            Some(hosts) => hosts.clone(),
+            None => return Err(format!("{}: No such interface", interface))
+        };
+
+        let env = self.hook_env(interface, &name);
+        let reporter = env.reporter.clone();
+        let owned: Vec<String> = args.iter().map(|&arg| String::from(arg)).collect();
+        let (results, finished) = channel();
+        let lifecycle = self.lifecycle.clone();
+        let started_by = name.clone();
+
+        thread::spawn(move || {
+            let args: Vec<&str> = owned.iter().map(|arg| arg.as_str()).collect();
+
+            let result = match hook {
+                Hook::HostMgr(func) => func(&args, &env, &mut hosts),
+                Hook::Standalone(func) => func(&args, &env),
+                Hook::Framework(_) => unreachable!()
+            };
+
+            // nobody is waiting any more, so a module started this late is stopped rather than left running
+            // unowned, and joined the way try_kill joins one so its cleanup still runs
+            if let Err(SendError(Ok(Some(mut module)))) = results.send(result) {
+                let _ = module.kill();
+                Lifecycle::join_killed(lifecycle, started_by, module);
+            }
+        });
+
+        match finished.recv_timeout(timeout) {
+            Ok(result) => self.start_module(interface, name, args, result, reporter),
+            Err(_) => Err(format!("{}: timed out after {:?}", name, timeout))
+        }
+    }
+
+    /// the env the named hook runs with, its Reporter feeding Framework::findings and its errors
+    /// Framework::recent_errors, and its Rng split off the Framework's
+    fn hook_env(&mut self, interface: &str, hook: &str) -> HookEnv {
+        let mut env = HookEnv::on(&self.config, interface);
+        env.reporter = self.findings.reporter(hook, interface);
+        env.errors = self.errors.sender();
+        env.rng = self.rng.split();
+        env
+    }
+
+    fn record_history(&mut self, interface: &str, name: &str, args: &[&str], succeeded: bool) {
+        if self.config.history_len > 0 {
+            if self.history.len() >= self.config.history_len {
+                let overflow = self.history.len() + 1 - self.config.history_len;
+                self.history.drain(..overflow);
+            }
+
+            self.history.push(HistoryEntry {
+                hook: String::from(name),
+                args: args.iter().map(|&arg| String::from(arg)).collect(),
+                interface: String::from(interface),
+                succeeded
+            });
+        }
+    }
+
+    fn run_hook(&mut self, interface: &str, name: &str, args: &[&str]) -> Result<bool, String> {
+        let name = match self.aliases.get(name) {
+            Some(target) => target.clone(),
+            None => name.to_owned()
+        };
+
+        if self.hosts_for(interface).is_none() {
+            return Err(format!("{}: No such interface", interface));
+        }
+
+        if self.hooks.contains_key(&name) {
+            self.check_cap(&name)?;
+
+            let env = self.hook_env(interface, &name);
+
+            let result = match self.hooks[&name] {
+                Hook::Framework(func) => func(args, &env, self),
+                Hook::HostMgr(func) => func(args, &env, self.hosts_for_mut(interface).unwrap()),
+                Hook::Standalone(func) => func(args, &env)
+            };
+
+            self.start_module(interface, name, args, result, env.reporter)
+        } else {
+            Err(format!("{}: No such hook", name))
+        }
+    }
+
+    /// takes in whatever module a hook run returned, name being the hook that was run and reporter the one its env had
+    fn start_module(&mut self, interface: &str, name: String, args: &[&str], result: Result<Option<Module>, String>, reporter: Reporter) -> Result<bool, String> {
+        match result {
+            Ok(modopt) => match modopt {
+                Some(mut module) => {
+                    if self.max_modules > 0 {
+                        self.reap_finished_modules();
+
+                        // the module never started as far as anyone else knows, so it is joined quietly
+                        // rather than through the lifecycle callbacks, just so its cleanup still runs
+                        if self.modules.len() >= self.max_modules {
+                            let _ = module.kill();
+                            thread::spawn(move || module.join());
+                            return Err(format!("{}: module limit of {} reached", name, self.max_modules));
+                        }
+                    }
+
+                    module.source_hook = name.clone();
+                    module.interface = String::from(interface);
+                    module.args = args.iter().map(|&arg| String::from(arg)).collect();
+                    module.reporter = Some(reporter);
+
+                    self.insert_module(&name, module);
+                    Ok(true)
+                },
+
+                None => Ok(false)
+            },
+
+            /*
+            because this Err is a Result<Option<...>, String> and not a Result<bool, String>, rustc won't let me just
+            use "e => e". The Ok(Option<...>) has already been matched, so any other result would HAVE to be an
+            Err(string) since that's only other type left in the enum. But because this Err(String) is "from" a
+            different kind of Result, apparently I have to get the innter String out of the Err and then re-wrap it.
+            I'm sure there's a way to do this that doesn't look fucking stupid, but I can't think of the it atm.
+            */
+            Err(s) => Err(s)
+        }
+    }
+
+    /// starts running module under desired, or desired_1, desired_2 and so on if that's taken,
+    /// subscribing its filter on the interface it was started on and returning the name it got
+    /// every module the Framework runs goes in through here, so no two ever end up sharing a name
+    pub fn insert_module(&mut self, desired: &str, mut module: Module) -> String {
+        let mut name = String::from(desired);
+        let mut counter = 1;
+
+        while self.modules.contains_key(&name) {
+            name = format!("{}_{}", desired, counter);
+            counter += 1;
+        }
+
+        if module.interface.is_empty() {
+            module.interface = self.config.interface.clone();
+        }
+
+        module = if self.pooled_hooks.contains(&module.source_hook) {
+            module.start(Some(self.module_pool()))
+        } else {
+            module.start(None)
+        };
+
+        module.name = name.clone();
+        module.span = ModuleSpan::new(&name, &module.source_hook, &module.interface);
+        module.span.started();
+
+        if let Some(filter) = module.filter.take() {
+            module.subscription = Some(self.dispatcher.subscribe_on(&module.interface, &name, filter, module.span.clone()));
+            self.start_sampling();
+        }
+
+        self.log(&format!("[*] Started '{}'", name));
+
+        if let Some(ref started) = self.lifecycle.lock().unwrap().started {
+            started(&name);
+        }
+
+        self.modules.insert(name.clone(), module);
+        name
+    }
+
+    /// starts the thread behind Module::rate_pps, once, when the first module with a filter is
+    /// inserted, so a Framework that never runs one never has it
+    fn start_sampling(&mut self) {
+        if self.sampling {
+            return;
+        }
+
+        if let Some(interval) = self.config.rate_interval {
+            let dispatcher = Arc::downgrade(&self.dispatcher);
+            let alpha = self.config.rate_alpha;
+
+            thread::spawn(move || dispatch::rate_loop(dispatcher, interval, alpha));
+            self.sampling = true;
+        }
+    }
+
+    /// runs one `hook arg1 arg2` command per line of the file, carrying on past failed lines
+    pub fn run_script(&mut self, path: &Path) -> Vec<Result<bool, String>> {
+        let script = match fs::read_to_string(path) {
+            Ok(script) => script,
+            Err(e) => return vec![Err(format!("{}: {}", path.display(), e))]
+        };
+
+        let mut results = Vec::new();
+
+        for (number, line) in script.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            results.push(match split_command(line) {
+                Ok(words) => {
+                    let args: Vec<&str> = words[1..].iter().map(|arg| arg.as_str()).collect();
+                    self.try_run_hook(&words[0], &args)
+                },
+
+                Err(e) => Err(format!("line {}: {}", number + 1, e))
+            });
+        }
+
+        results
+    }
+
+    /// starts taking commands over TCP on addr, returning the address actually bound
+    /// commands only run when the owner of the Framework calls handle_control_commands
+    pub fn serve_control(&mut self, addr: SocketAddr) -> io::Result<SocketAddr> {
+        let (bound, commands) = control::listen(addr)?;
+        self.control = Some(commands);
+        Ok(bound)
+    }
+
+    /// runs every command that has come in over the control socket, returning how many ran
+    pub fn handle_control_commands(&mut self) -> usize {
+        let requests: Vec<ControlRequest> = match self.control {
+            Some(ref commands) => commands.try_iter().collect(),
+            None => return 0
+        };
+
+        for request in requests.iter() {
+            let result = split_command(&request.line).and_then(|words| {
+                let args: Vec<&str> = words[1..].iter().map(|arg| arg.as_str()).collect();
+                self.try_run_hook(&words[0], &args)
+            });
+
+            let _ = request.reply.send(match result {
+                Ok(true) => String::from("ok: module started"),
+                Ok(false) => String::from("ok"),
+                Err(e) => format!("error: {}", e)
+            });
+        }
+
+        requests.len()
+    }
+
+    /// opens the configured interface and starts capturing from and sending to it
+    /// with promiscuous configured, fails rather than start if the interface can't be made promiscuous
+    /// fails too if the interface captures some other link type than config.link_type, so modules
+    /// never read 802.11 frames as ethernet or the other way around
+    pub fn open_channel(&mut self) -> io::Result<()> {
+        let interface = self.config.interface.clone();
+        self.open_channel_on(&interface)
+    }
+
+    /// like open_channel for an interface given its own HostMgr with add_interface, its frames are
+    /// captured tagged with interface and the frames queued for it are sent out on it, see start_sender_on
+    pub fn open_channel_on(&mut self, interface: &str) -> io::Result<()> {
+        if self.hosts_for(interface).is_none() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, format!("{}: interface was never added", interface)));
+        }
+
+        let interface = match datalink::interfaces().into_iter().find(|iface| iface.name == interface) {
+            Some(interface) => interface,
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, format!("{}: No such interface", interface)))
+        };
+
+        let link_type = os::link_type(&interface.name)?;
+
+        if link_type != self.config.link_type {
+            let hint = match self.config.link_type {
+                LinkType::Radiotap | LinkType::Ieee80211 => ", put it into monitor mode first",
+                _ => ""
+            };
+
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!(
+                "{}: captures {}, not {}{}",
+                interface.name,
+                link_type,
+                self.config.link_type,
+                hint
+            )));
+        }
+
+        match datalink::channel(&interface, self.config.datalink_config())? {
+            Channel::Ethernet(sender, receiver) => {
+                // before anything starts, so a failure here closes the channel again
+                if self.config.promiscuous {
+                    os::enable_promiscuous(&interface.name)?;
+                }
+
+                self.start_sender_on(&interface.name, sender);
+                self.start_capture_on(&interface.name, dispatch::InterfaceSource::new(receiver, &interface.name));
+                Ok(())
+            },
+
+            _ => Err(io::Error::other(format!("{}: not an ethernet channel", interface.name)))
+        }
+    }
+
+    /// the pre-dispatcher way of wiring up the packet path: every frame the caller's capture loop hands to the
+    /// sender given over mod_drop reaches module filters, and every queued frame is passed on to sender
+    #[deprecated(note = "use start_capture and start_sender, or open_channel")]
+    pub fn init_task_mpscs(&mut self, mod_drop: Sender<Sender<Arc<Vec<u8>>>>, sender: Sender<Vec<u8>>) {
+        let (frame_tx, frames) = channel();
+
+        // the old loop handed each frame to every sender it had been given, so this one gets them all
+        let _ = mod_drop.send(frame_tx);
+
+        self.start_capture(dispatch::ChannelSource::new(frames));
+        self.start_sender(dispatch::ChannelSink(sender));
+    }
+
+    /// starts feeding frames from source to module filters on a thread of its own
+    pub fn start_capture<S: PacketSource + 'static>(&mut self, source: S) {
+        let interface = self.config.interface.clone();
+        self.start_capture_on(&interface, source);
+    }
+
+    /// like start_capture, with frames tagged as captured on interface
+    pub fn start_capture_on<S: PacketSource + 'static>(&mut self, interface: &str, source: S) {
+        let dispatcher = self.dispatcher.clone();
+        let capturing = self.capturing.clone();
+        let interface = String::from(interface);
+        let workers = self.config.dispatch_workers;
+        let snaplen = self.config.snaplen;
+
+        capturing.store(true, Ordering::Relaxed);
+        thread::spawn(move || dispatch::capture_loop(source, &interface, dispatcher, capturing, workers, snaplen));
+    }
+
+    /// starts writing the packet queue out to sink on a thread of its own
+    /// with dry_run configured, frames are logged and counted as suppressed instead
+    /// with a dedup_window, repeats of a frame sent within it are dropped and counted as deduplicated
+    pub fn start_sender<K: PacketSink + 'static>(&mut self, sink: K) {
+        let (mut queues, frames) = dispatch::send_queues();
+        let sinks = self.sinks.clone();
+        let stats = self.send_stats.clone();
+        let logger = self.logger.clone();
+
+        let options = SendOptions {
+            dry_run: self.config.dry_run,
+            batch_size: self.config.batch_size,
+            dedup_window: self.config.dedup_window,
+            fairness: self.config.send_fairness,
+            interface: self.config.interface.clone()
+        };
+
+        // the added interfaces keep their sinks, they only need queues into the new consumer
+        for interface in sinks.lock().unwrap().keys() {
+            queues.on.insert(interface.clone(), dispatch::plain_queues(&queues.tagged, Some(interface)));
+        }
+
+        // replacing the queues drops the old senders, which lets any previous send thread finish
+        self.packet_queues = Some(queues);
+        thread::spawn(move || dispatch::send_loop(sink, frames, sinks, stats, options, logger));
+    }
+
+    /// starts sending the frames queued for interface out to sink, start_sender for the configured interface
+    /// the send consumer is shared, so frames for an added interface only go out once start_sender has been called too
+    pub fn start_sender_on<K: PacketSink + 'static>(&mut self, interface: &str, sink: K) {
+        if interface == self.config.interface {
+            return self.start_sender(sink);
+        }
+
+        self.sinks.lock().unwrap().insert(String::from(interface), Box::new(sink));
+
+        // one set of forwarders per interface, a sink swapped in later goes out through the same queues
+        if let Some(ref mut queues) = self.packet_queues {
+            let tagged = &queues.tagged;
+            queues.on.entry(String::from(interface)).or_insert_with(|| dispatch::plain_queues(tagged, Some(interface)));
+        }
+    }
+
+    /// takes a module out of the map and stops the dispatcher delivering to it
+    fn remove_module(&mut self, name: &str) -> Option<Module> {
+        let module = self.modules.remove(name);
+
+        if let Some(Module { subscription: Some(ref subscription), .. }) = module {
+            // a Block filter could otherwise hold the dispatcher, and with it unsubscribe, up indefinitely
+            subscription.close();
+            self.dispatcher.unsubscribe(name);
+        }
+
+        module
+    }
+
+    /// kills the named module without waiting for it, a module that had already exited is joined instead
+    /// a killed module is joined in the background, the lifecycle callbacks only hear of it once it has exited
+    pub fn try_kill(&mut self, name: &str) -> Result<(), String> {
+        if let Some(mut module) = self.remove_module(name) {
+            if module.kill().is_ok() {
+                self.join_in_background(name, module);
+                return Ok(());
+            }
+
+            // already dead, so joining won't block and its result isn't lost with it
+            let result = module.join();
+            self.module_ended(name, &result);
+
+            match result {
+                Ok(_) => Ok(()),
+                Err(err) => Err(format!("{}: {}", name, err))
+            }
+
+        } else {
+            Err(format!("{}: No such module", name))
+        }
+    }
+
+    /// kills the named module and waits for it to exit, returning what it reported
+    /// name can be a prefix, see resolve_module
+    pub fn stop_module(&mut self, name: &str) -> Result<ModuleOutcome, String> {
+        let name = self.resolve_module_name(name)?;
+
+        match self.remove_module(&name) {
+            Some(module) => self.stop_removed(&name, module),
+            None => Err(format!("{}: No such module", name))
+        }
+    }
+
+    /// stops every module and waits for each to exit, leaving hooks, host tables, capture and sender
+    /// as they were, unlike shutdown; one result per module, in name order, errors naming their module
+    pub fn clear_modules(&mut self) -> Vec<Result<(), String>> {
+        let mut names: Vec<String> = self.modules.keys().cloned().collect();
+        names.sort();
+
+        names.iter().map(|name| match self.remove_module(name) {
+            Some(module) => self.stop_removed(name, module).map(|_| ()).map_err(|err| format!("{}: {}", name, err)),
+            None => Err(format!("{}: No such module", name))
+        }).collect()
+    }
+
+    /// stops a module already taken out of modules, logging whether it acknowledged the stop
+    fn stop_removed(&self, name: &str, module: Module) -> Result<ModuleOutcome, String> {
+        let (ack, result) = module.stop_acked(self.config.ack_timeout);
+
+        match ack {
+            StopAck::Acked => self.log(&format!("[*] Stopped '{}' gracefully", name)),
+            StopAck::Unacked => self.log(&format!("[!] '{}' didn't acknowledge its stop within {:?}, joined it anyway", name, self.config.ack_timeout)),
+            StopAck::NoAck => ()
+        }
+
+        self.module_ended(name, &result);
+        result
+    }
+
+    /// stop_module, but waits at most timeout, see Module::stop_within
+    pub fn stop_module_within(&mut self, name: &str, timeout: Duration) -> Result<ModuleOutcome, String> {
+        let name = &self.resolve_module_name(name)?;
+
+        match self.remove_module(name) {
+            Some(module) => {
+                let result = module.stop_within(timeout);
+                self.module_ended(name, &result);
+                result.map_err(|err| format!("{}: {}", name, err))
+            },
+
+            None => Err(format!("{}: No such module", name))
+        }
+    }
+
+    /// stops the named module, waits for it to exit, then reruns the hook that started it with args
+    /// name can be a prefix, see resolve_module
+    pub fn restart_module(&mut self, name: &str, args: &[&str]) -> Result<(), String> {
+        let name = &self.resolve_module_name(name)?;
+
+        let (source, interface) = match self.modules.get(name) {
+            Some(module) => (module.source_hook.clone(), module.interface.clone()),
+            None => return Err(format!("{}: No such module", name))
+        };
+
+        if !self.hooks.contains_key(&source) {
+            return Err(format!("{}: hook '{}' is no longer registered", name, source));
+        }
+
+        // the old instance has to be gone before the new one starts, otherwise both run at once
+        let _ = self.stop_module(name);
+
+        self.try_run_hook_on(&interface, &source, args).map(|_| ())
+    }
+
+    /// how far along the named module is, done of total, as it last published through its Reporter
+    /// None if there is no such module or it never published any
+    pub fn scan_progress(&self, name: &str) -> Option<(usize, usize)> {
+        self.modules.get(name)?.reporter.as_ref()?.last_progress()
+    }
+
+    /// hands control to the named scan, an error if the module doesn't take ScanControl or has already finished
+    pub fn steer_scan(&self, name: &str, control: ScanControl) -> Result<(), String> {
+        let name = &self.resolve_module_name(name)?;
+
+        match self.modules.get(name) {
+            Some(&Module { controls: Some(ref controls), .. }) => match controls.send(control) {
+                Ok(()) => Ok(()),
+                Err(_) => Err(format!("{}: scan has already finished", name))
+            },
+
+            Some(_) => Err(format!("{}: module can't be steered", name)),
+            None => Err(format!("{}: No such module", name))
+        }
+    }
+
+    /// stops delivering frames to the named module without stopping it, so it keeps its state
+    pub fn pause_module(&mut self, name: &str) -> Result<(), String> {
+        self.set_module_paused(name, true)
+    }
+
+    pub fn resume_module(&mut self, name: &str) -> Result<(), String> {
+        self.set_module_paused(name, false)
+    }
+
+    /// points the named module at different frames without restarting it
+    pub fn retarget_module(&mut self, name: &str, filter: PackFilter) -> Result<(), String> {
+        let name = &self.resolve_module_name(name)?;
+
+        let module = match self.modules.get_mut(name) {
+            Some(module) => module,
+            None => return Err(format!("{}: No such module", name))
+        };
+
+        // a module started without a filter gets its first subscription here
+        if let Err(filter) = module.set_filter(filter) {
+            module.subscription = Some(self.dispatcher.subscribe_on(&module.interface, name, filter, module.span.clone()));
+            self.start_sampling();
+        }
+
+        Ok(())
+    }
+
+    fn set_module_paused(&mut self, name: &str, paused: bool) -> Result<(), String> {
+        let name = &self.resolve_module_name(name)?;
+
+        match self.modules.get(name) {
+            Some(&Module { subscription: Some(ref subscription), .. }) => {
+                subscription.set_paused(paused);
+                Ok(())
+            },
+
+            Some(_) => Err(format!("{}: module doesn't take packets", name)),
+            None => Err(format!("{}: No such module", name))
+        }
+    }
+
+    /// kills every module whose name starts with prefix and waits up to ack_timeout for them to
+    /// exit, returning the names of those that did, including those that exited with an error; one
+    /// that outlives the wait is logged and left to a background join, it doesn't hold up the rest
+    pub fn kill_modules_matching(&mut self, prefix: &str) -> Vec<String> {
+        let mut names: Vec<String> = self.modules.keys()
+            .filter(|name| name.starts_with(prefix))
+            .cloned()
+            .collect();
+
+        names.sort();
+
+        // every match is told to stop before any is waited on, so the wait is shared
+        let killed: Vec<(String, Module)> = names.into_iter()
+            .filter_map(|name| self.remove_module(&name).map(|mut module| {
+                let _ = module.kill();
+                (name, module)
+            }))
+            .collect();
+
+        let deadline = Instant::now() + self.config.ack_timeout;
+
+        killed.into_iter()
+            .filter_map(|(name, module)| {
+                while !module.is_finished() && Instant::now() < deadline {
+                    thread::sleep(STOP_POLL);
+                }
+
+                if !module.is_finished() {
+                    self.log(&format!("[!] '{}' didn't exit within {:?}, abandoned it", name, self.config.ack_timeout));
+                    self.join_in_background(&name, module);
+                    return None;
+                }
+
+                let result = module.join();
+                self.module_ended(&name, &result);
+
+                if let Err(ref err) = result {
+                    self.log(&format!("[!] {}: {}", name, err));
+                }
+
+                Some(name)
+            })
+            .collect()
+    }
+
+    /// names of still running modules whose heartbeat has gone quiet for longer than timeout
+    pub fn unhealthy_modules(&mut self, timeout: Duration) -> Vec<String> {
+        let mut names: Vec<String> = self.modules.iter_mut()
+            .filter(|(_, module)| !module.is_finished())
+            .filter_map(|(name, module)| if module.is_healthy(timeout) {
+                None
+            } else {
+                Some(name.clone())
+            })
+            .collect();
+
+        names.sort();
+        names
+    }
+
+    /// removes modules whose threads have exited, returning each one's name and result
+    /// a module that dropped its filter's receiver counts as finished too, it is killed before being joined
+    pub fn reap_finished_modules(&mut self) -> Vec<(String, Result<ModuleOutcome, String>)> {
+        let finished: Vec<String> = self.modules.iter()
+            .filter(|&(_, module)| module.is_finished() || module.is_disconnected())
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        finished.into_iter().map(|name| {
+            let result = self.stop_module(&name);
+            (name, result)
+        }).collect()
+    }
+
+    /// hands every frame to packet_tx for as long as the Framework lives
+    /// prefer Module::with_filter, which ties the delivery to the module and stops it with the module
+    /// no longer fails now the dispatcher is always there, the Result is only kept for older callers
+    pub fn insert_packet_tx(&mut self, packet_tx: Sender<Arc<Vec<u8>>>) -> Result<(), String> {
+        self.dispatcher.subscribe("", PackFilter::Entire(packet_tx));
+        Ok(())
+    }
+
+    /// global and per-module counters in prometheus' text exposition format
+    pub fn metrics_text(&self) -> String {
+        let mut text = String::new();
+
+        let globals = [
+            ("rustneedle_frames_received_total", "frames read off the interface", self.dispatcher.frames_received()),
+            ("rustneedle_capture_drops_total", "frames the capture source dropped before they were read", self.capture_stats().dropped),
+            ("rustneedle_bytes_received_total", "bytes of frames read off the interface", self.dispatcher.bytes_received()),
+            ("rustneedle_frames_sent_total", "frames written to the interface", self.send_stats.frames_sent()),
+            ("rustneedle_bytes_sent_total", "bytes of frames written to the interface", self.send_stats.bytes_sent()),
+            ("rustneedle_send_queue_drops_total", "queued frames that failed to send", self.send_stats.send_drops()),
+            ("rustneedle_frames_suppressed_total", "queued frames a dry run logged instead of sending", self.send_stats.suppressed()),
+            ("rustneedle_frames_deduplicated_total", "queued frames dropped as repeats within the dedup window", self.send_stats.deduplicated()),
+            ("rustneedle_frames_expired_total", "queued frames dropped because their expiry passed", self.send_stats.expired())
+        ];
+
+        for &(metric, help, value) in globals.iter() {
+            text.push_str(&format!("# HELP {} {}\n# TYPE {} counter\n{} {}\n", metric, help, metric, metric, value));
+        }
+
+        let mut modules: Vec<(&String, &Module)> = self.modules.iter().collect();
+        modules.sort_by(|a, b| a.0.cmp(b.0));
+
+        text.push_str("# HELP rustneedle_packets_total frames delivered to each module\n");
+        text.push_str("# TYPE rustneedle_packets_total counter\n");
+
+        for (name, module) in modules {
+            text.push_str(&format!("rustneedle_packets_total{{module=\"{}\"}} {}\n", escape_label(name), module.packets()));
+        }
+
+        text
+    }
+
+    /// the Normal priority queue
+    pub fn get_packet_queue(&self) -> Option<Sender<Vec<u8>>> {
+        self.get_packet_queue_at(SendPriority::Normal)
+    }
+
+    /// a queue whose frames go out ahead of everything queued at a lower priority
+    /// None until the sender has been started
+    pub fn get_packet_queue_at(&self, priority: SendPriority) -> Option<Sender<Vec<u8>>> {
+        self.packet_queues.as_ref().map(|queues| queues.plain[priority.level()].clone())
+    }
+
+    /// a queue like get_packet_queue_at whose frames go out on interface and no other
+    /// an error if the sender hasn't been started or interface has no sink, see start_sender_on
+    pub fn get_packet_queue_on(&self, interface: &str, priority: SendPriority) -> Result<Sender<Vec<u8>>, String> {
+        let queues = match self.packet_queues {
+            Some(ref queues) => queues,
+            None => return Err(String::from("the packet sender hasn't been started"))
+        };
+
+        if interface == self.config.interface {
+            return Ok(queues.plain[priority.level()].clone());
+        }
+
+        match queues.on.get(interface) {
+            Some(plain) => Ok(plain[priority.level()].clone()),
+            None => Err(format!("{}: no sender open on this interface", interface))
+        }
+    }
+
+    /// takes frames tagged with their own priority and, optionally, an expiry past which the
+    /// consumer drops rather than sends them; None until the sender has been started
+    pub fn get_send_queue(&self) -> Option<Sender<QueuedFrame>> {
+        self.packet_queues.as_ref().map(|queues| queues.tagged.clone())
+    }
+}
+/// splits a command line into words the way a simple shell would, honoring quotes and backslashes
+pub fn split_command(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('\''), c) => word.push(c),
+            (_, '\\') => match chars.next() {
+                Some(escaped) => {
+                    word.push(escaped);
+                    in_word = true;
+                },
+                None => return Err(String::from("trailing backslash"))
+            },
+            (Some(_), c) => word.push(c),
+            (None, '\'') | (None, '"') => {
+                quote = Some(c);
+                in_word = true;
+            },
+            (None, c) if c.is_whitespace() => if in_word {
+                words.push(word.clone());
+                word.clear();
+                in_word = false;
+            },
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+
+    if let Some(q) = quote {
+        return Err(format!("unterminated {} quote", q));
+    }
+
+    if in_word {
+        words.push(word);
+    }
+
+    if words.is_empty() {
+        Err(String::from("empty command"))
+    } else {
+        Ok(words)
+    }
+}
+
+/// escapes a prometheus label value
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+    use std::sync::mpsc::channel;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::{
+        BROADCAST,
+        Framework,
+        HostMgr,
+        Hook,
+        HookEnv,
+        HookResult,
+        KnownPair,
+        Module,
+        ModuleOutcome,
+        ScanControl
+    };
+
+    fn framework() -> Framework {
+        let gateway = KnownPair::new(Ipv4Addr::new(10, 0, 0, 1), BROADCAST);
+        let me = KnownPair::new(Ipv4Addr::new(10, 0, 0, 2), BROADCAST);
+        let mut framework = Framework::new(HostMgr::new(gateway, me));
+
+        let _ = framework.set_logger(Box::new(|_: &str| ()));
+        framework
+    }
+
+    /// a module that exits on its own straight away, dropping its killer's receiver as it goes
+    fn exits_at_once(result: Result<ModuleOutcome, String>) -> Module {
+        let (killer, killed) = channel::<()>();
+
+        let module = Module::new(thread::spawn(move || {
+            drop(killed);
+            result
+        }), killer);
+
+        while !module.is_finished() {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        module
+    }
+
+    #[test]
+    fn killing_an_exited_module_returns_its_result() {
+        let mut framework = framework();
+
+        let mut outcome = ModuleOutcome::new();
+        outcome.insert(String::from("hosts"), String::from("3"));
+
+        assert!(exits_at_once(Ok(outcome.clone())).kill().is_err());
+
+        framework.insert_module("done", exits_at_once(Ok(outcome.clone())));
+        assert_eq!(framework.try_kill("done"), Ok(()));
+
+        framework.insert_module("done", exits_at_once(Ok(outcome.clone())));
+        assert_eq!(framework.stop_module("done"), Ok(outcome));
+
+        framework.insert_module("broken", exits_at_once(Err(String::from("no route"))));
+        assert_eq!(framework.try_kill("broken"), Err(String::from("broken: no route")));
+        assert_eq!(framework.module_count(), 0);
+    }
+
+    #[test]
+    fn a_killed_module_is_only_reported_stopped_once_it_exits() {
+        let mut framework = framework();
+        let (stopped_tx, stopped) = channel();
+        let (killer, killed) = channel();
+
+        framework.on_module_stopped(Box::new(move |name: &str| stopped_tx.send(String::from(name)).unwrap()));
+        framework.insert_module("slow", Module::new(thread::spawn(move || {
+            let _ = killed.recv();
+            thread::sleep(Duration::from_millis(50));
+            Ok(ModuleOutcome::new())
+        }), killer));
+
+        assert_eq!(framework.try_kill("slow"), Ok(()));
+        assert!(stopped.try_recv().is_err());
+        assert_eq!(stopped.recv_timeout(Duration::from_secs(5)), Ok(String::from("slow")));
+    }
+
+    #[test]
+    fn a_module_ignoring_its_killer_doesnt_hold_up_the_other_matches() {
+        let mut framework = framework();
+        let (killer, killed) = channel::<()>();
+
+        framework.insert_module("scan-stuck", Module::new(thread::spawn(move || {
+            let _killed = killed;
+            thread::sleep(Duration::from_secs(3));
+            Ok(ModuleOutcome::new())
+        }), killer));
+
+        framework.insert_module("scan-done", exits_at_once(Ok(ModuleOutcome::new())));
+        framework.insert_module("scan-broken", exits_at_once(Err(String::from("no route"))));
+        framework.insert_module("sniff", exits_at_once(Ok(ModuleOutcome::new())));
+
+        let killed = framework.kill_modules_matching("scan-");
+
+        assert_eq!(killed, vec![String::from("scan-broken"), String::from("scan-done")]);
+        assert!(framework.contains_module("sniff"));
+        assert!(!framework.contains_module("scan-stuck"));
+    }
+
+    fn starts_nothing(_: &[&str], _: &HookEnv) -> HookResult {
+        Ok(None)
+    }
+
+    fn starts_one(_: &[&str], _: &HookEnv) -> HookResult {
+        Ok(Some(exits_at_once(Ok(ModuleOutcome::new()))))
+    }
+
+    #[test]
+    fn the_module_limit_only_refuses_hooks_that_start_a_module() {
+        let mut framework = framework();
+        let (killer, killed) = channel::<()>();
+
+        framework.hook_up("nothing", Hook::Standalone(starts_nothing)).unwrap();
+        framework.hook_up("one", Hook::Standalone(starts_one)).unwrap();
+        framework.set_max_modules(1);
+        framework.insert_module("running", Module::new(thread::spawn(move || {
+            let _ = killed.recv();
+            Ok(ModuleOutcome::new())
+        }), killer));
+
+        assert_eq!(framework.try_run_hook("nothing", &[]), Ok(false));
+        assert_eq!(framework.try_run_hook("one", &[]), Err(String::from("one: module limit of 1 reached")));
+        assert_eq!(framework.module_count(), 1);
+    }
+
+    fn waits_to_be_killed(_: &[&str], _: &HookEnv) -> HookResult {
+        Ok(Some(Module::deferred(|killed| {
+            let _ = killed.recv();
+            Ok(ModuleOutcome::new())
+        })))
+    }
+
+    #[test]
+    fn deferred_modules_of_pooled_hooks_run_on_the_pool() {
+        let mut framework = framework();
+
+        framework.hook_up("monitor", Hook::Standalone(waits_to_be_killed)).unwrap();
+        assert_eq!(framework.try_run_hook("monitor", &[]), Ok(true));
+        assert!(!framework.modules()["monitor"].is_pooled());
+
+        assert_eq!(framework.pool_hook("nonesuch"), Err(String::from("nonesuch: No such hook")));
+        assert_eq!(framework.pool_hook("monitor"), Ok(()));
+        assert_eq!(framework.try_run_hook("monitor", &[]), Ok(true));
+        assert!(framework.modules()["monitor_1"].is_pooled());
+
+        assert_eq!(framework.stop_module("monitor"), Ok(ModuleOutcome::new()));
+        assert_eq!(framework.stop_module("monitor_1"), Ok(ModuleOutcome::new()));
+    }
+
+    #[test]
+    fn module_names_resolve_the_same_way_everywhere() {
+        let mut framework = framework();
+
+        framework.insert_module("arpspoof", exits_at_once(Ok(ModuleOutcome::new())));
+        framework.insert_module("arpscan", exits_at_once(Ok(ModuleOutcome::new())));
+
+        assert_eq!(framework.pause_module("arpsp"), Err(String::from("arpspoof: module doesn't take packets")));
+        assert_eq!(framework.steer_scan("arps", ScanControl::Abort), Err(String::from("arps: ambiguous, could be arpscan, arpspoof")));
+        assert_eq!(framework.stop_module_within("arpsc", Duration::from_secs(1)), Ok(ModuleOutcome::new()));
+        assert!(framework.contains_module("arpspoof"));
+    }
+}
@@ -0,0 +1,37 @@
+use std::sync::{
+    Arc,
+    Mutex
+};
+
+/// takes one log line at a time, see Framework::set_logger
+pub type LogSink = Box<dyn Fn(&str) + Send>;
+
+/// where the Framework and the threads it starts send their log lines, stdout unless replaced
+#[derive(Clone)]
+pub struct Logger {
+    sink: Arc<Mutex<LogSink>>
+}
+
+impl Logger {
+    pub fn new() -> Logger {
+        Logger {
+            sink: Arc::new(Mutex::new(Box::new(|line: &str| println!("{}", line))))
+        }
+    }
+
+    pub fn log(&self, line: &str) {
+        (self.sink.lock().unwrap())(line)
+    }
+
+    /// swaps the sink out from under every clone of this logger, returning the old one
+    pub(crate) fn replace(&self, sink: LogSink) -> LogSink {
+        let mut current = self.sink.lock().unwrap();
+        ::std::mem::replace(&mut *current, sink)
+    }
+}
+
+impl Default for Logger {
+    fn default() -> Logger {
+        Logger::new()
+    }
+}
@@ -0,0 +1,293 @@
+/*
+Lookups that ask the operating system what it already knows about the network, so the operator
+doesn't have to type it in, or so what we learned can be checked against it. Each one returns
+nothing rather than an error when the platform gives no answer; the caller falls back to asking the
+operator.
+
+Also the interface settings pnet has no knob for. Those do fail with an error, since carrying on
+without them would quietly capture less than was asked for.
+*/
+
+use std::io;
+use std::net::Ipv4Addr;
+#[cfg(not(target_os = "linux"))]
+use std::process::Command;
+#[cfg(target_os = "linux")]
+use std::fs;
+
+use pnet::datalink::MacAddr;
+
+use LinkType;
+
+/// next hop of the default route, None if there is no default route or it can't be read
+#[cfg(target_os = "linux")]
+pub fn detect_default_gateway() -> Option<Ipv4Addr> {
+    let table = fs::read_to_string("/proc/net/route").ok()?;
+    parse_proc_route(&table)
+}
+
+#[cfg(any(target_os = "macos", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd"))]
+pub fn detect_default_gateway() -> Option<Ipv4Addr> {
+    // `route -n get default` answers with a "gateway: a.b.c.d" line
+    let output = Command::new("route").args(&["-n", "get", "default"]).output().ok()?;
+
+    String::from_utf8_lossy(&output.stdout).lines()
+        .filter_map(|line| line.trim().strip_prefix("gateway:"))
+        .filter_map(|gateway| gateway.trim().parse().ok())
+        .next()
+}
+
+#[cfg(windows)]
+pub fn detect_default_gateway() -> Option<Ipv4Addr> {
+    // the default route's row starts `0.0.0.0  0.0.0.0  <gateway>`
+    let output = Command::new("route").args(&["print", "-4", "0.0.0.0"]).output().ok()?;
+
+    String::from_utf8_lossy(&output.stdout).lines()
+        .map(|line| line.split_whitespace().collect::<Vec<&str>>())
+        .filter(|fields| fields.len() >= 3 && fields[0] == "0.0.0.0" && fields[1] == "0.0.0.0")
+        .filter_map(|fields| fields[2].parse().ok())
+        .next()
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    windows
+)))]
+pub fn detect_default_gateway() -> Option<Ipv4Addr> {
+    None
+}
+
+/// picks the default route out of /proc/net/route
+#[cfg(target_os = "linux")]
+fn parse_proc_route(table: &str) -> Option<Ipv4Addr> {
+    const RTF_UP: u32 = 0x1;
+    const RTF_GATEWAY: u32 = 0x2;
+
+    // the first line is column headings, and addresses are the kernel's raw network order words printed in hex
+    for line in table.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+
+        if fields.len() < 4 {
+            continue;
+        }
+
+        let destination = u32::from_str_radix(fields[1], 16);
+        let gateway = u32::from_str_radix(fields[2], 16);
+        let flags = u32::from_str_radix(fields[3], 16);
+
+        if let (Ok(0), Ok(gateway), Ok(flags)) = (destination, gateway, flags) {
+            if flags & RTF_UP != 0 && flags & RTF_GATEWAY != 0 {
+                return Some(Ipv4Addr::from(u32::from_be(gateway)));
+            }
+        }
+    }
+
+    None
+}
+
+/// every complete IPv4 entry of the OS's ARP cache, to check the host table or a spoof against
+/// empty if the cache can't be read, or on platforms without a way to read it
+#[cfg(target_os = "linux")]
+pub fn os_arp_table() -> Vec<(Ipv4Addr, MacAddr)> {
+    match fs::read_to_string("/proc/net/arp") {
+        Ok(table) => parse_proc_arp(&table),
+        Err(_) => Vec::new()
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd"))]
+pub fn os_arp_table() -> Vec<(Ipv4Addr, MacAddr)> {
+    // `arp -an` answers `? (10.0.0.1) at 0:1b:63:a:b:c on en0 ...`, with `(incomplete)` for the mac until it is learned
+    let output = match Command::new("arp").arg("-an").output() {
+        Ok(output) => output,
+        Err(_) => return Vec::new()
+    };
+
+    String::from_utf8_lossy(&output.stdout).lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let ip = fields.get(1)?.trim_start_matches('(').trim_end_matches(')').parse().ok()?;
+
+            match fields.get(2) {
+                Some(&"at") => Some((ip, parse_mac(fields.get(3)?)?)),
+                _ => None
+            }
+        })
+        .collect()
+}
+
+#[cfg(windows)]
+pub fn os_arp_table() -> Vec<(Ipv4Addr, MacAddr)> {
+    // `arp -a` answers a table per interface with rows of `10.0.0.1  aa-bb-cc-dd-ee-ff  dynamic`
+    let output = match Command::new("arp").arg("-a").output() {
+        Ok(output) => output,
+        Err(_) => return Vec::new()
+    };
+
+    String::from_utf8_lossy(&output.stdout).lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            Some((fields.first()?.parse().ok()?, parse_mac(fields.get(1)?)?))
+        })
+        .collect()
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    windows
+)))]
+pub fn os_arp_table() -> Vec<(Ipv4Addr, MacAddr)> {
+    Vec::new()
+}
+
+/// the complete entries of /proc/net/arp
+#[cfg(target_os = "linux")]
+fn parse_proc_arp(table: &str) -> Vec<(Ipv4Addr, MacAddr)> {
+    const ATF_COM: u32 = 0x2;
+
+    // after the headings: address, hardware type, flags, mac, mask, device
+    table.lines().skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+
+            if fields.len() < 4 {
+                return None;
+            }
+
+            let flags = u32::from_str_radix(fields[2].trim_start_matches("0x"), 16).ok()?;
+
+            if flags & ATF_COM == 0 {
+                return None;
+            }
+
+            Some((fields[0].parse().ok()?, parse_mac(fields[3])?))
+        })
+        .collect()
+}
+
+/// a mac written with colons or dashes, allowing the single digit octets BSD's arp prints
+/// the all zero mac of an entry still being resolved is taken as no mac at all
+#[cfg(any(
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    windows
+))]
+fn parse_mac(text: &str) -> Option<MacAddr> {
+    let mut octets = [0u8; 6];
+    let mut parts = text.split(&[':', '-'][..]);
+
+    for octet in octets.iter_mut() {
+        let part = parts.next()?;
+
+        if part.is_empty() || part.len() > 2 {
+            return None;
+        }
+
+        *octet = u8::from_str_radix(part, 16).ok()?;
+    }
+
+    if parts.next().is_some() || octets == [0; 6] {
+        return None;
+    }
+
+    Some(MacAddr(octets[0], octets[1], octets[2], octets[3], octets[4], octets[5]))
+}
+
+/// what the frames interface captures start with, from its ARPHRD_ type
+#[cfg(target_os = "linux")]
+pub(crate) fn link_type(interface: &str) -> io::Result<LinkType> {
+    const ARPHRD_ETHER: u32 = 1;
+    const ARPHRD_IEEE80211: u32 = 801;
+    const ARPHRD_IEEE80211_RADIOTAP: u32 = 803;
+
+    let kind = fs::read_to_string(format!("/sys/class/net/{}/type", interface))?;
+
+    match kind.trim().parse::<u32>() {
+        Ok(ARPHRD_ETHER) => Ok(LinkType::Ethernet),
+        Ok(ARPHRD_IEEE80211) => Ok(LinkType::Ieee80211),
+        Ok(ARPHRD_IEEE80211_RADIOTAP) => Ok(LinkType::Radiotap),
+        Ok(kind) => Ok(LinkType::Other(kind)),
+        Err(_) => Err(io::Error::new(io::ErrorKind::InvalidData, format!("{}: unreadable interface type", interface)))
+    }
+}
+
+/// pnet opens BPF devices and winpcap adapters with their default link type, which is ethernet even
+/// for wireless interfaces, and has no way to ask for another
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn link_type(_interface: &str) -> io::Result<LinkType> {
+    Ok(LinkType::Ethernet)
+}
+
+/// frames interface dropped on receive since it came up, as the kernel counts them, None if the
+/// counter can't be read
+#[cfg(target_os = "linux")]
+pub(crate) fn rx_dropped(interface: &str) -> Option<u64> {
+    let count = fs::read_to_string(format!("/sys/class/net/{}/statistics/rx_dropped", interface)).ok()?;
+    count.trim().parse().ok()
+}
+
+/// no other platform has a per-interface drop counter pnet's channels could be checked against
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn rx_dropped(_interface: &str) -> Option<u64> {
+    None
+}
+
+/// makes sure interface is capturing frames addressed to anyone, called once its channel is open
+/// pnet's linux and windows backends already ask for this when opening a channel, so there it is only
+/// checked; its BPF backend never does, so there the interface is switched over with ifconfig
+#[cfg(target_os = "linux")]
+pub(crate) fn enable_promiscuous(interface: &str) -> io::Result<()> {
+    const IFF_PROMISC: u32 = 0x100;
+
+    let flags = fs::read_to_string(format!("/sys/class/net/{}/flags", interface))?;
+    let flags = u32::from_str_radix(flags.trim().trim_start_matches("0x"), 16)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("{}: unreadable interface flags", interface)))?;
+
+    if flags & IFF_PROMISC == 0 {
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, format!("{}: couldn't be put into promiscuous mode", interface)));
+    }
+
+    Ok(())
+}
+
+#[cfg(any(target_os = "macos", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd"))]
+pub(crate) fn enable_promiscuous(interface: &str) -> io::Result<()> {
+    let output = Command::new("ifconfig").args(&[interface, "promisc"]).output()?;
+
+    if !output.status.success() {
+        // most often not being root
+        let reason = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, format!("{}: couldn't be put into promiscuous mode: {}", interface, reason.trim())));
+    }
+
+    Ok(())
+}
+
+#[cfg(windows)]
+pub(crate) fn enable_promiscuous(_interface: &str) -> io::Result<()> {
+    // opening the adapter already failed if winpcap couldn't set the promiscuous filter
+    Ok(())
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd",
+    windows
+)))]
+pub(crate) fn enable_promiscuous(interface: &str) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Other, format!("{}: promiscuous mode isn't supported on this platform", interface)))
+}
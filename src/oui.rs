@@ -0,0 +1,142 @@
+/*
+Vendors by the first three bytes of a mac, the OUI the IEEE assigned to whoever made the interface.
+The table built in only knows a handful of vendors common on the networks this gets pointed at,
+virtual machines and the like. Anything fuller has to come from a file, the IEEE's own oui.txt or a
+list in the same spirit: one vendor a line, its OUI first as six hex digits, separated by `-`, `:`
+or nothing, and the vendor's name after it, with the `(hex)` the IEEE puts between the two
+skipped. Every other line, the IEEE's `(base 16)` ones included, is ignored, so the file can be
+used as downloaded. Entries from a file take the place of built in ones for the same OUI.
+
+A locally administered mac was picked by software rather than assigned, so its first three bytes
+name nobody and lookup gives None for it whatever the table holds.
+*/
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use pnet::datalink::MacAddr;
+
+const BUILTIN: &[([u8; 3], &str)] = &[
+    ([0x00, 0x00, 0x0c], "Cisco"),
+    ([0x00, 0x00, 0x5e], "IANA"),
+    ([0x00, 0x03, 0x93], "Apple"),
+    ([0x00, 0x05, 0x69], "VMware"),
+    ([0x00, 0x0c, 0x29], "VMware"),
+    ([0x00, 0x15, 0x5d], "Microsoft"),
+    ([0x00, 0x16, 0x3e], "Xensource"),
+    ([0x00, 0x17, 0x88], "Philips Lighting"),
+    ([0x00, 0x1b, 0x63], "Apple"),
+    ([0x00, 0x1c, 0x42], "Parallels"),
+    ([0x00, 0x50, 0x56], "VMware"),
+    ([0x08, 0x00, 0x27], "VirtualBox"),
+    ([0x18, 0xb4, 0x30], "Nest Labs"),
+    ([0xb8, 0x27, 0xeb], "Raspberry Pi"),
+    ([0xdc, 0xa6, 0x32], "Raspberry Pi")
+];
+
+/// vendor names by OUI, see the header above
+pub struct OuiTable {
+    vendors: HashMap<[u8; 3], String>
+}
+
+impl OuiTable {
+    /// the vendors built in and nothing more
+    pub fn builtin() -> OuiTable {
+        OuiTable {
+            vendors: BUILTIN.iter().map(|&(oui, vendor)| (oui, String::from(vendor))).collect()
+        }
+    }
+
+    /// the built in vendors with those listed in the file at path on top,
+    /// only the built in ones if there is no such file
+    pub fn load(path: &Path) -> Result<OuiTable, String> {
+        let mut table = OuiTable::builtin();
+
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok(table),
+            Err(err) => return Err(format!("{}: {}", path.display(), err))
+        };
+
+        table.vendors.extend(text.lines().filter_map(parse_line));
+        Ok(table)
+    }
+
+    pub fn lookup(&self, mac: MacAddr) -> Option<&str> {
+        // the locally administered bit
+        if mac.0 & 0x02 != 0 {
+            return None;
+        }
+
+        self.vendors.get(&[mac.0, mac.1, mac.2]).map(|vendor| vendor.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.vendors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vendors.is_empty()
+    }
+}
+
+/// the OUI and vendor on one line of an oui.txt, None for a line that isn't one
+fn parse_line(line: &str) -> Option<([u8; 3], String)> {
+    let line = line.trim();
+    let split = line.find(char::is_whitespace)?;
+    let (oui, vendor) = line.split_at(split);
+
+    let digits: String = oui.chars().filter(|&c| c != '-' && c != ':').collect();
+
+    if digits.len() != 6 {
+        return None;
+    }
+
+    let mut bytes = [0u8; 3];
+
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(digits.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+
+    let vendor = vendor.trim();
+    let vendor = vendor.strip_prefix("(hex)").unwrap_or(vendor).trim();
+
+    if vendor.is_empty() || vendor.starts_with("(base 16)") {
+        return None;
+    }
+
+    Some((bytes, String::from(vendor)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ieee_lines_parse_and_the_rest_are_skipped() {
+        assert_eq!(parse_line("00-1B-63   (hex)\t\tApple, Inc."), Some(([0x00, 0x1b, 0x63], String::from("Apple, Inc."))));
+        assert_eq!(parse_line("001B63     (base 16)\t\tApple, Inc."), None);
+        assert_eq!(parse_line("08:00:27 Oracle"), Some(([0x08, 0x00, 0x27], String::from("Oracle"))));
+        assert_eq!(parse_line("\t\t\t\tCupertino  CA  95014"), None);
+        assert_eq!(parse_line("OUI/MA-L                                                    Organization"), None);
+        assert_eq!(parse_line("00-1B-63"), None);
+    }
+
+    #[test]
+    fn locally_administered_macs_have_no_vendor() {
+        let table = OuiTable::builtin();
+
+        assert_eq!(table.lookup(MacAddr(0x52, 0x54, 0x00, 0x12, 0x34, 0x56)), None);
+        assert_eq!(table.lookup(MacAddr(0x08, 0x00, 0x27, 0x12, 0x34, 0x56)), Some("VirtualBox"));
+        assert_eq!(table.lookup(MacAddr(0x00, 0x11, 0x22, 0x33, 0x44, 0x55)), None);
+    }
+
+    #[test]
+    fn a_missing_file_leaves_the_builtin_table() {
+        let table = OuiTable::load(Path::new("/nonexistent/oui.txt")).unwrap();
+
+        assert_eq!(table.len(), OuiTable::builtin().len());
+    }
+}
@@ -0,0 +1,153 @@
+use std::net::Ipv4Addr;
+
+use pnet::datalink::MacAddr;
+
+use dispatch::ETHER_HEADER_LEN;
+use super::ethernet;
+
+pub const OPERATION_REQUEST: u16 = 1;
+pub const OPERATION_REPLY: u16 = 2;
+
+pub const HARDWARE_ETHERNET: u16 = 1;
+
+const BROADCAST: MacAddr = MacAddr(0xff, 0xff, 0xff, 0xff, 0xff, 0xff);
+
+/// length of an ARP packet for IPv4 over ethernet, padding not included
+pub const PACKET_LEN: usize = 28;
+
+/// an ARP packet for IPv4 over ethernet, the only kind parse accepts
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArpPacket {
+    pub operation: u16,
+    pub sender_mac: MacAddr,
+    pub sender_ip: Ipv4Addr,
+    pub target_mac: MacAddr,
+    pub target_ip: Ipv4Addr
+}
+
+/// an ethernet frame broadcasting who has target_ip, tell sender_ip
+pub fn build_request(sender_mac: MacAddr, sender_ip: Ipv4Addr, target_ip: Ipv4Addr) -> Vec<u8> {
+    let packet = build(&ArpPacket {
+        operation: OPERATION_REQUEST,
+        sender_mac,
+        sender_ip,
+        target_mac: MacAddr(0, 0, 0, 0, 0, 0),
+        target_ip
+    });
+
+    ethernet::build_frame(BROADCAST, sender_mac, ethernet::ETHERTYPE_ARP, &packet)
+}
+
+/// the ARP packet alone, without an ethernet header
+pub fn build(packet: &ArpPacket) -> [u8; PACKET_LEN] {
+    let mut bytes = [0u8; PACKET_LEN];
+    let (sender_mac, target_mac) = (&packet.sender_mac, &packet.target_mac);
+
+    bytes[..8].copy_from_slice(&[
+        (HARDWARE_ETHERNET >> 8) as u8, HARDWARE_ETHERNET as u8,
+        (ethernet::ETHERTYPE_IPV4 >> 8) as u8, ethernet::ETHERTYPE_IPV4 as u8,
+        6, 4,
+        (packet.operation >> 8) as u8, packet.operation as u8
+    ]);
+    bytes[8..14].copy_from_slice(&[sender_mac.0, sender_mac.1, sender_mac.2, sender_mac.3, sender_mac.4, sender_mac.5]);
+    bytes[14..18].copy_from_slice(&packet.sender_ip.octets());
+    bytes[18..24].copy_from_slice(&[target_mac.0, target_mac.1, target_mac.2, target_mac.3, target_mac.4, target_mac.5]);
+    bytes[24..28].copy_from_slice(&packet.target_ip.octets());
+
+    bytes
+}
+
+/// reads the ARP packet in an ethernet frame, None if the frame isn't ARP for IPv4 over ethernet
+/// or is too short to hold one
+pub fn parse(frame: &[u8]) -> Option<ArpPacket> {
+    if ethernet::ethertype(frame)? != ethernet::ETHERTYPE_ARP {
+        return None;
+    }
+
+    let packet = frame.get(ETHER_HEADER_LEN..ETHER_HEADER_LEN + PACKET_LEN)?;
+    let hardware = u16::from(packet[0]) << 8 | u16::from(packet[1]);
+    let protocol = u16::from(packet[2]) << 8 | u16::from(packet[3]);
+
+    if hardware != HARDWARE_ETHERNET || protocol != ethernet::ETHERTYPE_IPV4 || packet[4] != 6 || packet[5] != 4 {
+        return None;
+    }
+
+    Some(ArpPacket {
+        operation: u16::from(packet[6]) << 8 | u16::from(packet[7]),
+        sender_mac: read_mac(&packet[8..14]),
+        sender_ip: Ipv4Addr::new(packet[14], packet[15], packet[16], packet[17]),
+        target_mac: read_mac(&packet[18..24]),
+        target_ip: Ipv4Addr::new(packet[24], packet[25], packet[26], packet[27])
+    })
+}
+
+/// the address and mac a reply says belong together, None for anything but a well formed reply
+pub fn parse_reply(frame: &[u8]) -> Option<(Ipv4Addr, MacAddr)> {
+    match parse(frame) {
+        Some(ref packet) if packet.operation == OPERATION_REPLY => Some((packet.sender_ip, packet.sender_mac)),
+        _ => None
+    }
+}
+
+fn read_mac(bytes: &[u8]) -> MacAddr {
+    MacAddr(bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use pnet::datalink::MacAddr;
+
+    use super::{
+        ArpPacket,
+        OPERATION_REPLY,
+        OPERATION_REQUEST,
+        build,
+        parse,
+        parse_reply
+    };
+    use super::super::ethernet;
+
+    fn frame(operation: u16) -> Vec<u8> {
+        let packet = build(&ArpPacket {
+            operation,
+            sender_mac: MacAddr(2, 0, 0, 0, 0, 1),
+            sender_ip: Ipv4Addr::new(10, 0, 0, 1),
+            target_mac: MacAddr(2, 0, 0, 0, 0, 2),
+            target_ip: Ipv4Addr::new(10, 0, 0, 2)
+        });
+
+        ethernet::build_frame(MacAddr(2, 0, 0, 0, 0, 2), MacAddr(2, 0, 0, 0, 0, 1), ethernet::ETHERTYPE_ARP, &packet)
+    }
+
+    #[test]
+    fn whole_replies_parse() {
+        assert_eq!(parse_reply(&frame(OPERATION_REPLY)), Some((Ipv4Addr::new(10, 0, 0, 1), MacAddr(2, 0, 0, 0, 0, 1))));
+    }
+
+    #[test]
+    fn truncated_frames_are_none() {
+        let reply = frame(OPERATION_REPLY);
+
+        for len in 0..reply.len() {
+            assert_eq!(parse(&reply[..len]), None, "{} bytes", len);
+            assert_eq!(parse_reply(&reply[..len]), None, "{} bytes", len);
+        }
+    }
+
+    #[test]
+    fn requests_and_other_kinds_are_none() {
+        let request = frame(OPERATION_REQUEST);
+        assert!(parse(&request).is_some());
+        assert_eq!(parse_reply(&request), None);
+
+        let mut not_ethernet = frame(OPERATION_REPLY);
+        not_ethernet[15] = 6; // token ring
+        assert_eq!(parse(&not_ethernet), None);
+
+        let mut not_arp = frame(OPERATION_REPLY);
+        not_arp[13] = 0x00; // 0x0800, IPv4
+        assert_eq!(parse(&not_arp), None);
+    }
+}
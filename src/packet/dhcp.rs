@@ -0,0 +1,82 @@
+use std::net::Ipv4Addr;
+
+use pnet::datalink::MacAddr;
+
+pub const SERVER_PORT: u16 = 67;
+pub const CLIENT_PORT: u16 = 68;
+
+// message types carried in option 53
+pub const DISCOVER: u8 = 1;
+pub const OFFER: u8 = 2;
+pub const REQUEST: u8 = 3;
+pub const ACK: u8 = 5;
+
+const OPTIONS_OFFSET: usize = 240;
+const MAGIC_COOKIE: [u8; 4] = [99, 130, 83, 99];
+
+const OPTION_PAD: u8 = 0;
+const OPTION_HOSTNAME: u8 = 12;
+const OPTION_REQUESTED_IP: u8 = 50;
+const OPTION_MESSAGE_TYPE: u8 = 53;
+const OPTION_END: u8 = 255;
+
+/// the parts of a DHCP message that say who a client is
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DhcpMessage {
+    pub message_type: Option<u8>,
+    pub client_mac: MacAddr,
+    pub client_ip: Ipv4Addr, // ciaddr, set by clients that already hold a lease
+    pub your_ip: Ipv4Addr, // yiaddr, the address a server is handing out
+    pub requested_ip: Option<Ipv4Addr>,
+    pub hostname: Option<String>
+}
+
+/// reads a DHCP message out of a UDP payload
+/// options are read up to the first one that runs past the end of the message and kept as far as they got
+pub fn parse(message: &[u8]) -> Option<DhcpMessage> {
+    let fixed = message.get(..OPTIONS_OFFSET)?;
+
+    // only ethernet hardware addresses are of any use to us
+    if fixed[1] != 1 || fixed[2] != 6 || fixed[236..240] != MAGIC_COOKIE {
+        return None;
+    }
+
+    let mut parsed = DhcpMessage {
+        message_type: None,
+        client_mac: MacAddr(fixed[28], fixed[29], fixed[30], fixed[31], fixed[32], fixed[33]),
+        client_ip: Ipv4Addr::new(fixed[12], fixed[13], fixed[14], fixed[15]),
+        your_ip: Ipv4Addr::new(fixed[16], fixed[17], fixed[18], fixed[19]),
+        requested_ip: None,
+        hostname: None
+    };
+
+    let mut options = &message[fixed.len()..];
+
+    while let Some((&code, rest)) = options.split_first() {
+        match code {
+            OPTION_PAD => {
+                options = rest;
+                continue;
+            },
+
+            OPTION_END => break,
+            _ => ()
+        }
+
+        let (value, rest) = match rest.split_first() {
+            Some((&len, rest)) if usize::from(len) <= rest.len() => rest.split_at(usize::from(len)),
+            _ => break // truncated
+        };
+
+        match code {
+            OPTION_MESSAGE_TYPE if value.len() == 1 => parsed.message_type = Some(value[0]),
+            OPTION_REQUESTED_IP if value.len() == 4 => parsed.requested_ip = Some(Ipv4Addr::new(value[0], value[1], value[2], value[3])),
+            OPTION_HOSTNAME if !value.is_empty() => parsed.hostname = Some(String::from_utf8_lossy(value).into_owned()),
+            _ => ()
+        }
+
+        options = rest;
+    }
+
+    Some(parsed)
+}
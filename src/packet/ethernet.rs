@@ -0,0 +1,44 @@
+use pnet::datalink::MacAddr;
+
+use dispatch::ETHER_HEADER_LEN;
+
+pub const ETHERTYPE_IPV4: u16 = 0x0800;
+pub const ETHERTYPE_ARP: u16 = 0x0806;
+pub const ETHERTYPE_IPV6: u16 = 0x86dd;
+pub const ETHERTYPE_VLAN: u16 = 0x8100; // 802.1Q tag
+pub const ETHERTYPE_QINQ: u16 = 0x88a8; // 802.1ad service tag, an 802.1Q tag usually follows
+
+/// bytes a VLAN tag adds after the source mac, its ethertype and tag control
+pub const VLAN_TAG_LEN: usize = 4;
+
+/// ethernet header followed by payload, ready for the packet queue
+pub fn build_frame(dst: MacAddr, src: MacAddr, ethertype: u16, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(ETHER_HEADER_LEN + payload.len());
+
+    frame.extend_from_slice(&[dst.0, dst.1, dst.2, dst.3, dst.4, dst.5]);
+    frame.extend_from_slice(&[src.0, src.1, src.2, src.3, src.4, src.5]);
+    frame.extend_from_slice(&[(ethertype >> 8) as u8, ethertype as u8]);
+    frame.extend_from_slice(payload);
+
+    frame
+}
+
+/// the ethertype in bytes 12 and 13, None if the frame is too short to have one
+pub fn ethertype(frame: &[u8]) -> Option<u16> {
+    frame.get(12..ETHER_HEADER_LEN).map(|field| u16::from(field[0]) << 8 | u16::from(field[1]))
+}
+
+/// the ethertype of what the frame carries, past any VLAN tags, None if the frame is cut short
+pub fn inner_ethertype(frame: &[u8]) -> Option<u16> {
+    let mut offset = 12;
+
+    loop {
+        let field = frame.get(offset..offset + 2)?;
+        let ethertype = u16::from(field[0]) << 8 | u16::from(field[1]);
+
+        match ethertype {
+            ETHERTYPE_VLAN | ETHERTYPE_QINQ => offset += VLAN_TAG_LEN,
+            _ => return Some(ethertype)
+        }
+    }
+}
@@ -0,0 +1,185 @@
+/// most header bytes a request may take before it is given up on as not being HTTP
+pub const MAX_HEADER_LEN: usize = 16 * 1024;
+
+pub const PORT: u16 = 80;
+
+/// a request line, its headers and as much body as Content-Length said to expect
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>, // in the order sent, names as sent
+    pub body: Vec<u8>
+}
+
+impl HttpRequest {
+    /// the first header called name, matched without regard to case
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Parsed {
+    Request(HttpRequest, usize), // a whole request and how many bytes of the stream it took up
+    Incomplete, // could still become a request once more of the stream arrives
+    Invalid // not HTTP, or a request line or header we can't make sense of
+}
+
+/// reads the request at the start of stream, which is a client's side of a connection
+pub fn parse_request(stream: &[u8]) -> Parsed {
+    let header_end = match find(stream, b"\r\n\r\n") {
+        Some(end) => end,
+        None if stream.len() > MAX_HEADER_LEN => return Parsed::Invalid,
+        None => return Parsed::Incomplete
+    };
+
+    let head = match ::std::str::from_utf8(&stream[..header_end]) {
+        Ok(head) => head,
+        Err(_) => return Parsed::Invalid
+    };
+
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or("").split(' ');
+
+    let (method, path, version) = match (request_line.next(), request_line.next(), request_line.next()) {
+        (Some(method), Some(path), Some(version)) => (method, path, version),
+        _ => return Parsed::Invalid
+    };
+
+    if method.is_empty() || !method.bytes().all(|byte| byte.is_ascii_uppercase()) || !version.starts_with("HTTP/") {
+        return Parsed::Invalid;
+    }
+
+    let mut headers = Vec::new();
+
+    for line in lines {
+        match line.find(':') {
+            Some(colon) => headers.push((String::from(&line[..colon]), String::from(line[colon + 1..].trim()))),
+            None => return Parsed::Invalid
+        }
+    }
+
+    let mut request = HttpRequest {
+        method: String::from(method),
+        path: String::from(path),
+        headers,
+        body: Vec::new()
+    };
+
+    // chunked bodies aren't put back together, only the headers of those requests are of use
+    let body_len = match request.header("Content-Length") {
+        Some(len) => match len.parse::<usize>() {
+            Ok(len) => len,
+            Err(_) => return Parsed::Invalid
+        },
+
+        None => 0
+    };
+
+    let body_start = header_end + 4;
+
+    // a Content-Length this large can't be honest, and would overflow working out where the body ends
+    let body_end = match body_start.checked_add(body_len) {
+        Some(end) => end,
+        None => return Parsed::Invalid
+    };
+
+    match stream.get(body_start..body_end) {
+        Some(body) => {
+            request.body = body.to_vec();
+            Parsed::Request(request, body_end)
+        },
+
+        None => Parsed::Incomplete
+    }
+}
+
+/// user and password out of an `Authorization: Basic ...` header value
+pub fn basic_credentials(authorization: &str) -> Option<(String, String)> {
+    let mut parts = authorization.trim().splitn(2, ' ');
+
+    if !parts.next()?.eq_ignore_ascii_case("basic") {
+        return None;
+    }
+
+    let decoded = String::from_utf8(decode_base64(parts.next()?.trim())?).ok()?;
+    let colon = decoded.find(':')?;
+
+    Some((String::from(&decoded[..colon]), String::from(&decoded[colon + 1..])))
+}
+
+/// name and value of every field in an application/x-www-form-urlencoded body
+pub fn form_fields(body: &[u8]) -> Vec<(String, String)> {
+    body.split(|&byte| byte == b'&')
+        .filter(|field| !field.is_empty())
+        .map(|field| match field.iter().position(|&byte| byte == b'=') {
+            Some(equals) => (url_decode(&field[..equals]), url_decode(&field[equals + 1..])),
+            None => (url_decode(field), String::new())
+        })
+        .collect()
+}
+
+fn url_decode(encoded: &[u8]) -> String {
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut bytes = encoded.iter();
+
+    while let Some(&byte) = bytes.next() {
+        match byte {
+            b'+' => decoded.push(b' '),
+
+            b'%' => {
+                let hex = bytes.clone().take(2).cloned().collect::<Vec<u8>>();
+
+                match ::std::str::from_utf8(&hex).ok().and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(value) if hex.len() == 2 => {
+                        decoded.push(value);
+                        bytes.nth(1);
+                    },
+
+                    // a stray percent is kept as it was
+                    _ => decoded.push(b'%')
+                }
+            },
+
+            byte => decoded.push(byte)
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// standard alphabet, padding optional
+fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(encoded.len() * 3 / 4);
+    let mut bits: u32 = 0;
+    let mut held = 0;
+
+    for byte in encoded.trim_end_matches('=').bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None
+        };
+
+        bits = bits << 6 | u32::from(value);
+        held += 6;
+
+        if held >= 8 {
+            held -= 8;
+            decoded.push((bits >> held) as u8);
+        }
+    }
+
+    Some(decoded)
+}
+
+/// where needle first starts in haystack
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
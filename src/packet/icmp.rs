@@ -0,0 +1,59 @@
+use super::checksum;
+
+pub const ECHO_REPLY: u8 = 0;
+pub const DEST_UNREACHABLE: u8 = 3;
+pub const REDIRECT: u8 = 5;
+pub const ECHO_REQUEST: u8 = 8;
+pub const TIME_EXCEEDED: u8 = 11;
+
+/// length of the type, code, checksum and rest-of-header fields
+pub const HEADER_LEN: usize = 8;
+
+/// an ICMP header, id and seq are only meaningful for echo messages
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IcmpMessage {
+    pub kind: u8,
+    pub code: u8,
+    pub id: u16,
+    pub seq: u16
+}
+
+pub fn build_echo_request(id: u16, seq: u16, payload: &[u8]) -> Vec<u8> {
+    build_echo(ECHO_REQUEST, id, seq, payload)
+}
+
+/// a reply to a request should echo its id, seq and payload back unchanged
+pub fn build_echo_reply(id: u16, seq: u16, payload: &[u8]) -> Vec<u8> {
+    build_echo(ECHO_REPLY, id, seq, payload)
+}
+
+fn build_echo(kind: u8, id: u16, seq: u16, payload: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(HEADER_LEN + payload.len());
+
+    message.extend_from_slice(&[kind, 0, 0, 0]);
+    message.extend_from_slice(&[(id >> 8) as u8, id as u8, (seq >> 8) as u8, seq as u8]);
+    message.extend_from_slice(payload);
+
+    let sum = checksum(&message);
+    message[2] = (sum >> 8) as u8;
+    message[3] = sum as u8;
+
+    message
+}
+
+/// reads the header of an ICMP message, None if it is too short to have one
+pub fn parse(message: &[u8]) -> Option<IcmpMessage> {
+    let header = message.get(..HEADER_LEN)?;
+
+    Some(IcmpMessage {
+        kind: header[0],
+        code: header[1],
+        id: u16::from(header[4]) << 8 | u16::from(header[5]),
+        seq: u16::from(header[6]) << 8 | u16::from(header[7])
+    })
+}
+
+/// whether the checksum covering message, header and payload, is correct
+pub fn checksum_ok(message: &[u8]) -> bool {
+    message.len() >= HEADER_LEN && checksum(message) == 0
+}
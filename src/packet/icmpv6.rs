@@ -0,0 +1,93 @@
+use std::net::Ipv6Addr;
+
+use pnet::datalink::MacAddr;
+
+use super::checksum;
+use super::ipv6::{
+    self,
+    NEXT_HEADER_ICMPV6
+};
+
+pub const ECHO_REQUEST: u8 = 128;
+pub const ECHO_REPLY: u8 = 129;
+pub const NEIGHBOR_SOLICITATION: u8 = 135;
+pub const NEIGHBOR_ADVERTISEMENT: u8 = 136;
+
+const OPTION_SOURCE_LINK_ADDR: u8 = 1;
+const OPTION_TARGET_LINK_ADDR: u8 = 2;
+
+/// type, code, checksum and the 4 reserved or flag bytes before a neighbor message's target
+const ND_HEADER_LEN: usize = 8;
+
+/// a neighbor advertisement, target_mac is None if it didn't carry a target link-layer address
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NeighborAdvertisement {
+    pub target: Ipv6Addr,
+    pub target_mac: Option<MacAddr>,
+    pub solicited: bool
+}
+
+/// checksum over the IPv6 pseudo header and message, with message's own checksum field zeroed
+pub fn icmpv6_checksum(src: Ipv6Addr, dst: Ipv6Addr, message: &[u8]) -> u16 {
+    let len = message.len() as u32;
+    let mut summed = Vec::with_capacity(40 + message.len());
+
+    summed.extend_from_slice(&src.octets());
+    summed.extend_from_slice(&dst.octets());
+    summed.extend_from_slice(&[(len >> 24) as u8, (len >> 16) as u8, (len >> 8) as u8, len as u8]);
+    summed.extend_from_slice(&[0, 0, 0, NEXT_HEADER_ICMPV6]);
+    summed.extend_from_slice(message);
+
+    checksum(&summed)
+}
+
+/// a solicitation asking who has target, sent from src with mac, checksum filled in for the solicited node group
+pub fn build_neighbor_solicitation(src: Ipv6Addr, mac: MacAddr, target: Ipv6Addr) -> Vec<u8> {
+    let mut message = Vec::with_capacity(ND_HEADER_LEN + 16 + 8);
+
+    message.extend_from_slice(&[NEIGHBOR_SOLICITATION, 0, 0, 0, 0, 0, 0, 0]);
+    message.extend_from_slice(&target.octets());
+    message.extend_from_slice(&[OPTION_SOURCE_LINK_ADDR, 1, mac.0, mac.1, mac.2, mac.3, mac.4, mac.5]);
+
+    let sum = icmpv6_checksum(src, ipv6::solicited_node(target), &message);
+    message[2] = (sum >> 8) as u8;
+    message[3] = sum as u8;
+
+    message
+}
+
+/// reads a neighbor advertisement out of an ICMPv6 message, None for anything else or anything malformed
+pub fn parse_neighbor_advertisement(message: &[u8]) -> Option<NeighborAdvertisement> {
+    let header = message.get(..ND_HEADER_LEN + 16)?;
+
+    if header[0] != NEIGHBOR_ADVERTISEMENT || header[1] != 0 {
+        return None;
+    }
+
+    let mut advertisement = NeighborAdvertisement {
+        target: ipv6::read_addr(&header[ND_HEADER_LEN..]),
+        target_mac: None,
+        solicited: header[4] & 0x40 != 0
+    };
+
+    // options are type, length in units of 8 bytes, then data
+    let mut options = &message[header.len()..];
+
+    while let Some(&[kind, units]) = options.get(..2) {
+        let len = usize::from(units) * 8;
+
+        let (option, rest) = match len {
+            0 => break,
+            _ if len > options.len() => break,
+            _ => options.split_at(len)
+        };
+
+        if let (OPTION_TARGET_LINK_ADDR, Some(mac)) = (kind, option.get(2..8)) {
+            advertisement.target_mac = Some(MacAddr(mac[0], mac[1], mac[2], mac[3], mac[4], mac[5]));
+        }
+
+        options = rest;
+    }
+
+    Some(advertisement)
+}
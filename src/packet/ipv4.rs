@@ -0,0 +1,151 @@
+use std::net::Ipv4Addr;
+use std::sync::atomic::{
+    AtomicUsize,
+    Ordering
+};
+
+use super::checksum;
+
+/// length of a header without options
+pub const HEADER_LEN: usize = 20;
+
+/// the more fragments flag, in the byte the flags share with the top of the fragment offset
+const MORE_FRAGMENTS: u8 = 0x20;
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// the protocol carried in an IPv4 packet
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IpProtocol {
+    Icmp,
+    Tcp,
+    Udp,
+    Other(u8)
+}
+
+impl IpProtocol {
+    pub fn from_number(number: u8) -> IpProtocol {
+        match number {
+            1 => IpProtocol::Icmp,
+            6 => IpProtocol::Tcp,
+            17 => IpProtocol::Udp,
+            other => IpProtocol::Other(other)
+        }
+    }
+
+    pub fn number(&self) -> u8 {
+        match *self {
+            IpProtocol::Icmp => 1,
+            IpProtocol::Tcp => 6,
+            IpProtocol::Udp => 17,
+            IpProtocol::Other(number) => number
+        }
+    }
+}
+
+/// the fields of a received header the modules care about
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ipv4Header {
+    pub src: Ipv4Addr,
+    pub dst: Ipv4Addr,
+    pub protocol: IpProtocol,
+    pub ttl: u8,
+    pub header_len: usize,
+    pub total_len: usize,
+    pub more_fragments: bool,
+    pub fragment_offset: usize // in bytes, the header field counts eight byte blocks
+}
+
+impl Ipv4Header {
+    /// whether the packet is only part of a datagram, the first part included
+    pub fn is_fragment(&self) -> bool {
+        self.more_fragments || self.fragment_offset != 0
+    }
+
+    /// whether the packet is a fragment past the first, whose payload starts mid datagram and so
+    /// carries no TCP, UDP or ICMP header of its own
+    pub fn is_later_fragment(&self) -> bool {
+        self.fragment_offset != 0
+    }
+}
+
+/// a 20 byte header for payload_len bytes of protocol, checksum filled in
+/// never carries options, so the header is always the fixed 20 bytes and payload follows at HEADER_LEN;
+/// the identification counts up from NEXT_ID and don't fragment is set
+pub fn build_header(src: Ipv4Addr, dst: Ipv4Addr, protocol: IpProtocol, payload_len: usize, ttl: u8) -> [u8; HEADER_LEN] {
+    let total_len = (HEADER_LEN + payload_len) as u16;
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed) as u16;
+    let mut header = [0u8; HEADER_LEN];
+
+    header[0] = 0x45; // version 4, 5 words
+    header[2] = (total_len >> 8) as u8;
+    header[3] = total_len as u8;
+    header[4] = (id >> 8) as u8;
+    header[5] = id as u8;
+    header[6] = 0x40; // don't fragment
+    header[8] = ttl;
+    header[9] = protocol.number();
+    header[12..16].copy_from_slice(&src.octets());
+    header[16..20].copy_from_slice(&dst.octets());
+
+    let sum = checksum(&header);
+    header[10] = (sum >> 8) as u8;
+    header[11] = sum as u8;
+
+    header
+}
+
+/// checksum over the IPv4 pseudo header and segment, as TCP and UDP use it, with segment's own checksum field zeroed
+pub fn pseudo_checksum(src: Ipv4Addr, dst: Ipv4Addr, protocol: IpProtocol, segment: &[u8]) -> u16 {
+    let len = segment.len() as u16;
+    let mut summed = Vec::with_capacity(12 + segment.len());
+
+    summed.extend_from_slice(&src.octets());
+    summed.extend_from_slice(&dst.octets());
+    summed.extend_from_slice(&[0, protocol.number(), (len >> 8) as u8, len as u8]);
+    summed.extend_from_slice(segment);
+
+    checksum(&summed)
+}
+
+/// splits packet into its header and payload, None if it isn't a well formed IPv4 packet
+pub fn parse(packet: &[u8]) -> Option<(Ipv4Header, &[u8])> {
+    let fixed = packet.get(..HEADER_LEN)?;
+
+    if fixed[0] >> 4 != 4 {
+        return None;
+    }
+
+    let header_len = usize::from(fixed[0] & 0x0f) * 4;
+    let total_len = usize::from(fixed[2]) << 8 | usize::from(fixed[3]);
+
+    // anything past total_len is ethernet padding
+    if header_len < HEADER_LEN || total_len < header_len {
+        return None;
+    }
+
+    let payload = packet.get(header_len..total_len)?;
+
+    let header = Ipv4Header {
+        src: Ipv4Addr::new(fixed[12], fixed[13], fixed[14], fixed[15]),
+        dst: Ipv4Addr::new(fixed[16], fixed[17], fixed[18], fixed[19]),
+        protocol: IpProtocol::from_number(fixed[9]),
+        ttl: fixed[8],
+        header_len,
+        total_len,
+        more_fragments: fixed[6] & MORE_FRAGMENTS != 0,
+        fragment_offset: (usize::from(fixed[6] & 0x1f) << 8 | usize::from(fixed[7])) * 8
+    };
+
+    Some((header, payload))
+}
+
+/// rough OS family of whoever sent a packet that arrived with ttl
+/// the ttl is rounded up to the nearest common initial value, since every hop on the way takes one off
+pub fn guess_os_from_ttl(ttl: u8) -> &'static str {
+    match ttl {
+        0..=64 => "Linux/Unix",
+        65..=128 => "Windows",
+        _ => "network device"
+    }
+}
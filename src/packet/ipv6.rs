@@ -0,0 +1,102 @@
+use std::net::Ipv6Addr;
+
+use pnet::datalink::MacAddr;
+
+/// length of the fixed header, extension headers not included
+pub const HEADER_LEN: usize = 40;
+
+pub const NEXT_HEADER_ICMPV6: u8 = 58;
+
+pub const NEXT_HEADER_FRAGMENT: u8 = 44;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ipv6Header {
+    pub src: Ipv6Addr,
+    pub dst: Ipv6Addr,
+    pub next_header: u8,
+    pub hop_limit: u8,
+    pub payload_len: usize
+}
+
+/// a fixed header for payload_len bytes of next_header, no extension headers
+pub fn build_header(src: Ipv6Addr, dst: Ipv6Addr, next_header: u8, payload_len: usize, hop_limit: u8) -> [u8; HEADER_LEN] {
+    let mut header = [0u8; HEADER_LEN];
+
+    header[0] = 0x60; // version 6, no traffic class or flow label
+    header[4] = (payload_len >> 8) as u8;
+    header[5] = payload_len as u8;
+    header[6] = next_header;
+    header[7] = hop_limit;
+    header[8..24].copy_from_slice(&src.octets());
+    header[24..40].copy_from_slice(&dst.octets());
+
+    header
+}
+
+/// splits packet into its fixed header and payload, None if it isn't a well formed IPv6 packet
+/// extension headers are left at the front of the payload, next_header names the first of them
+pub fn parse(packet: &[u8]) -> Option<(Ipv6Header, &[u8])> {
+    let header = packet.get(..HEADER_LEN)?;
+
+    if header[0] >> 4 != 6 {
+        return None;
+    }
+
+    let payload_len = usize::from(header[4]) << 8 | usize::from(header[5]);
+    let payload = packet.get(HEADER_LEN..HEADER_LEN + payload_len)?;
+
+    let header = Ipv6Header {
+        src: read_addr(&header[8..24]),
+        dst: read_addr(&header[24..40]),
+        next_header: header[6],
+        hop_limit: header[7],
+        payload_len
+    };
+
+    Some((header, payload))
+}
+
+/// whether a packet whose fixed header names next_header is part of a larger one, going by a
+/// fragment header straight after the fixed header; one further down the chain of extension headers
+/// isn't looked for, and an atomic fragment, whole in one piece, doesn't count
+pub fn is_fragment(next_header: u8, payload: &[u8]) -> bool {
+    if next_header != NEXT_HEADER_FRAGMENT {
+        return false;
+    }
+
+    // offset in the top 13 bits of bytes 2 and 3, more fragments the lowest bit
+    match payload.get(2..4) {
+        Some(field) => field[0] != 0 || field[1] & 0xf9 != 0,
+        None => false
+    }
+}
+
+pub(crate) fn read_addr(bytes: &[u8]) -> Ipv6Addr {
+    let mut octets = [0u8; 16];
+    octets.copy_from_slice(&bytes[..16]);
+    Ipv6Addr::from(octets)
+}
+
+/// fe80::/64 address a host with mac would give itself through EUI-64
+pub fn link_local_for(mac: MacAddr) -> Ipv6Addr {
+    Ipv6Addr::from([
+        0xfe, 0x80, 0, 0, 0, 0, 0, 0,
+        mac.0 ^ 0x02, mac.1, mac.2, 0xff, 0xfe, mac.3, mac.4, mac.5
+    ])
+}
+
+/// ff02::1:ffXX:XXXX, the multicast group neighbor solicitations for addr are sent to
+pub fn solicited_node(addr: Ipv6Addr) -> Ipv6Addr {
+    let octets = addr.octets();
+
+    Ipv6Addr::from([
+        0xff, 0x02, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0x01, 0xff, octets[13], octets[14], octets[15]
+    ])
+}
+
+/// the ethernet address frames for a multicast group are sent to, 33:33 then the group's low 32 bits
+pub fn multicast_mac(group: Ipv6Addr) -> MacAddr {
+    let octets = group.octets();
+    MacAddr(0x33, 0x33, octets[12], octets[13], octets[14], octets[15])
+}
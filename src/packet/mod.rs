@@ -0,0 +1,36 @@
+/*
+Raw byte builders and parsers for the protocols the built in modules speak. Everything here works on
+plain byte slices and Vec<u8> rather than typed packet structs, so the output can go straight onto
+the Framework's packet queue and the input can come straight off a PackFilter's channel. Parsers
+never trust a length they read from the wire and return None on anything truncated or malformed.
+*/
+
+pub mod arp;
+pub mod dhcp;
+pub mod ethernet;
+pub mod http;
+pub mod icmp;
+pub mod icmpv6;
+pub mod ipv4;
+pub mod ipv6;
+pub mod radiotap;
+pub mod tcp;
+pub mod udp;
+
+/// ones' complement sum of bytes as 16 bit words, as used by the IPv4, ICMP, TCP and UDP checksums
+pub fn checksum(bytes: &[u8]) -> u16 {
+    let mut sum: u32 = 0;
+
+    for word in bytes.chunks(2) {
+        // an odd trailing byte is padded with a zero
+        let high = u32::from(word[0]) << 8;
+        let low = word.get(1).map_or(0, |&byte| u32::from(byte));
+        sum += high | low;
+    }
+
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+
+    !(sum as u16)
+}
@@ -0,0 +1,28 @@
+/// radiotap header version, the only one there has ever been
+pub const VERSION: u8 = 0;
+
+/// version, padding, length and the first present word
+pub const MIN_HEADER_LEN: usize = 8;
+
+/// the length of the radiotap header at the start of frame, None if it isn't one
+pub fn header_len(frame: &[u8]) -> Option<usize> {
+    let header = frame.get(..MIN_HEADER_LEN)?;
+
+    if header[0] != VERSION {
+        return None;
+    }
+
+    // unlike everything else on the wire, radiotap is little endian
+    let len = usize::from(header[2]) | usize::from(header[3]) << 8;
+
+    if len < MIN_HEADER_LEN || len > frame.len() {
+        return None;
+    }
+
+    Some(len)
+}
+
+/// the 802.11 frame following frame's radiotap header, None if the header is malformed
+pub fn strip(frame: &[u8]) -> Option<&[u8]> {
+    header_len(frame).map(|len| &frame[len..])
+}
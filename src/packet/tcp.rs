@@ -0,0 +1,323 @@
+use std::cmp;
+use std::collections::{
+    BTreeMap,
+    HashMap
+};
+use std::net::{
+    Ipv4Addr,
+    SocketAddrV4
+};
+use std::time::{
+    Duration,
+    Instant
+};
+
+use super::ipv4::{
+    self,
+    IpProtocol
+};
+
+/// length of a header without options
+pub const HEADER_LEN: usize = 20;
+
+pub const FIN: u8 = 0x01;
+pub const SYN: u8 = 0x02;
+pub const RST: u8 = 0x04;
+pub const PSH: u8 = 0x08;
+pub const ACK: u8 = 0x10;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TcpHeader {
+    pub src_port: u16,
+    pub dst_port: u16,
+    pub seq: u32,
+    pub ack: u32,
+    pub flags: u8,
+    pub window: u16,
+    pub header_len: usize
+}
+
+/// a segment from src to dst with the flags given and no options, checksum filled in
+/// flags are sent as they are, whether they make sense together is up to the caller
+pub fn build_segment(src: SocketAddrV4, dst: SocketAddrV4, seq: u32, ack: u32, flags: u8, window: u16, payload: &[u8]) -> Vec<u8> {
+    let mut segment = vec![0u8; HEADER_LEN];
+
+    segment[0] = (src.port() >> 8) as u8;
+    segment[1] = src.port() as u8;
+    segment[2] = (dst.port() >> 8) as u8;
+    segment[3] = dst.port() as u8;
+    segment[4..8].copy_from_slice(&seq.to_be_bytes());
+    segment[8..12].copy_from_slice(&ack.to_be_bytes());
+    segment[12] = (HEADER_LEN as u8 / 4) << 4;
+    segment[13] = flags;
+    segment[14] = (window >> 8) as u8;
+    segment[15] = window as u8;
+    segment.extend_from_slice(payload);
+
+    let sum = ipv4::pseudo_checksum(*src.ip(), *dst.ip(), IpProtocol::Tcp, &segment);
+    segment[16] = (sum >> 8) as u8;
+    segment[17] = sum as u8;
+
+    segment
+}
+
+/// splits segment into its header and payload, None if the data offset doesn't fit
+pub fn parse(segment: &[u8]) -> Option<(TcpHeader, &[u8])> {
+    let header = segment.get(..HEADER_LEN)?;
+    let header_len = usize::from(header[12] >> 4) * 4;
+
+    if header_len < HEADER_LEN {
+        return None;
+    }
+
+    let payload = segment.get(header_len..)?;
+
+    let header = TcpHeader {
+        src_port: u16::from(header[0]) << 8 | u16::from(header[1]),
+        dst_port: u16::from(header[2]) << 8 | u16::from(header[3]),
+        seq: read_u32(&header[4..8]),
+        ack: read_u32(&header[8..12]),
+        flags: header[13],
+        window: u16::from(header[14]) << 8 | u16::from(header[15]),
+        header_len
+    };
+
+    Some((header, payload))
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from(bytes[0]) << 24 | u32::from(bytes[1]) << 16 | u32::from(bytes[2]) << 8 | u32::from(bytes[3])
+}
+
+/// source address and port, destination address and port, one direction of a connection
+pub type FlowKey = (Ipv4Addr, u16, Ipv4Addr, u16);
+
+/// how many flows a Reassembler follows at once, the least recently active is forgotten to make room
+pub const MAX_FLOWS: usize = 4096;
+
+/// how long a flow may go without a segment before it is forgotten
+pub const FLOW_IDLE: Duration = Duration::from_secs(60);
+
+/// one direction of a connection being put back together
+struct Flow {
+    next_seq: u32, // sequence number of the next byte that would be contiguous
+    offset: u64, // how many contiguous bytes have been seen, so pending can be keyed past wraparound
+    pending: BTreeMap<u64, Vec<u8>>, // out of order segments keyed by stream offset, never overlapping
+    ready: Vec<u8>, // contiguous bytes nobody has taken yet
+    buffered: usize,
+    place: u64, // in the Reassembler's order
+    last_seen: Instant
+}
+
+/// turns TCP segments back into the byte streams they were cut from
+/// bytes that arrive twice, from retransmits or overlapping segments, are taken from the first copy seen
+/// a flow is forgotten once it has gone FLOW_IDLE without a segment or MAX_FLOWS others have been
+/// active since, along with whatever it still held
+pub struct Reassembler {
+    flows: HashMap<FlowKey, Flow>,
+    order: BTreeMap<u64, FlowKey>, // place -> flow, least recently active first
+    next: u64,
+    max_flow_bytes: usize
+}
+
+impl Reassembler {
+    /// a flow holding max_flow_bytes that haven't been taken drops segments until some are
+    pub fn new(max_flow_bytes: usize) -> Reassembler {
+        Reassembler {
+            flows: HashMap::new(),
+            order: BTreeMap::new(),
+            next: 0,
+            max_flow_bytes
+        }
+    }
+
+    /// parses segment out of an IPv4 packet from src to dst and feeds it in, see push
+    pub fn push_segment(&mut self, src: Ipv4Addr, dst: Ipv4Addr, segment: &[u8]) -> Result<usize, String> {
+        let (header, payload) = parse(segment).ok_or_else(|| String::from("not a TCP segment"))?;
+
+        let key = (src, header.src_port, dst, header.dst_port);
+        let result = self.push(key, header.seq, header.flags & SYN != 0, payload);
+
+        // whatever was still waiting on a gap is never going to be filled
+        if header.flags & (FIN | RST) != 0 {
+            if let Some(flow) = self.flows.get_mut(&key) {
+                flow.buffered -= flow.pending.values().map(|bytes| bytes.len()).sum::<usize>();
+                flow.pending.clear();
+            }
+        }
+
+        result
+    }
+
+    /// feeds payload in at seq, returning how many bytes became contiguous because of it
+    /// Err means the flow was over its limit and the segment was dropped
+    /// a flow starts at its SYN or, when that was missed, at the first segment seen
+    pub fn push(&mut self, key: FlowKey, seq: u32, syn: bool, payload: &[u8]) -> Result<usize, String> {
+        let max_flow_bytes = self.max_flow_bytes;
+        let now = Instant::now();
+
+        self.forget_idle(now);
+
+        if !self.flows.contains_key(&key) && self.flows.len() >= MAX_FLOWS {
+            if let Some((&place, &oldest)) = self.order.iter().next() {
+                self.order.remove(&place);
+                self.flows.remove(&oldest);
+            }
+        }
+
+        let place = self.next;
+        self.next += 1;
+        self.order.insert(place, key);
+
+        let flow = self.flows.entry(key).or_insert_with(|| Flow {
+            next_seq: if syn { seq.wrapping_add(1) } else { seq },
+            offset: 0,
+            pending: BTreeMap::new(),
+            ready: Vec::new(),
+            buffered: 0,
+            place,
+            last_seen: now
+        });
+
+        if flow.place != place {
+            self.order.remove(&flow.place);
+            flow.place = place;
+        }
+
+        flow.last_seen = now;
+
+        // the SYN itself takes up a sequence number but carries no data
+        let seq = if syn { seq.wrapping_add(1) } else { seq };
+
+        if payload.is_empty() {
+            return Ok(0);
+        }
+
+        // distance from the next expected byte, read as signed so old data comes out negative
+        let ahead = i64::from(seq.wrapping_sub(flow.next_seq) as i32);
+        let start = flow.offset as i64 + ahead;
+        let end = start + payload.len() as i64;
+
+        if end <= flow.offset as i64 {
+            return Ok(0); // a pure retransmit
+        }
+
+        // anything at or before the contiguous edge has been seen already
+        let (start, payload) = if start < flow.offset as i64 {
+            (flow.offset, &payload[(flow.offset as i64 - start) as usize..])
+        } else {
+            (start as u64, payload)
+        };
+
+        let end = start + payload.len() as u64;
+
+        // the earlier copy wins where segments overlap, so only the stretches nothing pending covers are kept
+        let mut gaps = Vec::new();
+        let mut cursor = match flow.pending.range(..start).next_back() {
+            Some((&before, bytes)) => cmp::max(start, before + bytes.len() as u64),
+            None => start
+        };
+
+        for (&pending, bytes) in flow.pending.range(start..end) {
+            if pending > cursor {
+                gaps.push((cursor, pending));
+            }
+
+            cursor = cmp::max(cursor, pending + bytes.len() as u64);
+        }
+
+        if cursor < end {
+            gaps.push((cursor, end));
+        }
+
+        let fresh: u64 = gaps.iter().map(|&(from, to)| to - from).sum();
+
+        if flow.buffered + fresh as usize > max_flow_bytes {
+            return Err(format!("flow is holding {} bytes already, segment dropped", flow.buffered));
+        }
+
+        for (from, to) in gaps {
+            flow.pending.insert(from, payload[(from - start) as usize..(to - start) as usize].to_vec());
+        }
+
+        flow.buffered += fresh as usize;
+
+        Ok(flow.drain())
+    }
+
+    /// drops every flow that has gone FLOW_IDLE without a segment
+    fn forget_idle(&mut self, now: Instant) {
+        // order is by when each flow was last active, so the idle ones are all at the front
+        while let Some((&place, &oldest)) = self.order.iter().next() {
+            if now.duration_since(self.flows[&oldest].last_seen) < FLOW_IDLE {
+                break;
+            }
+
+            self.order.remove(&place);
+            self.flows.remove(&oldest);
+        }
+    }
+
+    /// every contiguous byte of the flow that hasn't been taken yet
+    pub fn take(&mut self, key: &FlowKey) -> Vec<u8> {
+        match self.flows.get_mut(key) {
+            Some(flow) => {
+                flow.buffered -= flow.ready.len();
+                flow.ready.split_off(0)
+            },
+
+            None => Vec::new()
+        }
+    }
+
+    /// forgets a flow, returning whatever contiguous bytes it still held
+    pub fn close(&mut self, key: &FlowKey) -> Vec<u8> {
+        match self.flows.remove(key) {
+            Some(flow) => {
+                self.order.remove(&flow.place);
+                flow.ready
+            },
+
+            None => Vec::new()
+        }
+    }
+
+    pub fn flows(&self) -> usize {
+        self.flows.len()
+    }
+
+    /// whether key is a flow still being followed, one closed or forgotten isn't
+    pub fn tracks(&self, key: &FlowKey) -> bool {
+        self.flows.contains_key(key)
+    }
+}
+
+impl Flow {
+    /// moves pending segments that now line up with the contiguous edge into ready
+    fn drain(&mut self) -> usize {
+        let mut drained = 0;
+
+        while let Some((&start, _)) = self.pending.iter().next() {
+            if start > self.offset {
+                break;
+            }
+
+            let segment = self.pending.remove(&start).unwrap();
+            self.buffered -= segment.len();
+
+            let seen = (self.offset - start) as usize;
+
+            if seen < segment.len() {
+                let fresh = &segment[seen..];
+
+                self.ready.extend_from_slice(fresh);
+                self.buffered += fresh.len();
+                self.offset += fresh.len() as u64;
+                self.next_seq = self.next_seq.wrapping_add(fresh.len() as u32);
+                drained += fresh.len();
+            }
+        }
+
+        drained
+    }
+}
@@ -0,0 +1,86 @@
+use std::net::Ipv4Addr;
+
+use super::ipv4::{
+    self,
+    IpProtocol
+};
+
+/// length of the source port, destination port, length and checksum fields
+pub const HEADER_LEN: usize = 8;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UdpHeader {
+    pub src_port: u16,
+    pub dst_port: u16,
+    pub length: usize
+}
+
+/// a datagram from src_ip:src_port to dst_ip:dst_port carrying payload, checksum filled in
+pub fn build_datagram(src_ip: Ipv4Addr, dst_ip: Ipv4Addr, src_port: u16, dst_port: u16, payload: &[u8]) -> Vec<u8> {
+    let length = (HEADER_LEN + payload.len()) as u16;
+    let mut datagram = Vec::with_capacity(HEADER_LEN + payload.len());
+
+    datagram.extend_from_slice(&[(src_port >> 8) as u8, src_port as u8, (dst_port >> 8) as u8, dst_port as u8]);
+    datagram.extend_from_slice(&[(length >> 8) as u8, length as u8, 0, 0]);
+    datagram.extend_from_slice(payload);
+
+    // a zero checksum means none was computed, one that comes out zero is sent as all ones instead
+    let sum = match ipv4::pseudo_checksum(src_ip, dst_ip, IpProtocol::Udp, &datagram) {
+        0 => 0xffff,
+        sum => sum
+    };
+
+    datagram[6] = (sum >> 8) as u8;
+    datagram[7] = sum as u8;
+
+    datagram
+}
+
+/// splits datagram into its header and payload, None if the length field doesn't fit
+pub fn parse(datagram: &[u8]) -> Option<(UdpHeader, &[u8])> {
+    let header = datagram.get(..HEADER_LEN)?;
+    let length = usize::from(header[4]) << 8 | usize::from(header[5]);
+
+    if length < HEADER_LEN {
+        return None;
+    }
+
+    let payload = datagram.get(HEADER_LEN..length)?;
+
+    let header = UdpHeader {
+        src_port: u16::from(header[0]) << 8 | u16::from(header[1]),
+        dst_port: u16::from(header[2]) << 8 | u16::from(header[3]),
+        length
+    };
+
+    Some((header, payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::{
+        build_datagram,
+        parse
+    };
+
+    #[test]
+    fn matches_a_reference_datagram() {
+        let datagram = build_datagram(Ipv4Addr::new(192, 168, 0, 31), Ipv4Addr::new(192, 168, 0, 30), 20, 10, b"Hi");
+
+        assert_eq!(datagram, vec![0x00, 0x14, 0x00, 0x0a, 0x00, 0x0a, 0x35, 0xc5, b'H', b'i']);
+
+        let (header, payload) = parse(&datagram).unwrap();
+        assert_eq!((header.src_port, header.dst_port, header.length), (20, 10, 10));
+        assert_eq!(payload, b"Hi");
+    }
+
+    #[test]
+    fn a_zero_checksum_goes_out_as_all_ones() {
+        // the one payload word that brings the reference datagram's sum to exactly 0xffff
+        let datagram = build_datagram(Ipv4Addr::new(192, 168, 0, 31), Ipv4Addr::new(192, 168, 0, 30), 20, 10, &[0x7e, 0x2e]);
+
+        assert_eq!(&datagram[6..8], &[0xff, 0xff]);
+    }
+}
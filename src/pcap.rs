@@ -0,0 +1,210 @@
+/*
+Replaying capture files. PcapReader reads the classic libpcap format (not pcapng), in either byte
+order and with microsecond or nanosecond timestamps, and is a PacketSource, so a recorded capture
+can be fed to Framework::start_capture in place of an interface. Only ethernet captures are
+accepted since that is all the dispatcher understands.
+
+How fast frames come out is up to the reader's ReplaySpeed. Unthrottled hands them over as fast as
+the dispatcher takes them, which is what load testing module throughput wants. RealTime and
+Multiplier space them out by their recorded timestamps, scaled down by the multiplier, measured from
+when the first frame was read rather than from the previous one so that time spent dispatching
+doesn't add up into drift. A long gap in the capture is waited out a slice at a time, reporting a
+timeout in between, so the capture loop can still be stopped during it.
+*/
+
+use std::fs::File;
+use std::io::{
+    self,
+    BufReader,
+    Read
+};
+use std::path::Path;
+use std::thread;
+use std::time::{
+    Duration,
+    Instant
+};
+
+use dispatch::PacketSource;
+
+const MAGIC_MICROS: u32 = 0xa1b2_c3d4;
+const MAGIC_NANOS: u32 = 0xa1b2_3c4d;
+const LINKTYPE_ETHERNET: u32 = 1;
+
+const GLOBAL_HEADER_LEN: usize = 24;
+const RECORD_HEADER_LEN: usize = 16;
+
+/// largest record accepted, anything longer is taken to be a corrupt file
+const MAX_RECORD_LEN: usize = 256 * 1024;
+
+/// longest a single next_frame call sleeps before giving the capture loop a timeout
+const MAX_WAIT: Duration = Duration::from_millis(100);
+
+/// slowest multiplier parse accepts, anything slower replays a one second gap over more than eleven days
+pub const MIN_MULTIPLIER: f64 = 1e-6;
+
+/// how a PcapReader paces the frames it replays
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ReplaySpeed {
+    RealTime, // as far apart as they were recorded
+    Multiplier(f64), // that many times faster than recorded, 0.5 for half speed
+    #[default]
+    Unthrottled // as fast as they can be read
+}
+
+impl ReplaySpeed {
+    /// `realtime`, `max` for Unthrottled, or a multiplier like `5` or `0.5`, as given to `replay file --speed`
+    pub fn parse(speed: &str) -> Result<ReplaySpeed, String> {
+        match speed {
+            "realtime" => Ok(ReplaySpeed::RealTime),
+            "max" => Ok(ReplaySpeed::Unthrottled),
+
+            _ => match speed.parse::<f64>() {
+                Ok(multiplier) if multiplier >= MIN_MULTIPLIER && multiplier.is_finite() => Ok(ReplaySpeed::Multiplier(multiplier)),
+                _ => Err(format!("{}: not a speed, expected realtime, max or a multiplier of at least {}", speed, MIN_MULTIPLIER))
+            }
+        }
+    }
+
+    /// how much faster than recorded frames go out, None when they aren't timed at all
+    fn multiplier(&self) -> Option<f64> {
+        match *self {
+            ReplaySpeed::RealTime => Some(1.0),
+            ReplaySpeed::Multiplier(multiplier) => Some(multiplier),
+            ReplaySpeed::Unthrottled => None
+        }
+    }
+}
+
+/// the frames of a pcap capture, in the order they were recorded
+pub struct PcapReader<R: Read + Send> {
+    reader: R,
+    swapped: bool, // written on a machine of the other byte order
+    nanos: bool, // timestamps are in nanoseconds rather than microseconds
+    speed: ReplaySpeed,
+    frame: Vec<u8>,
+    due: Option<Duration>, // when the frame in frame was recorded, relative to the first, while it waits to go out
+    first: Option<(Duration, Instant)>, // the first frame's timestamp and when it was read
+    frames: u64
+}
+
+impl PcapReader<BufReader<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<PcapReader<BufReader<File>>> {
+        PcapReader::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read + Send> PcapReader<R> {
+    /// reads the file header off reader, failing if it isn't an ethernet pcap capture
+    pub fn new(mut reader: R) -> io::Result<PcapReader<R>> {
+        let mut header = [0u8; GLOBAL_HEADER_LEN];
+        reader.read_exact(&mut header)?;
+
+        let magic = read_u32(&header[..4], false);
+
+        let (swapped, nanos) = match (magic, magic.swap_bytes()) {
+            (MAGIC_MICROS, _) => (false, false),
+            (MAGIC_NANOS, _) => (false, true),
+            (_, MAGIC_MICROS) => (true, false),
+            (_, MAGIC_NANOS) => (true, true),
+            _ => return Err(invalid("not a pcap file (pcapng isn't supported)"))
+        };
+
+        let linktype = read_u32(&header[20..], swapped);
+
+        if linktype != LINKTYPE_ETHERNET {
+            return Err(invalid(&format!("link type {} isn't ethernet", linktype)));
+        }
+
+        Ok(PcapReader {
+            reader,
+            swapped,
+            nanos,
+            speed: ReplaySpeed::default(),
+            frame: Vec::new(),
+            due: None,
+            first: None,
+            frames: 0
+        })
+    }
+
+    pub fn speed(mut self, speed: ReplaySpeed) -> PcapReader<R> {
+        self.speed = speed;
+        self
+    }
+
+    /// frames handed out so far
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// reads the next record into frame, returning its timestamp
+    fn read_record(&mut self) -> io::Result<Duration> {
+        let mut header = [0u8; RECORD_HEADER_LEN];
+        self.reader.read_exact(&mut header)?;
+
+        let secs = read_u32(&header[..4], self.swapped);
+        let fraction = read_u32(&header[4..8], self.swapped);
+        let len = read_u32(&header[8..12], self.swapped) as usize;
+
+        if len > MAX_RECORD_LEN {
+            return Err(invalid(&format!("record of {} bytes, the file is likely corrupt", len)));
+        }
+
+        self.frame.resize(len, 0);
+        self.reader.read_exact(&mut self.frame)?;
+
+        let nanos = if self.nanos { fraction } else { fraction.saturating_mul(1000) };
+        Ok(Duration::new(u64::from(secs), 0) + Duration::from_nanos(u64::from(nanos)))
+    }
+}
+
+impl<R: Read + Send> PacketSource for PcapReader<R> {
+    fn next_frame(&mut self) -> io::Result<&[u8]> {
+        let due = match self.due {
+            Some(due) => due,
+
+            None => {
+                let recorded = self.read_record()?;
+                let &mut (first, _) = self.first.get_or_insert((recorded, Instant::now()));
+
+                // a timestamp earlier than the first frame's goes out straight away
+                let due = recorded.checked_sub(first).unwrap_or_default();
+                self.due = Some(due);
+                due
+            }
+        };
+
+        if let (Some(multiplier), Some((_, started))) = (self.speed.multiplier(), self.first) {
+            // a Multiplier built by hand can still be small enough to overflow a Duration, so that saturates
+            let due = Duration::try_from_secs_f64(due.as_secs_f64() / multiplier).unwrap_or(Duration::MAX);
+            let wait = due.checked_sub(started.elapsed()).unwrap_or_default();
+
+            if wait > MAX_WAIT {
+                thread::sleep(MAX_WAIT);
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "waiting for the next frame's time"));
+            }
+
+            thread::sleep(wait);
+        }
+
+        self.due = None;
+        self.frames += 1;
+        Ok(&self.frame)
+    }
+}
+
+/// little endian unless the file's magic said otherwise
+fn read_u32(bytes: &[u8], swapped: bool) -> u32 {
+    let value = u32::from(bytes[3]) << 24 | u32::from(bytes[2]) << 16 | u32::from(bytes[1]) << 8 | u32::from(bytes[0]);
+
+    if swapped {
+        value.swap_bytes()
+    } else {
+        value
+    }
+}
+
+fn invalid(reason: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason)
+}
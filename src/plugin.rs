@@ -0,0 +1,90 @@
+/*
+What a plugin dylib has to export for Framework::load_hooks_from to accept it. Hook is a plain Rust
+enum of fn pointers with no stable layout, so a plugin built against a different version of this
+crate can't safely be called into at all. Every plugin therefore exports abi_version() alongside
+load(), and the loader refuses the library before calling load() if the two versions differ.
+
+Hooks are handed a HookEnv and a Framework or HostMgr, and return a Module, so RUSTNEEDLE_ABI is
+bumped by every change to Hook, HookEnv (FrameworkConfig and everything else it carries included),
+Module, Framework or HostMgr (the host tables behind it included), not only by changes to this file. Forgetting to is how a stale plugin gets
+called with a struct laid out differently from the one it was built against.
+
+A plugin may also export plugin_info(), a PluginMeta naming it, which the loader copies into the
+PluginInfo listed by Framework::plugins. Plugins shouldn't write any of these symbols by hand,
+rustneedle_plugin! writes them all, taking the plugin's name, author and version from its Cargo.toml
+unless they are given:
+
+    rustneedle_plugin! {
+        "scan" => Hook::HostMgr(scan),
+        "spoof" => Hook::Framework(spoof)
+    }
+
+    rustneedle_plugin! {
+        name: "scanners", author: "someone", version: "2.0.0";
+        "scan" => Hook::HostMgr(scan)
+    }
+*/
+
+/// bumped whenever a change would break plugins built against an older version, see the header above
+pub const RUSTNEEDLE_ABI: u32 = 5;
+
+pub(crate) type AbiVersion = unsafe fn() -> u32;
+pub(crate) type InfoLoader = unsafe fn() -> PluginMeta;
+
+/// what a plugin says about itself, exported through plugin_info()
+pub struct PluginMeta {
+    pub name: &'static str,
+    pub author: &'static str,
+    pub version: &'static str
+}
+
+/// a loaded plugin, its metadata copied out so it can't outlive the library it came from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PluginInfo {
+    pub library: usize, // index for Framework::unload_library
+    pub name: String,
+    pub author: String,
+    pub version: String,
+    pub hooks: Vec<String> // the hooks it registered, sorted
+}
+
+/// what Framework::reload_library changed, each list sorted
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LibraryReload {
+    pub library: usize, // the index the new build was loaded at, the old index stays unloaded
+    pub kept: Vec<String>, // hooks both builds register
+    pub added: Vec<String>, // only in the new build
+    pub removed: Vec<String> // only in the old build, and gone now
+}
+
+/// exports load(), abi_version() and plugin_info() for a plugin, given its hooks as `"name" => hook` pairs
+#[macro_export]
+macro_rules! rustneedle_plugin {
+    (name: $plugin:expr, author: $author:expr, version: $version:expr; $($name:expr => $hook:expr),* $(,)*) => {
+        #[no_mangle]
+        pub fn abi_version() -> u32 {
+            $crate::RUSTNEEDLE_ABI
+        }
+
+        #[no_mangle]
+        pub fn plugin_info() -> $crate::PluginMeta {
+            $crate::PluginMeta {
+                name: $plugin,
+                author: $author,
+                version: $version
+            }
+        }
+
+        #[no_mangle]
+        pub fn load() -> Vec<(&'static str, $crate::Hook)> {
+            vec![$(($name, $hook)),*]
+        }
+    };
+
+    ($($name:expr => $hook:expr),* $(,)*) => {
+        $crate::rustneedle_plugin! {
+            name: env!("CARGO_PKG_NAME"), author: env!("CARGO_PKG_AUTHORS"), version: env!("CARGO_PKG_VERSION");
+            $($name => $hook),*
+        }
+    };
+}
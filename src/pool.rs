@@ -0,0 +1,139 @@
+/*
+A shared set of worker threads that lightweight modules can run on instead of each getting a
+dedicated thread. Which hooks' modules go on the pool is up to the Framework, not the hook: a hook
+builds its module with Module::deferred, and Framework::pool_hook marks the hooks whose deferred
+modules are started on the pool, any hook kind alike; the rest get a thread each. A hook can also
+spawn onto Framework::module_pool() itself. Either way the Module is killed and joined exactly like
+any other.
+
+The tradeoff is that a pooled module occupies its worker for as long as it runs. Pooled modules
+should be short-lived or should wake up regularly (recv_timeout on their killer, for instance) and
+return once killed. A pooled module that blocks indefinitely starves every module queued behind it,
+so anything that sits on a blocking read belongs on its own thread.
+*/
+
+use std::panic::{
+    self,
+    AssertUnwindSafe
+};
+use std::sync::{
+    Arc,
+    Mutex,
+    Condvar,
+    mpsc::channel,
+    mpsc::Receiver,
+    mpsc::Sender
+};
+use std::thread;
+
+use super::{
+    Module,
+    ModuleOutcome
+};
+
+type Task = Box<dyn FnOnce() + Send>;
+
+/// holds a pooled module's result until someone joins it
+pub(crate) struct TaskSlot {
+    result: Mutex<Option<Result<ModuleOutcome, String>>>,
+    done: Condvar
+}
+
+impl TaskSlot {
+    fn new() -> TaskSlot {
+        TaskSlot {
+            result: Mutex::new(None),
+            done: Condvar::new()
+        }
+    }
+
+    fn finish(&self, result: Result<ModuleOutcome, String>) {
+        *self.result.lock().unwrap() = Some(result);
+        self.done.notify_all();
+    }
+
+    pub(crate) fn is_done(&self) -> bool {
+        self.result.lock().unwrap().is_some()
+    }
+
+    pub(crate) fn wait(&self) -> Result<ModuleOutcome, String> {
+        let mut result = self.result.lock().unwrap();
+
+        loop {
+            match result.take() {
+                Some(result) => return result,
+                None => result = self.done.wait(result).unwrap()
+            }
+        }
+    }
+}
+
+/// fixed size set of worker threads that run modules as tasks
+pub struct ModulePool {
+    tasks: Sender<Task>,
+    workers: usize
+}
+
+impl ModulePool {
+    pub fn new(workers: usize) -> ModulePool {
+        let workers = workers.max(1);
+        let (tasks, queue) = channel::<Task>();
+        let queue = Arc::new(Mutex::new(queue));
+
+        for _ in 0..workers {
+            let queue = queue.clone();
+
+            thread::spawn(move || loop {
+                // the lock is released as soon as a task is taken so other workers can pick up the next
+                let task = match queue.lock().unwrap().recv() {
+                    Ok(task) => task,
+                    Err(_) => break // pool dropped
+                };
+
+                task();
+            });
+        }
+
+        ModulePool {
+            tasks,
+            workers
+        }
+    }
+
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    /// queues task on the pool, handing it the receiving end of the returned module's killer
+    pub fn spawn<F>(&self, task: F) -> Module
+        where F: FnOnce(Receiver<()>) -> Result<ModuleOutcome, String> + Send + 'static
+    {
+        let (killer, killed) = channel();
+        Module::pooled(self.start(task, killed), killer)
+    }
+
+    /// queues task on the pool with killed, returning the slot its result ends up in
+    pub(crate) fn start<F>(&self, task: F, killed: Receiver<()>) -> Arc<TaskSlot>
+        where F: FnOnce(Receiver<()>) -> Result<ModuleOutcome, String> + Send + 'static
+    {
+        let slot = Arc::new(TaskSlot::new());
+        let task_slot = slot.clone();
+
+        let job: Task = Box::new(move || {
+            // catching the panic keeps the worker alive for the rest of the pool
+            let result = match panic::catch_unwind(AssertUnwindSafe(move || task(killed))) {
+                Ok(result) => result,
+                Err(_) => Err(String::from("module task panicked"))
+            };
+
+            task_slot.finish(result);
+        });
+
+        if let Err(unsent) = self.tasks.send(job) {
+            // every worker is gone, so run the task here rather than lose it
+            (unsent.0)();
+        }
+
+        slot
+    }
+}
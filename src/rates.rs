@@ -0,0 +1,138 @@
+/*
+Per-host traffic rates, kept beside a HostMgr's host table rather than in it so that counting a frame
+never waits on a scan holding the table. Each host gets a ring of one second buckets covering the
+last RATE_WINDOW_SECS seconds; recording clears whatever buckets have gone stale since the host was
+last seen, so a host that goes quiet decays to nothing instead of keeping its old rate forever.
+Nothing counts on its own: the talkers builtin is what feeds every frame's source address in.
+*/
+
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+use std::time::Instant;
+
+/// seconds of traffic a rate is averaged over
+pub const RATE_WINDOW_SECS: u64 = 10;
+
+const BUCKETS: usize = RATE_WINDOW_SECS as usize;
+
+/// packets and bytes one host sent in each of the last RATE_WINDOW_SECS seconds
+struct Counter {
+    packets: [u64; BUCKETS],
+    bytes: [u64; BUCKETS],
+    last: u64 // the second the newest bucket belongs to
+}
+
+impl Counter {
+    fn new(now: u64) -> Counter {
+        Counter {
+            packets: [0; BUCKETS],
+            bytes: [0; BUCKETS],
+            last: now
+        }
+    }
+
+    /// clears the buckets of every second between the last one recorded and now
+    fn advance(&mut self, now: u64) {
+        if now <= self.last {
+            return;
+        }
+
+        if now - self.last >= RATE_WINDOW_SECS {
+            self.packets = [0; BUCKETS];
+            self.bytes = [0; BUCKETS];
+        } else {
+            for second in self.last + 1..=now {
+                let bucket = (second % RATE_WINDOW_SECS) as usize;
+                self.packets[bucket] = 0;
+                self.bytes[bucket] = 0;
+            }
+        }
+
+        self.last = now;
+    }
+
+    /// packets and bytes across the window ending at now
+    fn totals(&self, now: u64) -> (u64, u64) {
+        let oldest = (now + 1).saturating_sub(RATE_WINDOW_SECS);
+
+        if self.last < oldest {
+            return (0, 0);
+        }
+
+        // nothing newer than last was ever recorded, and advance cleared any bucket it reused
+        (oldest..=self.last)
+            .map(|second| (second % RATE_WINDOW_SECS) as usize)
+            .fold((0, 0), |(packets, bytes), bucket| (packets + self.packets[bucket], bytes + self.bytes[bucket]))
+    }
+}
+
+/// time-windowed packet and byte counts for every host recently seen sending
+pub struct HostRates {
+    started: Instant,
+    counters: HashMap<Ipv4Addr, Counter>
+}
+
+impl HostRates {
+    pub fn new() -> HostRates {
+        HostRates {
+            started: Instant::now(),
+            counters: HashMap::new()
+        }
+    }
+
+    fn now(&self) -> u64 {
+        self.started.elapsed().as_secs()
+    }
+
+    /// counts one packet of len bytes from host
+    pub fn record(&mut self, host: Ipv4Addr, len: usize) {
+        let now = self.now();
+        let counter = self.counters.entry(host).or_insert_with(|| Counter::new(now));
+        let bucket = (now % RATE_WINDOW_SECS) as usize;
+
+        counter.advance(now);
+        counter.packets[bucket] += 1;
+        counter.bytes[bucket] += len as u64;
+    }
+
+    /// packets per second from each host over the window, busiest first, rounded down
+    /// hosts quiet for the whole window are forgotten
+    pub fn packet_rates(&mut self) -> Vec<(Ipv4Addr, u64)> {
+        self.rates(|(packets, _)| packets)
+    }
+
+    /// bytes per second from each host over the window, busiest first
+    pub fn byte_rates(&mut self) -> Vec<(Ipv4Addr, u64)> {
+        self.rates(|(_, bytes)| bytes)
+    }
+
+    pub fn len(&self) -> usize {
+        self.counters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counters.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.counters.clear();
+    }
+
+    fn rates<F: Fn((u64, u64)) -> u64>(&mut self, pick: F) -> Vec<(Ipv4Addr, u64)> {
+        let now = self.now();
+        self.counters.retain(|_, counter| counter.totals(now) != (0, 0));
+
+        let mut rates: Vec<(Ipv4Addr, u64)> = self.counters.iter()
+            .map(|(&host, counter)| (host, pick(counter.totals(now)) / RATE_WINDOW_SECS))
+            .collect();
+
+        rates.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        rates
+    }
+}
+
+impl Default for HostRates {
+    fn default() -> HostRates {
+        HostRates::new()
+    }
+}
@@ -0,0 +1,72 @@
+/*
+The one source of randomness everything in the crate draws from, so a run can be repeated exactly.
+With FrameworkConfig::rng_seed set, the Framework seeds a generator from it and hands every hook a
+generator split off that one in its HookEnv, in the order hooks are run, so the same seed and the
+same commands give the same scan orders and jitter every time; recording the seed in a report makes
+the run reproducible. Without the seed, the Framework's generator is seeded from the clock and the
+process id instead. Rng is xorshift64*, plenty for shuffling probes and not meant for anything more.
+*/
+
+use std::process;
+use std::time::{
+    SystemTime,
+    UNIX_EPOCH
+};
+
+#[derive(Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    /// the same seed always gives the same sequence
+    pub fn seeded(seed: u64) -> Rng {
+        // splitmix64, spreading seeds like 0 and 1 apart and keeping the state off zero
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+        Rng((z ^ (z >> 31)) | 1)
+    }
+
+    /// a different sequence every run
+    pub fn from_entropy() -> Rng {
+        let now = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(now) => now.as_secs() ^ u64::from(now.subsec_nanos()) << 32,
+            Err(_) => 0
+        };
+
+        Rng::seeded(now ^ u64::from(process::id()))
+    }
+
+    /// seeded from seed if there is one, from_entropy otherwise
+    pub fn from_seed(seed: Option<u64>) -> Rng {
+        match seed {
+            Some(seed) => Rng::seeded(seed),
+            None => Rng::from_entropy()
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// roughly uniform in 0..bound, bound must not be zero
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    /// items in a random order, fisher-yates
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+
+    /// a generator of its own, seeded from this one so it stays reproducible
+    pub fn split(&mut self) -> Rng {
+        Rng::seeded(self.next_u64())
+    }
+}
@@ -0,0 +1,92 @@
+use std::net::Ipv4Addr;
+
+/// an IPv4 network in a.b.c.d/nn form
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cidr {
+    pub addr: Ipv4Addr,
+    pub prefix: u8
+}
+
+impl Cidr {
+    pub fn new(addr: Ipv4Addr, prefix: u8) -> Result<Cidr, String> {
+        if prefix > 32 {
+            Err(format!("/{}: prefix is longer than 32 bits", prefix))
+        } else {
+            Ok(Cidr {
+                addr,
+                prefix
+            })
+        }
+    }
+
+    /// a bare address is taken as a /32
+    pub fn parse(cidr: &str) -> Result<Cidr, String> {
+        let mut parts = cidr.splitn(2, '/');
+
+        let addr = match parts.next().unwrap_or("").parse::<Ipv4Addr>() {
+            Ok(addr) => addr,
+            Err(_) => return Err(format!("{}: not an IPv4 address", cidr))
+        };
+
+        let prefix = match parts.next() {
+            Some(prefix) => match prefix.parse::<u8>() {
+                Ok(prefix) => prefix,
+                Err(_) => return Err(format!("{}: not a prefix length", cidr))
+            },
+
+            None => 32
+        };
+
+        Cidr::new(addr, prefix)
+    }
+
+    pub fn contains(&self, ip: Ipv4Addr) -> bool {
+        same_subnet(self.addr, ip, self.prefix)
+    }
+}
+
+/// the mask for a prefix, 0.0.0.0 for /0
+pub fn netmask(prefix: u8) -> Ipv4Addr {
+    Ipv4Addr::from(mask_bits(prefix))
+}
+
+pub fn network_address(ip: Ipv4Addr, prefix: u8) -> Ipv4Addr {
+    Ipv4Addr::from(u32::from(ip) & mask_bits(prefix))
+}
+
+pub fn broadcast_address(ip: Ipv4Addr, prefix: u8) -> Ipv4Addr {
+    Ipv4Addr::from(u32::from(ip) | !mask_bits(prefix))
+}
+
+/// whether a and b fall in the same /prefix, any two addresses do on a /0
+pub fn same_subnet(a: Ipv4Addr, b: Ipv4Addr, prefix: u8) -> bool {
+    network_address(a, prefix) == network_address(b, prefix)
+}
+
+/// every address worth probing in cidr
+/// the network and broadcast addresses are left out, except on a /31 or /32 where there are none
+pub fn hosts_in(cidr: Cidr) -> impl Iterator<Item = Ipv4Addr> {
+    let first = u32::from(network_address(cidr.addr, cidr.prefix));
+    let last = u32::from(broadcast_address(cidr.addr, cidr.prefix));
+
+    let (first, last) = if cidr.prefix >= 31 {
+        (first, last)
+    } else {
+        (first + 1, last - 1)
+    };
+
+    (first..=last).map(Ipv4Addr::from)
+}
+
+/// every address from start to end, both included, none if end comes before start
+/// unlike hosts_in nothing is left out, a range has no network or broadcast address of its own
+pub fn range(start: Ipv4Addr, end: Ipv4Addr) -> impl Iterator<Item = Ipv4Addr> {
+    (u32::from(start)..=u32::from(end)).map(Ipv4Addr::from)
+}
+
+fn mask_bits(prefix: u8) -> u32 {
+    match prefix {
+        0 => 0,
+        prefix => !0u32 << (32 - u32::from(prefix.min(32)))
+    }
+}
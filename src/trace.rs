@@ -0,0 +1,109 @@
+/*
+Structured diagnostics through the tracing crate, compiled in with the `tracing` feature. Each module
+the Framework starts gets a span named after it that the frames delivered to it, the frames it drops
+while paused and its exit are recorded under, and the capture and send loops emit events of their
+own, so any tracing subscriber the program installs sees them. Without the feature every function
+here is empty and ModuleSpan is zero sized, so none of it costs anything.
+*/
+
+#[cfg(feature = "tracing")]
+use tracing;
+
+use ModuleOutcome;
+
+/// the span a module's activity is recorded under
+#[cfg(feature = "tracing")]
+#[derive(Clone)]
+pub(crate) struct ModuleSpan(tracing::Span);
+
+#[cfg(not(feature = "tracing"))]
+#[derive(Clone)]
+pub(crate) struct ModuleSpan;
+
+#[cfg(feature = "tracing")]
+impl ModuleSpan {
+    pub(crate) fn new(name: &str, hook: &str, interface: &str) -> ModuleSpan {
+        ModuleSpan(tracing::info_span!("module", name = name, hook = hook, interface = interface))
+    }
+
+    /// for subscriptions that don't belong to a module
+    pub(crate) fn none() -> ModuleSpan {
+        ModuleSpan(tracing::Span::none())
+    }
+
+    pub(crate) fn started(&self) {
+        tracing::info!(parent: &self.0, "started");
+    }
+
+    pub(crate) fn delivered(&self, bytes: usize) {
+        tracing::trace!(parent: &self.0, bytes = bytes as u64, "frame delivered");
+    }
+
+    pub(crate) fn dropped_paused(&self, bytes: usize) {
+        tracing::trace!(parent: &self.0, bytes = bytes as u64, "frame dropped while paused");
+    }
+
+    pub(crate) fn disconnected(&self) {
+        tracing::warn!(parent: &self.0, "filter receiver hung up");
+    }
+
+    pub(crate) fn exited(&self, result: &Result<ModuleOutcome, String>) {
+        match *result {
+            Ok(ref outcome) => tracing::info!(parent: &self.0, entries = outcome.len() as u64, "exited"),
+            Err(ref err) => tracing::error!(parent: &self.0, error = err.as_str(), "exited with an error")
+        }
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+impl ModuleSpan {
+    pub(crate) fn new(_name: &str, _hook: &str, _interface: &str) -> ModuleSpan {
+        ModuleSpan
+    }
+
+    pub(crate) fn none() -> ModuleSpan {
+        ModuleSpan
+    }
+
+    pub(crate) fn started(&self) {}
+
+    pub(crate) fn delivered(&self, _bytes: usize) {}
+
+    pub(crate) fn dropped_paused(&self, _bytes: usize) {}
+
+    pub(crate) fn disconnected(&self) {}
+
+    pub(crate) fn exited(&self, _result: &Result<ModuleOutcome, String>) {}
+}
+
+pub(crate) fn frame_received(interface: &str, bytes: usize, deliveries: usize) {
+    #[cfg(feature = "tracing")]
+    tracing::trace!(interface = interface, bytes = bytes as u64, deliveries = deliveries as u64, "frame received");
+
+    #[cfg(not(feature = "tracing"))]
+    let _ = (interface, bytes, deliveries);
+}
+
+pub(crate) fn frame_sent(bytes: usize) {
+    #[cfg(feature = "tracing")]
+    tracing::trace!(bytes = bytes as u64, "frame sent");
+
+    #[cfg(not(feature = "tracing"))]
+    let _ = bytes;
+}
+
+pub(crate) fn frame_suppressed(bytes: usize) {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(bytes = bytes as u64, "frame suppressed by dry run");
+
+    #[cfg(not(feature = "tracing"))]
+    let _ = bytes;
+}
+
+pub(crate) fn send_failed(bytes: usize, err: &::std::io::Error) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(bytes = bytes as u64, error = %err, "send failed");
+
+    #[cfg(not(feature = "tracing"))]
+    let _ = (bytes, err);
+}